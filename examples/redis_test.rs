@@ -1,21 +1,21 @@
-/// Example test for Redis client
-/// Run with: cargo run --example redis_test
-///
-/// Make sure Redis is running on localhost:6379
+//! Example test for Redis client
+//! Run with: cargo run --example redis_test
+//!
+//! Make sure Redis from the active settings profile is running
 
-use api_gateway::core::configure::app::AppConfig;
+use api_gateway::infrastructure::constant::CONFIG;
 use api_gateway::infrastructure::persistence::redis_client::RedisConnectionPool;
 use std::time::Duration;
+use utils::telemetry::init_telemetry;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::init();
-
     println!("Loading configuration...");
-    let config = AppConfig::from_env()?;
+    let config = CONFIG.clone();
+    let _telemetry = init_telemetry("redis-test", &config.telemetry)?;
 
     println!("Connecting to Redis at {}...", config.redis.get_url());
-    let redis = RedisConnectionPool::new(&config).await?;
+    let redis = RedisConnectionPool::new(&config.redis.get_url()).await?;
 
     // Test 1: Ping
     println!("\n=== Test 1: Ping ===");
@@ -31,30 +31,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("✓ Get key 'test:key' = {:?}", value);
     assert_eq!(value, Some("test_value".to_string()));
 
-    // Test 3: TTL
-    println!("\n=== Test 3: TTL ===");
-    let ttl = redis.ttl("test:key").await?;
-    println!("✓ TTL for 'test:key' = {} seconds", ttl);
-    assert!(ttl > 0 && ttl <= 60);
-
-    // Test 4: Exists
-    println!("\n=== Test 4: Exists ===");
-    let exists = redis.exists("test:key").await?;
-    println!("✓ Key 'test:key' exists: {}", exists);
-    assert!(exists);
-
-    // Test 5: Delete
-    println!("\n=== Test 5: Delete ===");
-    let deleted = redis.delete("test:key").await?;
+    // Test 3: Delete
+    println!("\n=== Test 3: Delete ===");
+    let deleted = redis.delete_key("test:key").await?;
     println!("✓ Deleted 'test:key': {}", deleted);
     assert!(deleted);
 
-    let exists_after = redis.exists("test:key").await?;
-    println!("✓ Key 'test:key' exists after delete: {}", exists_after);
-    assert!(!exists_after);
+    let value_after = redis.get("test:key").await?;
+    println!("✓ Get 'test:key' after delete = {:?}", value_after);
+    assert_eq!(value_after, None);
 
-    // Test 6: JSON serialization
-    println!("\n=== Test 6: JSON Serialization ===");
+    // Test 4: JSON serialization
+    println!("\n=== Test 4: JSON Serialization ===");
     let json_value = serde_json::json!({
         "name": "John Doe",
         "age": 30,
@@ -76,11 +64,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("✓ Retrieved and deserialized: {:?}", person);
     assert_eq!(person.name, "John Doe");
     assert_eq!(person.age, 30);
-    assert_eq!(person.active, true);
+    assert!(person.active);
 
-    // Test 7: Key prefix
-    println!("\n=== Test 7: Key Prefix ===");
-    let redis_prefixed = redis.clone_with_prefix("myapp");
+    // Test 5: Key prefix
+    println!("\n=== Test 5: Key Prefix ===");
+    let redis_prefixed = redis.with_prefix("myapp");
     redis_prefixed.set("user:1", "Alice", Duration::from_secs(60)).await?;
     println!("✓ Set prefixed key 'myapp:user:1'");
 
@@ -94,8 +82,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     assert_eq!(direct_value, Some("Alice".to_string()));
 
     // Cleanup
-    redis.delete("myapp:user:1").await?;
-    redis.delete("test:json").await?;
+    redis.delete_key("myapp:user:1").await?;
+    redis.delete_key("test:json").await?;
 
     println!("\n✅ All tests passed!");
 
@@ -1,2 +1,3 @@
+pub mod address_event_handler;
 pub mod address_service;
 pub mod address_service_interface;
@@ -0,0 +1,17 @@
+use crate::application::address::address_event_handler::{
+    AddressAddedHandler, AddressRemovedHandler, AddressUpdatedHandler,
+};
+use crate::domain::address::events::address_added::AddressAddedEvent;
+use crate::domain::address::events::address_removed::AddressRemovedEvent;
+use crate::domain::address::events::address_updated::AddressUpdatedEvent;
+use utils::events::DomainEvent;
+use utils::kafka_consumer::Projection;
+
+/// Projections `order-service replay --projection <name>` can rebuild
+pub fn projections() -> Vec<Projection> {
+    vec![Projection::new("addresses")
+        .register(AddressAddedEvent::TOPIC, AddressAddedEvent::EVENT_TYPE, AddressAddedHandler)
+        .register(AddressUpdatedEvent::TOPIC, AddressUpdatedEvent::EVENT_TYPE, AddressUpdatedHandler)
+        .register(AddressRemovedEvent::TOPIC, AddressRemovedEvent::EVENT_TYPE, AddressRemovedHandler)]
+}
+
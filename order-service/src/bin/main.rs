@@ -1,19 +1,71 @@
-use argon2::password_hash::SaltString;
-use argon2::{Argon2, PasswordHasher};
-use log::{error, info, LevelFilter};
-use rand::rngs::OsRng;
-use order_service::core::error::AppResult;
+use clap::{Parser, Subcommand};
+use log::{error, info};
+use order_service::application::projection::projections;
+use order_service::core::configure::app::AppConfig;
+use order_service::core::error::{AppError, AppResult};
+use order_service::infrastructure::persistence::postgres::{DatabaseClient, DatabaseClientExt};
+use std::sync::Arc;
+use utils::kafka_consumer::{ConsumerRuntime, EventReplay, ReplayArgs, ReplayError};
+use utils::metrics::init_metrics;
+use utils::telemetry::init_telemetry;
 use order_service::core::http::server::AppServer;
+use order_service::infrastructure::constant::{CONFIG, EVENT_CONSUMER_GROUP_ID};
+
+#[derive(Parser)]
+#[command(name = "order-service")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve HTTP and consume events (the default)
+    Serve,
+    /// Re-read a topic into a projection, e.g. to rebuild a read model
+    Replay(ReplayArgs),
+}
 
 #[tokio::main]
 async fn main() -> AppResult<()> {
-    env_logger::builder().filter_level(LevelFilter::Debug).format_target(true).init();
+    let cli = Cli::parse();
+    let config = CONFIG.clone();
+    let _telemetry = init_telemetry("order-service", &config.telemetry)
+        .map_err(|e| AppError::UnknownError(e.into()))?;
+    init_metrics("order-service");
 
     info!("The initialization of Tracing was successful!");
-    let config = CONFIG.clone();
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(config).await,
+        Command::Replay(args) => replay(config, args).await,
+    }
+}
+
+async fn serve(config: AppConfig) -> AppResult<()> {
     let server = AppServer::new(config).await?;
-    let db = server.state.db.clone();
-    let redis = server.state.redis.clone();
+    let kafka_enabled = server.state.config.kafka.enabled();
+    // Live consumption runs the handlers of every projection, so a replay rebuilds the same read models
+    let consumer = projections().into_iter().fold(
+        ConsumerRuntime::new(
+            server.state.config.kafka.consumer_client_config(),
+            EVENT_CONSUMER_GROUP_ID,
+            server.state.db.clone(),
+            server.state.kafka_producer.clone(),
+        ),
+        ConsumerRuntime::register_projection,
+    );
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let consumer_task = tokio::spawn(async move {
+        let shutdown = async {
+            let _ = shutdown_rx.await;
+        };
+        if !kafka_enabled {
+            info!("Kafka is disabled, not consuming events");
+        } else if let Err(e) = consumer.run(shutdown).await {
+            error!("Kafka consumer error: {:?}", e);
+        }
+    });
+
     info!("Starting server...");
 
     let server_task = tokio::spawn(async {
@@ -22,7 +74,50 @@ async fn main() -> AppResult<()> {
         }
     });
 
-    let _server_result = tokio::join!(server_task);
+    tokio::select! {
+        _ = server_task => {},
+        _ = tokio::signal::ctrl_c() => info!("Shutting down..."),
+    }
+
+    // Let in-flight events finish and their offsets commit before exiting
+    let _ = shutdown_tx.send(());
+    let _ = consumer_task.await;
+
+    Ok(())
+}
+
+async fn replay(config: AppConfig, args: ReplayArgs) -> AppResult<()> {
+    let db = Arc::new(DatabaseClient::build_from_config(&config).await?);
+
+    let projection = projections()
+        .into_iter()
+        .find(|projection| projection.name() == args.projection)
+        .ok_or_else(|| AppError::BadRequestError(format!("Unknown projection {}", args.projection)))?;
+    if !projection.topics().contains(args.topic.as_str()) {
+        return Err(AppError::BadRequestError(format!(
+            "Projection {} does not read {}, it reads {:?}",
+            args.projection,
+            args.topic,
+            projection.topics()
+        )));
+    }
+
+    let report = EventReplay::new(config.kafka.consumer_client_config(), db)
+        .with_partition(args.partition)
+        .with_dry_run(args.dry_run)
+        .run(&projection, &args.topic, args.start())
+        .await
+        .map_err(|e| match e {
+            ReplayError::DryRunWithSideEffects(_) => AppError::BadRequestError(e.to_string()),
+            ReplayError::Kafka(e) => AppError::UnknownError(e.into()),
+        })?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
 
+    if !report.failed.is_empty() {
+        return Err(AppError::UnknownError(anyhow::anyhow!(
+            "{} records failed to replay",
+            report.failed.len()
+        )));
+    }
     Ok(())
 }
@@ -1,11 +1,12 @@
 use crate::core::configure::app::AppConfig;
-use crate::core::configure::kafka::KafkaConfig;
 use crate::core::error::{AppError, AppResult};
 use crate::infrastructure::persistence::postgres::{DatabaseClient, DatabaseClientExt};
 use crate::application::address::address_service::AddressService;
 
+use axum::extract::FromRef;
 use rdkafka::producer::FutureProducer;
 use std::sync::Arc;
+use utils::internal_auth::InternalTokenVerifier;
 use utils::redis_client::RedisConnectionPool;
 
 #[derive(Clone)]
@@ -15,6 +16,7 @@ pub struct AppState {
     pub redis: Arc<RedisConnectionPool>,
     pub kafka_producer: Arc<FutureProducer>,
     pub address_service: Arc<AddressService>,
+    pub internal_token_verifier: InternalTokenVerifier,
 }
 
 impl AppState {
@@ -27,9 +29,11 @@ impl AppState {
                 .await
                 .map_err(|e| AppError::BadRequestError(e.to_string()))?,
         );
-        let kafka_producer = Arc::new(KafkaConfig::new().create_kafka_producer());
+        let kafka_producer = Arc::new(config.kafka.create_kafka_producer());
         let address_service =
             Arc::new(AddressService::new(redis.clone(), kafka_producer.clone()));
+        let internal_token_verifier =
+            InternalTokenVerifier::new(&config.secret.internal_token_secret);
 
         Ok(Self {
             config,
@@ -37,6 +41,7 @@ impl AppState {
             redis,
             kafka_producer,
             address_service,
+            internal_token_verifier,
         })
     }
 }
@@ -46,3 +51,9 @@ impl AppState {
         &self.kafka_producer
     }
 }
+
+impl FromRef<AppState> for InternalTokenVerifier {
+    fn from_ref(state: &AppState) -> Self {
+        state.internal_token_verifier.clone()
+    }
+}
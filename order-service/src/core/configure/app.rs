@@ -1,13 +1,14 @@
 use crate::core::configure::db::DatabaseConfig;
 use crate::core::configure::env::get_env_source;
 use crate::core::configure::http::HttpClientConfig;
-use crate::core::configure::kafka::KafkaConfig;
 use crate::core::configure::redis::RedisConfig;
 use crate::core::configure::secret::SecretConfig;
 use crate::core::configure::server::ServerConfig;
 use config::{ConfigError, Environment};
 use serde::{Deserialize, Serialize};
 use utils::dir::get_project_root;
+use utils::kafka_config::KafkaConfig;
+use utils::telemetry::TelemetryConfig;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct AppConfig {
@@ -19,6 +20,8 @@ pub struct AppConfig {
     pub secret: SecretConfig,
     pub http: HttpClientConfig,
     pub kafka: KafkaConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
 }
 
 impl AppConfig {
@@ -29,7 +32,9 @@ impl AppConfig {
             .add_source(profile.env_source())
             .build()?;
         log::info!("Successfully read config profile: {profile}.");
-        config.try_deserialize()
+        let config: Self = config.try_deserialize()?;
+        config.kafka.validate()?;
+        Ok(config)
     }
 
     pub fn get_sentry_dsn(&self) -> &str {
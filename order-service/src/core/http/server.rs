@@ -2,19 +2,23 @@ use crate::api::build_routes;
 use crate::core::app_state::AppState;
 use crate::core::configure::app::AppConfig;
 use crate::core::error::AppResult;
-use axum::body::Bytes;
+use axum::body::{Body, Bytes};
 use axum::extract::DefaultBodyLimit;
-use axum::http::{header, HeaderValue};
+use axum::http::{header, HeaderValue, Request};
+use axum::middleware::from_fn;
 use fred::tracing;
 use std::sync::Arc;
 use std::time::Duration;
 use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
 use tower_http::timeout::TimeoutLayer;
-use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
+use tower_http::trace::{DefaultOnResponse, TraceLayer};
 use tower_http::ServiceBuilderExt;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_swagger_ui::SwaggerUi;
+use utils::metrics::track_http_metrics;
+use utils::request_id::propagate_request_id;
+use utils::telemetry::http_request_span;
 
 pub struct AppServer {
     pub state: AppState,
@@ -39,7 +43,7 @@ impl AppServer {
             .layer(
                 TraceLayer::new_for_http()
                     .on_body_chunk(|chunk: &Bytes, latency: Duration, _: &tracing::Span| {})
-                    .make_span_with(DefaultMakeSpan::new().include_headers(true))
+                    .make_span_with(|request: &Request<Body>| http_request_span(request))
                     .on_response(
                         DefaultOnResponse::new()
                             .include_headers(true)
@@ -61,8 +65,10 @@ impl AppServer {
 
         let app = router
             .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", api.clone()))
+            .layer(from_fn(track_http_metrics))
             .layer(CorsLayer::permissive())
             .layer(middleware)
+            .layer(from_fn(propagate_request_id))
             .with_state(self.state);
 
         axum::serve(self.tcp, app.into_make_service()).await?;
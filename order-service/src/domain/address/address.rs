@@ -12,8 +12,6 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i64,
     pub user_id: i64,
-    #[sea_orm(belongs_to, from = "user_id", to = "id")]
-    pub user: HasOne<super::super::user::user::Entity>,
     pub title: Option<String>,
     pub address_line_1: String,
     pub address_line_2: Option<String>,
@@ -68,7 +66,6 @@ impl ModelEx {
         Ok(Self {
             id: 0, // Will be set by the database
             user_id: request.user_id,
-            user: Default::default(),
             title: request.title.clone(),
             address_line_1: request.address_line_1.clone(),
             address_line_2: request.address_line_2.clone(),
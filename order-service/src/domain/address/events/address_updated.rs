@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use utils::events::DomainEvent;
+
+/// Published when an address changes, with the address after the change
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct AddressUpdatedEvent {
+    pub address_id: i64,
+    pub user_id: i64,
+    /// Names of the fields below that changed, e.g. `["city", "postal_code"]`
+    pub changed_fields: Vec<String>,
+    pub title: Option<String>,
+    pub address_line_1: String,
+    pub address_line_2: Option<String>,
+    pub country: String,
+    pub city: String,
+    pub postal_code: Option<String>,
+    pub landmark: Option<String>,
+    pub phone_number: Option<String>,
+    /// `active` or `inactive`
+    pub status: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DomainEvent for AddressUpdatedEvent {
+    const EVENT_TYPE: &'static str = "address.updated";
+    const SCHEMA_VERSION: u32 = 1;
+    const TOPIC: &'static str = "address_updated";
+
+    fn aggregate_id(&self) -> String {
+        self.user_id.to_string()
+    }
+}
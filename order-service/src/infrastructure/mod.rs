@@ -1,3 +1,4 @@
 pub mod persistence;
 pub mod third_party;
 pub mod model;
+pub mod constant;
@@ -2,5 +2,5 @@ pub mod api;
 pub mod core;
 pub mod application;
 pub mod domain;
-mod infrastructure;
+pub mod infrastructure;
 pub mod presentation;
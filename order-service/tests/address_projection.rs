@@ -0,0 +1,101 @@
+//! Handlers of the `addresses` projection against a mock database
+
+use order_service::application::address::address_event_handler::{
+    AddressAddedHandler, AddressRemovedHandler, AddressUpdatedHandler,
+};
+use order_service::domain::address::events::address_added::AddressAddedEvent;
+use order_service::domain::address::events::address_removed::AddressRemovedEvent;
+use order_service::domain::address::events::address_updated::AddressUpdatedEvent;
+use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase, MockExecResult, TransactionTrait};
+use utils::events::{DomainEvent, EventEnvelope};
+use utils::kafka_consumer::{EventHandler, HandlerError, RecordMeta};
+
+fn mock_db() -> DatabaseConnection {
+    MockDatabase::new(DatabaseBackend::Postgres)
+        .append_exec_results([MockExecResult { last_insert_id: 0, rows_affected: 1 }])
+        .into_connection()
+}
+
+fn meta<E: DomainEvent>() -> RecordMeta {
+    RecordMeta {
+        topic: E::TOPIC.to_string(),
+        partition: 0,
+        offset: 0,
+        key: Some("7".to_string()),
+        event_type: E::EVENT_TYPE.to_string(),
+        attempt: 0,
+    }
+}
+
+/// Statements the handler ran, with quotes unescaped
+async fn statements<H: EventHandler>(handler: H, event: H::Event) -> Result<String, HandlerError>
+where
+    H::Event: DomainEvent,
+{
+    let db = mock_db();
+    let tx = db.begin().await.unwrap();
+    let result = handler.handle(&tx, EventEnvelope::new("api-gateway", event), &meta::<H::Event>()).await;
+    tx.commit().await.unwrap();
+    result?;
+    Ok(format!("{:?}", db.into_transaction_log()).replace("\\\"", "\""))
+}
+
+fn added(status: &str) -> AddressAddedEvent {
+    AddressAddedEvent {
+        address_id: 42,
+        user_id: 7,
+        title: Some("Home".to_string()),
+        address_line_1: "1 Main St".to_string(),
+        address_line_2: None,
+        country: "VN".to_string(),
+        city: "Hanoi".to_string(),
+        postal_code: None,
+        landmark: None,
+        phone_number: None,
+        status: status.to_string(),
+        created_at: chrono::Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn added_and_updated_addresses_are_upserted_by_id() {
+    let sql = statements(AddressAddedHandler, added("active")).await.unwrap();
+    assert!(sql.contains(r#"INSERT INTO "addresses""#), "{}", sql);
+    assert!(sql.contains(r#"ON CONFLICT ("id") DO UPDATE"#), "{}", sql);
+
+    let updated = AddressUpdatedEvent {
+        address_id: 42,
+        user_id: 7,
+        changed_fields: vec!["city".to_string()],
+        title: Some("Home".to_string()),
+        address_line_1: "1 Main St".to_string(),
+        address_line_2: None,
+        country: "VN".to_string(),
+        city: "Hue".to_string(),
+        postal_code: None,
+        landmark: None,
+        phone_number: None,
+        status: "inactive".to_string(),
+        updated_at: chrono::Utc::now(),
+    };
+    let sql = statements(AddressUpdatedHandler, updated).await.unwrap();
+    assert!(sql.contains(r#"ON CONFLICT ("id") DO UPDATE"#), "{}", sql);
+    assert!(!sql.contains(r#""created_at" = "excluded"."created_at""#), "keeps the original creation time");
+}
+
+#[tokio::test]
+async fn removed_addresses_are_soft_deleted() {
+    let removed = AddressRemovedEvent { address_id: 42, user_id: 7, removed_at: chrono::Utc::now() };
+
+    let sql = statements(AddressRemovedHandler, removed).await.unwrap();
+
+    assert!(sql.contains(r#"UPDATE "addresses" SET "is_deleted" = $1"#), "{}", sql);
+    assert!(!sql.contains("DELETE"), "{}", sql);
+}
+
+#[tokio::test]
+async fn unknown_status_is_a_permanent_failure() {
+    let result = statements(AddressAddedHandler, added("archived")).await;
+
+    assert!(matches!(result, Err(HandlerError::Permanent(_))), "{:?}", result.err());
+}
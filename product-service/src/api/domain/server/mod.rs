@@ -3,7 +3,8 @@ use crate::core::error::{AppError, AppResult};
 use crate::core::response::{
     ClientResponseError, EntityResponse, MessageResponse, ServiceStatusResponse,
 };
-use axum::{extract::State, Json};
+use axum::{extract::State, response::Response, Json};
+use utils::metrics::metrics_response;
 use axum_extra::extract::Multipart;
 use log::error;
 
@@ -36,3 +37,11 @@ pub async fn server_state(State(state): State<AppState>) -> AppResult<Json<Servi
     let resp = ServiceStatusResponse { db: db.is_ok(), redis: true };
     Ok(Json(resp))
 }
+
+/// Prometheus scrape endpoint
+pub async fn metrics(State(state): State<AppState>) -> Response {
+    let pool = state.db.get_postgres_connection_pool();
+    let metrics = utils::metrics::metrics();
+    metrics.set_db_pool(pool.size(), pool.num_idle(), pool.options().get_max_connections());
+    metrics_response()
+}
@@ -1,4 +1,5 @@
 use axum::http::{StatusCode, Uri};
+use axum::routing::get;
 use crate::core::app_state::AppState;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
@@ -6,7 +7,8 @@ pub mod domain;
 
 pub fn build_routes() -> OpenApiRouter<AppState> {
     let server_routes = OpenApiRouter::new()
-        .routes(routes!(domain::server::health_check));
+        .routes(routes!(domain::server::health_check))
+        .route("/metrics", get(domain::server::metrics));
 
 
     let address_routes = OpenApiRouter::new()
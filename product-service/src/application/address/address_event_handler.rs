@@ -0,0 +1,163 @@
+use crate::domain::address::address::{ActiveModel, Column, Entity, Status};
+use crate::domain::address::events::address_added::AddressAddedEvent;
+use crate::domain::address::events::address_removed::AddressRemovedEvent;
+use crate::domain::address::events::address_updated::AddressUpdatedEvent;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{ActiveEnum, ColumnTrait, DatabaseTransaction, EntityTrait, QueryFilter, Set};
+use utils::events::EventEnvelope;
+use utils::kafka_consumer::{EventHandler, HandlerError, RecordMeta};
+
+/// Fields of an address as the api-gateway last published them
+struct AddressSnapshot<'a> {
+    address_id: i64,
+    user_id: i64,
+    title: &'a Option<String>,
+    address_line_1: &'a str,
+    address_line_2: &'a Option<String>,
+    country: &'a str,
+    city: &'a str,
+    postal_code: &'a Option<String>,
+    landmark: &'a Option<String>,
+    phone_number: &'a Option<String>,
+    status: &'a str,
+    created_at: Option<DateTime<Utc>>,
+}
+
+/// Insert the address, or overwrite every published field of it, so handling an event
+/// again or out of a replay leaves the same row
+async fn upsert(tx: &DatabaseTransaction, address: AddressSnapshot<'_>) -> Result<(), HandlerError> {
+    let status = Status::try_from_value(&address.status.to_string())
+        .map_err(|e| HandlerError::Permanent(format!("Address {}: {}", address.address_id, e)))?;
+    let model = ActiveModel {
+        id: Set(address.address_id),
+        user_id: Set(address.user_id),
+        title: Set(address.title.clone()),
+        address_line_1: Set(address.address_line_1.to_string()),
+        address_line_2: Set(address.address_line_2.clone()),
+        country: Set(address.country.to_string()),
+        city: Set(address.city.to_string()),
+        postal_code: Set(address.postal_code.clone()),
+        landmark: Set(address.landmark.clone()),
+        phone_number: Set(address.phone_number.clone()),
+        status: Set(status),
+        is_deleted: Set(false),
+        created_at: Set(address.created_at.map(|created_at| created_at.naive_utc())),
+        deleted_at: Set(None),
+    };
+
+    Entity::insert(model)
+        .on_conflict(
+            OnConflict::column(Column::Id)
+                .update_columns([
+                    Column::UserId,
+                    Column::Title,
+                    Column::AddressLine1,
+                    Column::AddressLine2,
+                    Column::Country,
+                    Column::City,
+                    Column::PostalCode,
+                    Column::Landmark,
+                    Column::PhoneNumber,
+                    Column::Status,
+                ])
+                .to_owned(),
+        )
+        .exec_without_returning(tx)
+        .await?;
+    Ok(())
+}
+
+/// Adds the address to the service's copy of the user's addresses
+pub struct AddressAddedHandler;
+
+#[async_trait]
+impl EventHandler for AddressAddedHandler {
+    type Event = AddressAddedEvent;
+
+    async fn handle(
+        &self,
+        tx: &DatabaseTransaction,
+        event: EventEnvelope<AddressAddedEvent>,
+        _meta: &RecordMeta,
+    ) -> Result<(), HandlerError> {
+        let address = &event.data;
+        upsert(
+            tx,
+            AddressSnapshot {
+                address_id: address.address_id,
+                user_id: address.user_id,
+                title: &address.title,
+                address_line_1: &address.address_line_1,
+                address_line_2: &address.address_line_2,
+                country: &address.country,
+                city: &address.city,
+                postal_code: &address.postal_code,
+                landmark: &address.landmark,
+                phone_number: &address.phone_number,
+                status: &address.status,
+                created_at: Some(address.created_at),
+            },
+        )
+        .await
+    }
+}
+
+/// Overwrites the address with its state after the change
+pub struct AddressUpdatedHandler;
+
+#[async_trait]
+impl EventHandler for AddressUpdatedHandler {
+    type Event = AddressUpdatedEvent;
+
+    async fn handle(
+        &self,
+        tx: &DatabaseTransaction,
+        event: EventEnvelope<AddressUpdatedEvent>,
+        _meta: &RecordMeta,
+    ) -> Result<(), HandlerError> {
+        let address = &event.data;
+        upsert(
+            tx,
+            AddressSnapshot {
+                address_id: address.address_id,
+                user_id: address.user_id,
+                title: &address.title,
+                address_line_1: &address.address_line_1,
+                address_line_2: &address.address_line_2,
+                country: &address.country,
+                city: &address.city,
+                postal_code: &address.postal_code,
+                landmark: &address.landmark,
+                phone_number: &address.phone_number,
+                status: &address.status,
+                created_at: None,
+            },
+        )
+        .await
+    }
+}
+
+/// Soft deletes the address, like the api-gateway does
+pub struct AddressRemovedHandler;
+
+#[async_trait]
+impl EventHandler for AddressRemovedHandler {
+    type Event = AddressRemovedEvent;
+
+    async fn handle(
+        &self,
+        tx: &DatabaseTransaction,
+        event: EventEnvelope<AddressRemovedEvent>,
+        _meta: &RecordMeta,
+    ) -> Result<(), HandlerError> {
+        Entity::update_many()
+            .col_expr(Column::IsDeleted, Expr::value(true))
+            .col_expr(Column::DeletedAt, Expr::value(event.data.removed_at.naive_utc()))
+            .filter(Column::Id.eq(event.data.address_id))
+            .exec(tx)
+            .await?;
+        Ok(())
+    }
+}
@@ -1,10 +1,11 @@
 use crate::core::configure::app::AppConfig;
-use crate::core::configure::kafka::KafkaConfig;
 use crate::core::error::{AppError, AppResult};
 use crate::application::address::address_service::AddressService;
 
+use axum::extract::FromRef;
 use rdkafka::producer::FutureProducer;
 use std::sync::Arc;
+use utils::internal_auth::InternalTokenVerifier;
 use utils::redis_client::RedisConnectionPool;
 use crate::infrastructure::persistence::postgres::{DatabaseClient, DatabaseClientExt};
 
@@ -15,6 +16,7 @@ pub struct AppState {
     pub redis: Arc<RedisConnectionPool>,
     pub kafka_producer: Arc<FutureProducer>,
     pub address_service: Arc<AddressService>,
+    pub internal_token_verifier: InternalTokenVerifier,
 }
 
 impl AppState {
@@ -27,10 +29,12 @@ impl AppState {
                 .await
                 .map_err(|e| AppError::BadRequestError(e.to_string()))?,
         );
-        let kafka_producer = Arc::new(KafkaConfig::new().create_kafka_producer());
+        let kafka_producer = Arc::new(config.kafka.create_kafka_producer());
 
         let address_service =
             Arc::new(AddressService::new(redis.clone(), kafka_producer.clone()));
+        let internal_token_verifier =
+            InternalTokenVerifier::new(&config.secret.internal_token_secret);
 
         Ok(Self {
             config,
@@ -38,6 +42,7 @@ impl AppState {
             redis,
             kafka_producer,
             address_service,
+            internal_token_verifier,
         })
     }
 }
@@ -47,3 +52,9 @@ impl AppState {
         &self.kafka_producer
     }
 }
+
+impl FromRef<AppState> for InternalTokenVerifier {
+    fn from_ref(state: &AppState) -> Self {
+        state.internal_token_verifier.clone()
+    }
+}
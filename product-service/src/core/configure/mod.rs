@@ -2,7 +2,6 @@ pub mod app;
 pub mod db;
 pub mod env;
 pub mod http;
-pub mod kafka;
 pub mod redis;
 pub mod secret;
 pub mod server;
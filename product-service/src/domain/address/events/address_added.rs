@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use utils::events::DomainEvent;
+
+/// Published when a user adds an address, with the whole address
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct AddressAddedEvent {
+    pub address_id: i64,
+    pub user_id: i64,
+    pub title: Option<String>,
+    pub address_line_1: String,
+    pub address_line_2: Option<String>,
+    pub country: String,
+    pub city: String,
+    pub postal_code: Option<String>,
+    pub landmark: Option<String>,
+    pub phone_number: Option<String>,
+    /// `active` or `inactive`
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DomainEvent for AddressAddedEvent {
+    const EVENT_TYPE: &'static str = "address.added";
+    const SCHEMA_VERSION: u32 = 1;
+    const TOPIC: &'static str = "address_added";
+
+    fn aggregate_id(&self) -> String {
+        self.user_id.to_string()
+    }
+}
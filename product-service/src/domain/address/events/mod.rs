@@ -0,0 +1,5 @@
+//! Address events published by the api-gateway, which owns addresses
+
+pub mod address_added;
+pub mod address_removed;
+pub mod address_updated;
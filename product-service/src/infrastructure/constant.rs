@@ -0,0 +1,9 @@
+use crate::core::configure;
+use crate::core::configure::app::Profile;
+use std::sync::LazyLock;
+
+/// Consumer group of the service's own event handlers
+pub const EVENT_CONSUMER_GROUP_ID: &str = "product-service-events";
+
+pub static CONFIG: LazyLock<configure::app::AppConfig> =
+    LazyLock::new(|| configure::app::AppConfig::read(Profile::Local).unwrap());
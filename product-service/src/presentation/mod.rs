@@ -1,2 +1,3 @@
 pub mod address;
 mod common;
+pub mod user;
@@ -0,0 +1,226 @@
+use crate::api::domain::admin::require_admin;
+use crate::application::authen::claim::UserClaims;
+use crate::core::app_state::AppState;
+use crate::core::response::{ClientResponseError, EntityResponse, MessageResponse};
+use crate::infrastructure::constant::{
+    DEAD_LETTER_MAX_LIST, DEAD_LETTER_RESOLUTION_TTL, EVENT_CONSUMER_GROUP_ID,
+};
+use crate::infrastructure::error::{AppError, AppResult};
+use crate::presentation::admin::dead_letter::DeadLetterSerializer;
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use rdkafka::error::KafkaError;
+use serde::Deserialize;
+use std::collections::HashSet;
+use utils::kafka_consumer::{DeadLetter, DeadLetterQueue};
+use utoipa::IntoParams;
+
+const REPLAYED: &str = "replayed";
+const DISCARDED: &str = "discarded";
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DeadLetterGroupQuery {
+    /// Consumer group owning the dead-letter topic, defaults to the gateway's
+    pub group: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ListDeadLettersQuery {
+    /// Consumer group owning the dead-letter topic, defaults to the gateway's
+    pub group: Option<String>,
+    /// Only read this partition
+    pub partition: Option<i32>,
+    /// First offset to read in each partition
+    pub offset: Option<i64>,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    /// Also list dead letters that were already replayed or discarded
+    #[serde(default)]
+    pub include_resolved: bool,
+}
+
+fn default_limit() -> usize {
+    50
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/dead-letters",
+    tags = ["admin"],
+    params(ListDeadLettersQuery),
+    responses(
+        (status = 200, description = "Dead letters in partition and offset order", body = EntityResponse<Vec<DeadLetterSerializer>>),
+        (status = 401, description = "Unauthorized", body = ClientResponseError),
+        (status = 403, description = "Admin role required", body = ClientResponseError),
+        (status = 503, description = "Kafka unavailable", body = ClientResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn controller_list_dead_letters(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Query(query): Query<ListDeadLettersQuery>,
+) -> AppResult<Json<EntityResponse<Vec<DeadLetterSerializer>>>> {
+    require_admin(&claims, "dead letters")?;
+    let queue = dead_letter_queue(&state, query.group.as_deref())?;
+    // Skipped while reading, so resolved letters do not use up the limit
+    let resolved = if query.include_resolved { HashSet::new() } else { resolved_letters(&state, &queue).await? };
+    let letters = queue
+        .list(query.partition, query.offset, query.limit.min(DEAD_LETTER_MAX_LIST), resolved)
+        .await
+        .map_err(kafka_unavailable)?;
+
+    let mut serializers = Vec::with_capacity(letters.len());
+    for letter in letters {
+        let resolution = if query.include_resolved {
+            state.redis.get(&resolution_key(&queue, &letter)).await?
+        } else {
+            None
+        };
+        serializers.push(DeadLetterSerializer { letter, resolution });
+    }
+
+    Ok(Json(EntityResponse {
+        message: format!("Dead letters of {}.", queue.topic()),
+        total: serializers.len() as i64,
+        data: Some(serializers),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/dead-letters/{partition}/{offset}/replay",
+    tags = ["admin"],
+    params(
+        ("partition" = i32, Path, description = "Partition of the dead letter"),
+        ("offset" = i64, Path, description = "Offset of the dead letter"),
+        DeadLetterGroupQuery
+    ),
+    responses(
+        (status = 200, description = "Dead letter sent to the first retry topic", body = MessageResponse),
+        (status = 401, description = "Unauthorized", body = ClientResponseError),
+        (status = 403, description = "Admin role required", body = ClientResponseError),
+        (status = 404, description = "Dead letter not found", body = ClientResponseError),
+        (status = 409, description = "Dead letter already replayed or discarded", body = ClientResponseError),
+        (status = 503, description = "Kafka unavailable", body = ClientResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn controller_replay_dead_letter(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Path((partition, offset)): Path<(i32, i64)>,
+    Query(query): Query<DeadLetterGroupQuery>,
+) -> AppResult<Json<MessageResponse>> {
+    require_admin(&claims, "dead letters")?;
+    let queue = dead_letter_queue(&state, query.group.as_deref())?;
+    let letter = unresolved_letter(&state, &queue, partition, offset).await?;
+
+    queue.replay(&letter).await.map_err(kafka_unavailable)?;
+    resolve(&state, &queue, &letter, REPLAYED).await?;
+    log::info!("User {} replayed dead letter {}/{}@{}", claims.user_id, queue.topic(), partition, offset);
+    Ok(Json(MessageResponse::new(format!("Replayed dead letter {}@{}", partition, offset))))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/dead-letters/{partition}/{offset}",
+    tags = ["admin"],
+    params(
+        ("partition" = i32, Path, description = "Partition of the dead letter"),
+        ("offset" = i64, Path, description = "Offset of the dead letter"),
+        DeadLetterGroupQuery
+    ),
+    responses(
+        (status = 200, description = "Dead letter discarded", body = MessageResponse),
+        (status = 401, description = "Unauthorized", body = ClientResponseError),
+        (status = 403, description = "Admin role required", body = ClientResponseError),
+        (status = 404, description = "Dead letter not found", body = ClientResponseError),
+        (status = 409, description = "Dead letter already replayed or discarded", body = ClientResponseError),
+        (status = 503, description = "Kafka unavailable", body = ClientResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn controller_discard_dead_letter(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Path((partition, offset)): Path<(i32, i64)>,
+    Query(query): Query<DeadLetterGroupQuery>,
+) -> AppResult<Json<MessageResponse>> {
+    require_admin(&claims, "dead letters")?;
+    let queue = dead_letter_queue(&state, query.group.as_deref())?;
+    let letter = unresolved_letter(&state, &queue, partition, offset).await?;
+
+    resolve(&state, &queue, &letter, DISCARDED).await?;
+    log::info!("User {} discarded dead letter {}/{}@{}", claims.user_id, queue.topic(), partition, offset);
+    Ok(Json(MessageResponse::new(format!("Discarded dead letter {}@{}", partition, offset))))
+}
+
+/// Dead letters live in Kafka, so profiles without a broker have none to manage
+fn dead_letter_queue(state: &AppState, group: Option<&str>) -> AppResult<DeadLetterQueue> {
+    let producer = state.kafka_producer.clone().ok_or_else(|| AppError::ServiceUnavailableError {
+        detail: "Kafka is disabled in this profile".to_string(),
+        retry_after_secs: 5,
+    })?;
+    Ok(DeadLetterQueue::new(
+        state.config.kafka.consumer_client_config(),
+        producer,
+        group.unwrap_or(EVENT_CONSUMER_GROUP_ID),
+    ))
+}
+
+async fn unresolved_letter(
+    state: &AppState,
+    queue: &DeadLetterQueue,
+    partition: i32,
+    offset: i64,
+) -> AppResult<DeadLetter> {
+    let letter = queue.get(partition, offset).await.map_err(kafka_unavailable)?.ok_or_else(|| {
+        AppError::EntityNotFoundError {
+            detail: format!("Dead letter {}@{} not found in {}", partition, offset, queue.topic()),
+        }
+    })?;
+    if let Some(resolution) = state.redis.get(&resolution_key(queue, &letter)).await? {
+        return Err(AppError::ConflictError(format!(
+            "Dead letter {}@{} was already {}",
+            partition, offset, resolution
+        )));
+    }
+    Ok(letter)
+}
+
+/// Kafka keeps dead letters until retention, so what was done with them is kept in Redis
+fn resolution_key(queue: &DeadLetterQueue, letter: &DeadLetter) -> String {
+    format!("dead_letter:{}:{}:{}", queue.topic(), letter.partition, letter.offset)
+}
+
+/// Partition and offset of every dead letter of the topic that has a resolution
+async fn resolved_letters(state: &AppState, queue: &DeadLetterQueue) -> AppResult<HashSet<(i32, i64)>> {
+    let keys = state.redis.scan(&format!("dead_letter:{}:*", queue.topic()), None, None).await?;
+    Ok(keys
+        .iter()
+        .filter_map(|key| {
+            let mut parts = key.rsplitn(3, ':');
+            let offset = parts.next()?.parse().ok()?;
+            let partition = parts.next()?.parse().ok()?;
+            Some((partition, offset))
+        })
+        .collect())
+}
+
+async fn resolve(
+    state: &AppState,
+    queue: &DeadLetterQueue,
+    letter: &DeadLetter,
+    resolution: &str,
+) -> AppResult<()> {
+    state
+        .redis
+        .set(&resolution_key(queue, letter), resolution, DEAD_LETTER_RESOLUTION_TTL)
+        .await?;
+    Ok(())
+}
+
+fn kafka_unavailable(err: KafkaError) -> AppError {
+    AppError::ServiceUnavailableError { detail: format!("Kafka unavailable: {}", err), retry_after_secs: 5 }
+}
@@ -1 +1,16 @@
+use crate::application::authen::claim::UserClaims;
+use crate::infrastructure::error::{AppError, AppResult};
+
+pub mod dead_letter;
 pub mod user;
+pub mod webhook;
+
+fn require_admin(claims: &UserClaims, managed: &str) -> AppResult<()> {
+    if !claims.roles.iter().any(|role| role == "admin") {
+        return Err(AppError::PermissionDeniedError(format!(
+            "Managing {} requires the admin role",
+            managed
+        )));
+    }
+    Ok(())
+}
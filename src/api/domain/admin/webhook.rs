@@ -0,0 +1,283 @@
+use crate::api::domain::admin::require_admin;
+use crate::application::authen::claim::UserClaims;
+use crate::application::webhook::webhook_service_interface::WebhookServiceInterface;
+use crate::core::app_state::AppState;
+use crate::core::response::{ClientResponseError, EntityResponse, MessageResponse};
+use crate::domain::webhook::webhook_delivery;
+use crate::infrastructure::error::AppResult;
+use crate::presentation::admin::webhook::{
+    CreateWebhookSubscriptionRequest, UpdateWebhookSubscriptionRequest, WebhookDeliverySerializer,
+    WebhookSubscriptionSerializer,
+};
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use sea_orm::TransactionTrait;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+const MANAGED: &str = "webhooks";
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ListWebhookSubscriptionsQuery {
+    /// Only list the subscriptions of this tenant or partner
+    pub owner: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ListWebhookDeliveriesQuery {
+    /// Only list deliveries in this status
+    pub status: Option<webhook_delivery::Status>,
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+}
+
+fn default_limit() -> u64 {
+    50
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/webhooks",
+    tags = ["admin"],
+    request_body = CreateWebhookSubscriptionRequest,
+    responses(
+        (status = 200, description = "Subscription created, with its signing secret", body = EntityResponse<WebhookSubscriptionSerializer>),
+        (status = 400, description = "Invalid URL or unknown event type", body = ClientResponseError),
+        (status = 401, description = "Unauthorized", body = ClientResponseError),
+        (status = 403, description = "Admin role required", body = ClientResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn controller_create_webhook_subscription(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Json(request): Json<CreateWebhookSubscriptionRequest>,
+) -> AppResult<Json<EntityResponse<WebhookSubscriptionSerializer>>> {
+    require_admin(&claims, MANAGED)?;
+    let tx = state.db.begin().await?;
+
+    match state.webhook_service.create_subscription(&tx, request).await {
+        Ok(result) => {
+            tx.commit().await?;
+            Ok(Json(EntityResponse {
+                message: "Webhook subscription created successfully.".to_string(),
+                data: Some(result),
+                total: 1,
+            }))
+        }
+        Err(err) => {
+            tx.rollback().await?;
+            log::error!("Failed to create webhook subscription: {err:?}");
+            Err(err)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/webhooks",
+    tags = ["admin"],
+    params(ListWebhookSubscriptionsQuery),
+    responses(
+        (status = 200, description = "Subscriptions in creation order", body = EntityResponse<Vec<WebhookSubscriptionSerializer>>),
+        (status = 401, description = "Unauthorized", body = ClientResponseError),
+        (status = 403, description = "Admin role required", body = ClientResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn controller_list_webhook_subscriptions(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Query(query): Query<ListWebhookSubscriptionsQuery>,
+) -> AppResult<Json<EntityResponse<Vec<WebhookSubscriptionSerializer>>>> {
+    require_admin(&claims, MANAGED)?;
+    let tx = state.db.begin().await?;
+
+    let subscriptions = state.webhook_service.list_subscriptions(&tx, query.owner.as_deref()).await?;
+    Ok(Json(EntityResponse {
+        message: "Webhook subscriptions retrieved successfully.".to_string(),
+        total: subscriptions.len() as i64,
+        data: Some(subscriptions),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/webhooks/{id}",
+    tags = ["admin"],
+    params(
+        ("id" = i64, Path, description = "Webhook subscription ID")
+    ),
+    responses(
+        (status = 200, description = "Subscription retrieved successfully", body = EntityResponse<WebhookSubscriptionSerializer>),
+        (status = 401, description = "Unauthorized", body = ClientResponseError),
+        (status = 403, description = "Admin role required", body = ClientResponseError),
+        (status = 404, description = "Subscription not found", body = ClientResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn controller_get_webhook_subscription(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Path(id): Path<i64>,
+) -> AppResult<Json<EntityResponse<WebhookSubscriptionSerializer>>> {
+    require_admin(&claims, MANAGED)?;
+    let tx = state.db.begin().await?;
+
+    let subscription = state.webhook_service.get_subscription(&tx, id).await?;
+    Ok(Json(EntityResponse {
+        message: "Webhook subscription retrieved successfully.".to_string(),
+        data: Some(subscription),
+        total: 1,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/webhooks/{id}",
+    tags = ["admin"],
+    request_body = UpdateWebhookSubscriptionRequest,
+    params(
+        ("id" = i64, Path, description = "Webhook subscription ID")
+    ),
+    responses(
+        (status = 200, description = "Subscription updated successfully", body = EntityResponse<WebhookSubscriptionSerializer>),
+        (status = 400, description = "Invalid URL or unknown event type", body = ClientResponseError),
+        (status = 401, description = "Unauthorized", body = ClientResponseError),
+        (status = 403, description = "Admin role required", body = ClientResponseError),
+        (status = 404, description = "Subscription not found", body = ClientResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn controller_update_webhook_subscription(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Path(id): Path<i64>,
+    Json(request): Json<UpdateWebhookSubscriptionRequest>,
+) -> AppResult<Json<EntityResponse<WebhookSubscriptionSerializer>>> {
+    require_admin(&claims, MANAGED)?;
+    let tx = state.db.begin().await?;
+
+    match state.webhook_service.update_subscription(&tx, id, request).await {
+        Ok(result) => {
+            tx.commit().await?;
+            log::info!("User {} updated webhook subscription {}", claims.user_id, id);
+            Ok(Json(EntityResponse {
+                message: "Webhook subscription updated successfully.".to_string(),
+                data: Some(result),
+                total: 1,
+            }))
+        }
+        Err(err) => {
+            tx.rollback().await?;
+            log::error!("Failed to update webhook subscription: {err:?}");
+            Err(err)
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/webhooks/{id}",
+    tags = ["admin"],
+    params(
+        ("id" = i64, Path, description = "Webhook subscription ID")
+    ),
+    responses(
+        (status = 200, description = "Subscription and its delivery log deleted", body = MessageResponse),
+        (status = 401, description = "Unauthorized", body = ClientResponseError),
+        (status = 403, description = "Admin role required", body = ClientResponseError),
+        (status = 404, description = "Subscription not found", body = ClientResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn controller_delete_webhook_subscription(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Path(id): Path<i64>,
+) -> AppResult<Json<MessageResponse>> {
+    require_admin(&claims, MANAGED)?;
+    let tx = state.db.begin().await?;
+
+    match state.webhook_service.delete_subscription(&tx, id).await {
+        Ok(_) => {
+            tx.commit().await?;
+            log::info!("User {} deleted webhook subscription {}", claims.user_id, id);
+            Ok(Json(MessageResponse::new(format!("Deleted webhook subscription {}", id))))
+        }
+        Err(err) => {
+            tx.rollback().await?;
+            Err(err)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/webhooks/{id}/deliveries",
+    tags = ["admin"],
+    params(
+        ("id" = i64, Path, description = "Webhook subscription ID"),
+        ListWebhookDeliveriesQuery
+    ),
+    responses(
+        (status = 200, description = "Delivery log, newest first", body = EntityResponse<Vec<WebhookDeliverySerializer>>),
+        (status = 401, description = "Unauthorized", body = ClientResponseError),
+        (status = 403, description = "Admin role required", body = ClientResponseError),
+        (status = 404, description = "Subscription not found", body = ClientResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn controller_list_webhook_deliveries(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Path(id): Path<i64>,
+    Query(query): Query<ListWebhookDeliveriesQuery>,
+) -> AppResult<Json<EntityResponse<Vec<WebhookDeliverySerializer>>>> {
+    require_admin(&claims, MANAGED)?;
+    let tx = state.db.begin().await?;
+
+    let deliveries = state.webhook_service.list_deliveries(&tx, id, query.status, query.limit).await?;
+    Ok(Json(EntityResponse {
+        message: format!("Deliveries of webhook subscription {}.", id),
+        total: deliveries.len() as i64,
+        data: Some(deliveries),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/webhooks/deliveries/{delivery_id}/redeliver",
+    tags = ["admin"],
+    params(
+        ("delivery_id" = i64, Path, description = "Webhook delivery ID")
+    ),
+    responses(
+        (status = 200, description = "Delivery queued to be sent again", body = MessageResponse),
+        (status = 401, description = "Unauthorized", body = ClientResponseError),
+        (status = 403, description = "Admin role required", body = ClientResponseError),
+        (status = 404, description = "Delivery not found", body = ClientResponseError),
+        (status = 409, description = "Delivery still pending or subscription disabled", body = ClientResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn controller_redeliver_webhook(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Path(delivery_id): Path<i64>,
+) -> AppResult<Json<MessageResponse>> {
+    require_admin(&claims, MANAGED)?;
+    let tx = state.db.begin().await?;
+
+    match state.webhook_service.redeliver(&tx, delivery_id).await {
+        Ok(_) => {
+            tx.commit().await?;
+            log::info!("User {} redelivered webhook delivery {}", claims.user_id, delivery_id);
+            Ok(Json(MessageResponse::new(format!("Queued webhook delivery {} for redelivery", delivery_id))))
+        }
+        Err(err) => {
+            tx.rollback().await?;
+            Err(err)
+        }
+    }
+}
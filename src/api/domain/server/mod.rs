@@ -1,8 +1,10 @@
 use crate::core::app_state::AppState;
 use crate::core::response::{
-    ClientResponseError, EntityResponse, MessageResponse, ServiceStatusResponse,
+    ClientResponseError, EntityResponse, MessageResponse, ReadinessResponse, ServiceStatusResponse,
 };
-use axum::{extract::State, Json};
+use crate::infrastructure::health::health_monitor::HealthTransition;
+use axum::{extract::State, http::StatusCode, response::Response, Json};
+use utils::metrics::metrics_response;
 use axum_extra::extract::Multipart;
 use log::error;
 use crate::infrastructure::error::AppResult;
@@ -29,10 +31,81 @@ pub async fn health_check() -> AppResult<Json<MessageResponse>> {
     )
 )]
 pub async fn server_state(State(state): State<AppState>) -> AppResult<Json<ServiceStatusResponse>> {
-    let db = state.db.ping().await;
-    if let Err(err) = db.as_ref() {
-        error!("Database connection failed error: {err}.");
+    let monitor = &state.health_monitor;
+    let db = monitor.get("postgres").await.is_some_and(|c| c.healthy);
+    let redis = monitor.get("redis").await.is_some_and(|c| c.healthy);
+    if !db {
+        error!("Database connection is unhealthy.");
     }
-    let resp = ServiceStatusResponse { db: db.is_ok(), redis: true };
+    if !redis {
+        error!("Redis connection is unhealthy.");
+    }
+    let resp = ServiceStatusResponse { db, redis };
     Ok(Json(resp))
 }
+
+/// Liveness probe
+///
+/// Succeeds as long as the process can serve HTTP, dependencies are not checked.
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tags = ["server_service"],
+    responses(
+        (status = 200, description = "process is alive", body = MessageResponse)
+    )
+)]
+pub async fn liveness() -> AppResult<Json<MessageResponse>> {
+    Ok(Json(MessageResponse::new("Ok")))
+}
+
+/// Readiness probe
+///
+/// Succeeds once the first round of health checks has finished and every critical
+/// dependency (Postgres, Redis) is healthy. Upstreams and Kafka only degrade the report.
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tags = ["server_service"],
+    responses(
+        (status = 200, description = "ready to receive traffic", body = ReadinessResponse),
+        (status = 503, description = "not ready", body = ReadinessResponse)
+    )
+)]
+pub async fn readiness(State(state): State<AppState>) -> AppResult<(StatusCode, Json<ReadinessResponse>)> {
+    let ready = state.health_monitor.is_ready().await;
+    let components = state.health_monitor.components().await;
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    Ok((status, Json(ReadinessResponse { ready, components })))
+}
+
+/// Health history
+///
+/// Status changes of every monitored dependency, most recent first.
+#[utoipa::path(
+    get,
+    path = "/health/history",
+    tags = ["server_service"],
+    responses(
+        (status = 200, description = "health status changes", body = EntityResponse<Vec<HealthTransition>>)
+    )
+)]
+pub async fn health_history(
+    State(state): State<AppState>,
+) -> AppResult<Json<EntityResponse<Vec<HealthTransition>>>> {
+    let history = state.health_monitor.history().await;
+    let total = history.len() as i64;
+    Ok(Json(EntityResponse {
+        message: "Health history".to_string(),
+        data: Some(history),
+        total,
+    }))
+}
+
+/// Prometheus scrape endpoint
+pub async fn metrics(State(state): State<AppState>) -> Response {
+    let pool = state.db.get_postgres_connection_pool();
+    let metrics = utils::metrics::metrics();
+    metrics.set_db_pool(pool.size(), pool.num_idle(), pool.options().get_max_connections());
+    metrics_response()
+}
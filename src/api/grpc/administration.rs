@@ -0,0 +1,207 @@
+use crate::api::grpc::pb;
+use crate::api::grpc::pb::administration_service_server::AdministrationService;
+use crate::application::authen::claim::UserClaims;
+use crate::core::app_state::AppState;
+use crate::domain::address::address;
+use crate::domain::address::address_repository_interface::AddressRepositoryInterface;
+use crate::domain::user::user;
+use crate::domain::user::user_repository_interface::UserRepositoryInterface;
+use crate::infrastructure::constant::{ACCESS_TOKEN_DECODE_KEY, GRPC_MAX_BATCH_USERS};
+use crate::infrastructure::error::AppError;
+use sea_orm::{DatabaseTransaction, TransactionTrait};
+use std::collections::HashSet;
+use tonic::{Request, Response, Status};
+use utils::internal_auth::ServiceClaims;
+
+/// Read-only user lookups for internal services, see `proto/administration.proto`
+pub struct AdministrationGrpc {
+    state: AppState,
+}
+
+impl AdministrationGrpc {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    async fn begin(&self) -> Result<DatabaseTransaction, Status> {
+        Ok(self.state.db.begin().await.map_err(AppError::from)?)
+    }
+}
+
+#[tonic::async_trait]
+impl AdministrationService for AdministrationGrpc {
+    async fn get_user(&self, request: Request<pb::GetUserRequest>) -> Result<Response<pb::User>, Status> {
+        let caller = caller(&request);
+        let user_id = request.into_inner().user_id;
+        log::debug!("gRPC GetUser {} from {}", user_id, caller);
+
+        let tx = self.begin().await?;
+        let user = user::Entity::find_users_by_ids(&tx, &[user_id])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Status::not_found(format!("User with id {} not found", user_id)))?;
+        Ok(Response::new(user_message(user)))
+    }
+
+    async fn batch_get_users(
+        &self,
+        request: Request<pb::BatchGetUsersRequest>,
+    ) -> Result<Response<pb::BatchGetUsersResponse>, Status> {
+        let caller = caller(&request);
+        let mut seen = HashSet::new();
+        let user_ids: Vec<i64> =
+            request.into_inner().user_ids.into_iter().filter(|id| seen.insert(*id)).collect();
+        if user_ids.len() > GRPC_MAX_BATCH_USERS {
+            return Err(Status::invalid_argument(format!(
+                "At most {} user ids per batch",
+                GRPC_MAX_BATCH_USERS
+            )));
+        }
+        log::debug!("gRPC BatchGetUsers {} ids from {}", user_ids.len(), caller);
+
+        let tx = self.begin().await?;
+        let users = user::Entity::find_users_by_ids(&tx, &user_ids).await?;
+        let found: HashSet<i64> = users.iter().map(|user| user.id).collect();
+        let missing_user_ids = user_ids.into_iter().filter(|id| !found.contains(id)).collect();
+
+        Ok(Response::new(pb::BatchGetUsersResponse {
+            users: users.into_iter().map(user_message).collect(),
+            missing_user_ids,
+        }))
+    }
+
+    async fn get_user_addresses(
+        &self,
+        request: Request<pb::GetUserAddressesRequest>,
+    ) -> Result<Response<pb::GetUserAddressesResponse>, Status> {
+        let caller = caller(&request);
+        let request = request.into_inner();
+        log::debug!("gRPC GetUserAddresses {} from {}", request.user_id, caller);
+
+        let tx = self.begin().await?;
+        let addresses = address::Entity::find_addresses_by_user_id(&tx, request.user_id).await?;
+        let addresses = addresses
+            .into_iter()
+            .filter(|address| request.include_inactive || address.status == address::Status::ACTIVE)
+            .map(address_message)
+            .collect();
+        Ok(Response::new(pb::GetUserAddressesResponse { addresses }))
+    }
+
+    /// Answers with `valid = false` and a reason instead of an error status, so callers
+    /// only see errors when the check itself could not run
+    async fn validate_session(
+        &self,
+        request: Request<pb::ValidateSessionRequest>,
+    ) -> Result<Response<pb::ValidateSessionResponse>, Status> {
+        let token = request.into_inner().access_token;
+        let claims = match UserClaims::decode(&token, &ACCESS_TOKEN_DECODE_KEY) {
+            Ok(data) => data.claims,
+            Err(e) => return Ok(Response::new(invalid_session(format!("Invalid access token: {}", e)))),
+        };
+
+        let session = self
+            .state
+            .redis
+            .get_key::<String>(&format!("refresh_token:session:{}", claims.sid))
+            .await
+            .map_err(|e| Status::unavailable(format!("Session store unavailable: {}", e)))?;
+        if session.is_none() {
+            return Ok(Response::new(invalid_session("Session has ended".to_string())));
+        }
+
+        let tx = self.begin().await?;
+        let user = user::Entity::find_users_by_ids(&tx, &[claims.user_id]).await?.into_iter().next();
+        let reason = match user {
+            None => Some("User no longer exists"),
+            Some(user) if user.status == user::Status::INACTIVE => Some("User is inactive"),
+            Some(_) => None,
+        };
+        if let Some(reason) = reason {
+            return Ok(Response::new(invalid_session(reason.to_string())));
+        }
+
+        Ok(Response::new(pb::ValidateSessionResponse {
+            valid: true,
+            user_id: claims.user_id,
+            session_id: claims.sid.to_string(),
+            roles: claims.roles,
+            expires_at: claims.exp,
+            reason: String::new(),
+        }))
+    }
+}
+
+impl From<AppError> for Status {
+    fn from(err: AppError) -> Self {
+        match err {
+            AppError::EntityNotFoundError { detail } => Status::not_found(detail),
+            AppError::InvalidPayloadError(detail) | AppError::BadRequestError(detail) => {
+                Status::invalid_argument(detail)
+            },
+            err => {
+                log::error!("gRPC request failed: {}", err);
+                Status::internal("Internal server error")
+            },
+        }
+    }
+}
+
+/// Calling service, as authenticated by the service token interceptor
+fn caller<T>(request: &Request<T>) -> String {
+    request
+        .extensions()
+        .get::<ServiceClaims>()
+        .map(|claims| claims.iss.clone())
+        .unwrap_or_default()
+}
+
+fn invalid_session(reason: String) -> pb::ValidateSessionResponse {
+    pb::ValidateSessionResponse { valid: false, reason, ..Default::default() }
+}
+
+fn user_message(user: user::Model) -> pb::User {
+    let status = match user.status {
+        user::Status::PENDING => pb::UserStatus::Pending,
+        user::Status::ACTIVE => pb::UserStatus::Active,
+        user::Status::INACTIVE => pb::UserStatus::Inactive,
+    };
+    let role = match user.role {
+        user::Role::CUSTOMER => "customer",
+        user::Role::ADMIN => "admin",
+    };
+
+    pb::User {
+        user_id: user.id,
+        username: user.username,
+        first_name: user.first_name,
+        last_name: user.last_name,
+        email: user.email,
+        phone_number: user.phone_number,
+        status: status.into(),
+        roles: vec![role.to_string()],
+        email_verified: user.email_verified_at.is_some(),
+    }
+}
+
+fn address_message(address: address::ModelEx) -> pb::Address {
+    let status = match address.status {
+        address::Status::ACTIVE => pb::AddressStatus::Active,
+        address::Status::INACTIVE => pb::AddressStatus::Inactive,
+    };
+
+    pb::Address {
+        address_id: address.id,
+        user_id: address.user_id,
+        title: address.title,
+        address_line_1: address.address_line_1,
+        address_line_2: address.address_line_2,
+        city: address.city,
+        country: address.country,
+        postal_code: address.postal_code,
+        landmark: address.landmark,
+        phone_number: address.phone_number,
+        status: status.into(),
+    }
+}
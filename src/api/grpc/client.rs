@@ -0,0 +1,54 @@
+use crate::api::grpc::pb::administration_service_client::AdministrationServiceClient;
+use crate::infrastructure::constant::GRPC_SERVICE_AUDIENCE;
+use std::sync::Arc;
+use tonic::metadata::MetadataValue;
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Status};
+use utils::internal_auth::ServiceTokenSigner;
+
+/// Administration API client used by other services, authenticated with their service token
+pub type AdministrationClient =
+    AdministrationServiceClient<InterceptedService<Channel, ServiceTokenInterceptor>>;
+
+/// Adds a freshly signed service token to every call
+#[derive(Clone)]
+pub struct ServiceTokenInterceptor {
+    signer: Arc<ServiceTokenSigner>,
+    audience: String,
+}
+
+impl ServiceTokenInterceptor {
+    pub fn new(signer: ServiceTokenSigner, audience: &str) -> Self {
+        Self {
+            signer: Arc::new(signer),
+            audience: audience.to_string(),
+        }
+    }
+}
+
+impl Interceptor for ServiceTokenInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let token = self
+            .signer
+            .sign(&self.audience)
+            .map_err(|e| Status::internal(format!("Failed to sign service token: {}", e)))?;
+        let value = MetadataValue::try_from(format!("Bearer {}", token))
+            .map_err(|e| Status::internal(format!("Invalid service token: {}", e)))?;
+        request.metadata_mut().insert("authorization", value);
+        Ok(request)
+    }
+}
+
+/// Connect to the gateway's administration API at `endpoint`, e.g. `http://api-gateway:50051`
+pub async fn connect(
+    endpoint: String,
+    signer: ServiceTokenSigner,
+) -> Result<AdministrationClient, tonic::transport::Error> {
+    let channel = Endpoint::from_shared(endpoint)?.connect().await?;
+    Ok(AdministrationServiceClient::with_interceptor(
+        channel,
+        ServiceTokenInterceptor::new(signer, GRPC_SERVICE_AUDIENCE),
+    ))
+}
@@ -0,0 +1,61 @@
+use crate::api::grpc::administration::AdministrationGrpc;
+use crate::api::grpc::pb::administration_service_server::AdministrationServiceServer;
+use crate::core::app_state::AppState;
+use crate::infrastructure::constant::GRPC_SERVICE_AUDIENCE;
+use crate::infrastructure::error::{AppError, AppResult};
+use std::net::SocketAddr;
+use tonic::{Request, Status};
+use utils::internal_auth::ServiceTokenVerifier;
+use utils::telemetry::http_request_span;
+
+pub mod administration;
+pub mod client;
+
+/// Code generated from `proto/administration.proto`
+pub mod pb {
+    tonic::include_proto!("administration");
+
+    /// Descriptors of the administration API, e.g. for gateway transcoding
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("administration_descriptor");
+}
+
+/// Serve the internal gRPC API on `grpc.port` until the process exits
+pub async fn serve(state: AppState) -> AppResult<()> {
+    let config = state.config.clone();
+    let addr: SocketAddr = format!("{}:{}", config.server.addr, config.grpc.port).parse()?;
+    let verifier = ServiceTokenVerifier::new(GRPC_SERVICE_AUDIENCE, &config.grpc.callers);
+
+    let administration = AdministrationServiceServer::with_interceptor(
+        AdministrationGrpc::new(state),
+        move |request| authenticate_service(&verifier, request),
+    );
+
+    log::info!("The gRPC server is listening on: {addr}");
+    tonic::transport::Server::builder()
+        .trace_fn(http_request_span)
+        .add_service(administration)
+        .serve(addr)
+        .await
+        .map_err(|e| AppError::UnknownError(e.into()))
+}
+
+/// Reject calls without a valid service token, keeping its claims for the handlers
+pub fn authenticate_service(
+    verifier: &ServiceTokenVerifier,
+    mut request: Request<()>,
+) -> Result<Request<()>, Status> {
+    let token = request
+        .metadata()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| Status::unauthenticated("Missing service token"))?;
+
+    let claims = verifier.verify(token).map_err(|e| {
+        log::warn!("Rejected gRPC service token: {}", e);
+        Status::unauthenticated("Invalid service token")
+    })?;
+    request.extensions_mut().insert(claims);
+    Ok(request)
+}
@@ -3,13 +3,20 @@ use axum::routing::{any, get};
 use crate::core::app_state::AppState;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
-use crate::infrastructure::gateway::routes::{gateway_health_check, list_services, proxy_to_inventory_service, proxy_to_notification_service, proxy_to_order_service, proxy_to_product_service};
+use crate::infrastructure::gateway;
+use crate::infrastructure::gateway::routes::{proxy_to_inventory_service, proxy_to_notification_service, proxy_to_order_service, proxy_to_product_service};
 
 pub mod domain;
+pub mod grpc;
 
 pub fn build_routes() -> OpenApiRouter<AppState> {
     let server_routes = OpenApiRouter::new()
-        .routes(routes!(domain::server::health_check));
+        .routes(routes!(domain::server::health_check))
+        .routes(routes!(domain::server::server_state))
+        .routes(routes!(domain::server::liveness))
+        .routes(routes!(domain::server::readiness))
+        .routes(routes!(domain::server::health_history))
+        .route("/metrics", get(domain::server::metrics));
 
     let auth_routes =
         OpenApiRouter::new()
@@ -34,9 +41,23 @@ pub fn build_routes() -> OpenApiRouter<AppState> {
         .routes(routes!(domain::address::address::controller_get_addresses_by_user_id))
         .routes(routes!(domain::address::address::controller_delete_address));
 
+    let admin_routes = OpenApiRouter::new()
+        .routes(routes!(domain::admin::dead_letter::controller_list_dead_letters))
+        .routes(routes!(domain::admin::dead_letter::controller_replay_dead_letter))
+        .routes(routes!(domain::admin::dead_letter::controller_discard_dead_letter))
+        .routes(routes!(domain::admin::webhook::controller_create_webhook_subscription))
+        .routes(routes!(domain::admin::webhook::controller_list_webhook_subscriptions))
+        .routes(routes!(domain::admin::webhook::controller_get_webhook_subscription))
+        .routes(routes!(domain::admin::webhook::controller_update_webhook_subscription))
+        .routes(routes!(domain::admin::webhook::controller_delete_webhook_subscription))
+        .routes(routes!(domain::admin::webhook::controller_list_webhook_deliveries))
+        .routes(routes!(domain::admin::webhook::controller_redeliver_webhook));
+
     let gateway_routes = OpenApiRouter::new()
-        .route("/gateway/health", get(gateway_health_check))
-        .route("/gateway/services", get(list_services))
+        .routes(routes!(gateway::routes::gateway_health_check))
+        .routes(routes!(gateway::routes::list_services))
+        .routes(routes!(gateway::routes::purge_cache))
+        .routes(routes!(gateway::routes::compose))
         .route("/gateway/product-service/{*path}", any(proxy_to_product_service))
         .route("/gateway/order-service/{*path}", any(proxy_to_order_service))
         .route("/gateway/inventory-service/{*path}", any(proxy_to_inventory_service))
@@ -46,6 +67,7 @@ pub fn build_routes() -> OpenApiRouter<AppState> {
         .merge(auth_routes)
         .merge(user_routes)
         .merge(address_routes)
+        .merge(admin_routes)
         .merge(gateway_routes)
         .merge(server_routes)
         .fallback(handler_404)
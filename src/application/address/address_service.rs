@@ -4,21 +4,24 @@ use crate::domain::address::address_repository_interface::AddressRepositoryInter
 use crate::domain::user::user_repository_interface::UserRepositoryInterface;
 use crate::infrastructure::persistence::redis_client::RedisConnectionPool;
 use crate::presentation::address::address::{AddressSerializer, CreateAddressRequest, UpdateAddressRequest};
-use rdkafka::producer::FutureProducer;
 use sea_orm::{DatabaseTransaction, IntoActiveModel};
 use std::sync::Arc;
 use crate::domain::address;
+use crate::domain::address::events::address_added::AddressAddedEvent;
+use crate::domain::address::events::address_removed::AddressRemovedEvent;
+use crate::domain::address::events::address_updated::AddressUpdatedEvent;
+use crate::domain::outbox::outbox;
+use crate::domain::outbox::outbox_repository_interface::OutboxRepositoryInterface;
 use crate::infrastructure::error::{AppError, AppResult};
 
 /// Application service - orchestrates domain logic, database, and external services
 pub struct AddressService {
     pub redis: Arc<RedisConnectionPool>,
-    pub kafka_producer: Arc<FutureProducer>,
 }
 
 impl AddressService {
-    pub fn new(redis: Arc<RedisConnectionPool>, kafka_producer: Arc<FutureProducer>) -> Self {
-        Self { redis, kafka_producer }
+    pub fn new(redis: Arc<RedisConnectionPool>) -> Self {
+        Self { redis }
     }
 }
 
@@ -46,8 +49,9 @@ impl AddressServiceInterface for AddressService {
         // Infrastructure: Persist address (Model → ActiveModel in repository)
         let created_address = Entity::create_address(conn, address.into_active_model()).await?;
 
-        // TODO: External service - Kafka event publishing
-        // self.kafka_producer.send(...)
+        // Published by the outbox relay once the transaction commits
+        outbox::Entity::enqueue_event(conn, AddressAddedEvent::from_address(&created_address)).await?;
+        log::info!("AddressAdded event queued for address_id: {}", created_address.id);
 
         Ok(true)
     }
@@ -66,18 +70,22 @@ impl AddressServiceInterface for AddressService {
             })?;
 
         // Domain: Update model with validation
-        let updated_model = existing_address.update_from(
+        let updated_model = existing_address.clone().update_from(
             &request
         )?;
+        let event = AddressUpdatedEvent::from_change(&existing_address, &updated_model, chrono::Utc::now());
 
         // Infrastructure: Persist updated address (Model → ActiveModel in repository)
-        let updated_address = Entity::update_address(conn, updated_model.into_active_model()).await?;
+        Entity::update_address(conn, updated_model.into_active_model()).await?;
 
         // TODO: External service - Clear related cache if needed
         // let _ = self.redis.delete_key(...).await;
 
-        // TODO: External service - Kafka event publishing
-        // self.kafka_producer.send(...)
+        // Published by the outbox relay once the transaction commits, only if something changed
+        if let Some(event) = event {
+            outbox::Entity::enqueue_event(conn, event).await?;
+            log::info!("AddressUpdated event queued for address_id: {}", id);
+        }
 
         Ok(true)
     }
@@ -103,18 +111,17 @@ impl AddressServiceInterface for AddressService {
         id: i64,
     ) -> AppResult<bool> {
         // Database: Check if address exists
-        let address = Entity::find_address_by_id(conn, id).await?;
-        if address.is_none() {
-            return Err(AppError::EntityNotFoundError {
-                detail: format!("Address with id {} not found", id),
-            });
-        }
+        let address = Entity::find_address_by_id(conn, id).await?.ok_or_else(|| AppError::EntityNotFoundError {
+            detail: format!("Address with id {} not found", id),
+        })?;
 
         // Database: Soft delete
         Entity::delete_address(conn, id).await?;
 
-        // TODO: External service - Kafka event publishing
-        // self.kafka_producer.send(...)
+        // Published by the outbox relay once the transaction commits
+        let event = AddressRemovedEvent::new(id, address.user_id, chrono::Utc::now());
+        outbox::Entity::enqueue_event(conn, event).await?;
+        log::info!("AddressRemoved event queued for address_id: {}", id);
 
         Ok(true)
     }
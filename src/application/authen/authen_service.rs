@@ -2,7 +2,6 @@ use crate::application::authen::authen_service_interface::AuthenServiceInterface
 use crate::infrastructure::persistence::redis_client::RedisConnectionPool;
 use crate::infrastructure::third_party::token;
 use crate::presentation::authen::authen::TokenResponse;
-use rdkafka::producer::FutureProducer;
 use sea_orm::{DatabaseTransaction, IntoActiveModel};
 use std::sync::Arc;
 use uuid::Uuid;
@@ -14,12 +13,11 @@ use crate::infrastructure::error::{AppError, AppResult};
 
 pub struct AuthenService {
     pub redis: Arc<RedisConnectionPool>,
-    pub kafka_producer: Arc<FutureProducer>,
 }
 
 impl AuthenService {
-    pub fn new(redis: Arc<RedisConnectionPool>, kafka_producer: Arc<FutureProducer>) -> Self {
-        Self { redis, kafka_producer }
+    pub fn new(redis: Arc<RedisConnectionPool>) -> Self {
+        Self { redis }
     }
 
 
@@ -31,20 +29,23 @@ impl AuthenServiceInterface for AuthenService {
         conn: &DatabaseTransaction,
         req: &LoginByEmailCommand
     ) -> AppResult<TokenResponse> {
-        use rdkafka::producer::FutureRecord;
-        use std::time::Duration as StdDuration;
+        use crate::domain::outbox::outbox;
+        use crate::domain::outbox::outbox_repository_interface::OutboxRepositoryInterface;
+        use utils::metrics::metrics;
         use crate::domain::user::events::user_logged_in::{UserLoggedInEvent, DeviceInfoEvent};
         use crate::presentation::authen::authen::UserInfo;
 
         // Find user by email
         let user_opt = user::Entity::find_user_by_email(conn, req.get_email()).await?;
 
-        let mut user = user_opt.ok_or_else(||
+        let mut user = user_opt.ok_or_else(|| {
+            metrics().record_login(false, "unknown_user");
             AppError::UnauthorizedError("Invalid email or password".to_string())
-        )?;
+        })?;
 
         // Validate login attempt (check account status, lock status, failed login limit)
         if let Err(err) = user.validate_login_attempt() {
+            metrics().record_login(false, "rejected");
             return Err(err);
         }
 
@@ -59,7 +60,12 @@ impl AuthenServiceInterface for AuthenService {
 
         if !password_valid {
             // Handle failed login: increment counter and potentially lock account
+            let locked_until = user.account_locked_until;
             let updated_user = user.handle_failed_login();
+            metrics().record_login(false, "invalid_password");
+            if updated_user.account_locked_until != locked_until {
+                metrics().account_lockouts_total.inc();
+            }
             user::Entity::update_user(conn, updated_user.into_active_model()).await?;
 
             return Err(AppError::UnauthorizedError("Invalid email or password".to_string()));
@@ -67,6 +73,7 @@ impl AuthenServiceInterface for AuthenService {
 
         // Handle successful login: reset failed attempts and update last_login_at
         user = user.handle_successful_login();
+        metrics().record_login(true, "none");
         user::Entity::update_user(conn, user.clone().into_active_model()).await?;
 
         // Generate session ID
@@ -107,21 +114,12 @@ impl AuthenServiceInterface for AuthenService {
             user.email.clone(),
             session_id.to_string(),
             device_info_event,
-            chrono::Utc::now().naive_utc(),
+            chrono::Utc::now(),
         );
 
-        let event_json = serde_json::to_string(&event)
-            .map_err(|e| AppError::BadRequestError(format!("Failed to serialize event: {}", e)))?;
-
-        let user_id_key = user.id.to_string();
-        let kafka_record = FutureRecord::to(UserLoggedInEvent::topic_name())
-            .payload(&event_json)
-            .key(&user_id_key);
-
-        match self.kafka_producer.send(kafka_record, StdDuration::from_secs(5)).await {
-            Ok(_) => log::info!("UserLoggedIn event published for user_id: {}", user.id),
-            Err(e) => log::error!("Failed to publish UserLoggedIn event: {:?}", e),
-        }
+        // Published by the outbox relay once the transaction commits
+        outbox::Entity::enqueue_event(conn, event).await?;
+        log::info!("UserLoggedIn event queued for user_id: {}", user.id);
 
         Ok(token_response)
     }
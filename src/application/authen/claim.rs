@@ -23,6 +23,8 @@ pub struct UserClaims {
     pub exp: i64,
     pub user_id: i64,
     pub sid: Uuid,
+    #[serde(default)]
+    pub roles: Vec<String>,
 }
 
 impl UserClaims {
@@ -30,6 +32,7 @@ impl UserClaims {
         duration: Duration,
         user_id: &i64,
         session_id: &Uuid,
+        roles: &[String],
     ) -> Self {
         let now = Utc::now().timestamp();
         Self {
@@ -37,6 +40,7 @@ impl UserClaims {
             exp: now + (duration.as_secs() as i64),
             user_id: *user_id,
             sid: *session_id,
+            roles: roles.to_vec(),
         }
     }
 
@@ -78,11 +82,12 @@ pub fn service_generate_tokens(
     session_id: &Uuid,
     user_info: crate::presentation::authen::authen::UserInfo,
 ) -> AppResult<TokenResponse> {
+    let roles = std::slice::from_ref(&user_info.role);
     let access_token =
-        UserClaims::new(EXPIRE_BEARER_TOKEN_SECS, user_id, session_id)
+        UserClaims::new(EXPIRE_BEARER_TOKEN_SECS, user_id, session_id, roles)
             .encode(&ACCESS_TOKEN_ENCODE_KEY)?;
     let refresh_token =
-        UserClaims::new(EXPIRE_REFRESH_TOKEN_SECS, user_id, session_id)
+        UserClaims::new(EXPIRE_REFRESH_TOKEN_SECS, user_id, session_id, roles)
             .encode(&REFRESH_TOKEN_ENCODE_KEY)?;
     Ok(TokenResponse::new(access_token, refresh_token, EXPIRE_BEARER_TOKEN_SECS.as_secs(), user_info))
 }
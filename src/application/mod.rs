@@ -1,3 +1,5 @@
 pub mod authen;
 pub mod user;
 pub mod address;
+pub mod webhook;
+pub mod projection;
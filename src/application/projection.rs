@@ -0,0 +1,13 @@
+use crate::application::user::user_event_handler::UserActivatedHandler;
+use crate::domain::user::events::user_activated::UserActivatedEvent;
+use crate::infrastructure::persistence::redis_client::RedisConnectionPool;
+use std::sync::Arc;
+use utils::events::DomainEvent;
+use utils::kafka_consumer::Projection;
+
+/// Projections `api-gateway replay --projection <name>` can rebuild
+pub fn projections(redis: Arc<RedisConnectionPool>) -> Vec<Projection> {
+    vec![Projection::new("user-profile-cache")
+        .register(UserActivatedEvent::TOPIC, UserActivatedEvent::EVENT_TYPE, UserActivatedHandler::new(redis))
+        .with_side_effects()]
+}
@@ -1,3 +1,4 @@
 pub mod user_command;
 pub mod user_service;
 pub mod user_service_interface;
+pub mod user_event_handler;
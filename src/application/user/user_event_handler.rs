@@ -0,0 +1,36 @@
+use crate::domain::user::events::user_activated::UserActivatedEvent;
+use crate::infrastructure::persistence::redis_client::RedisConnectionPool;
+use async_trait::async_trait;
+use sea_orm::DatabaseTransaction;
+use std::sync::Arc;
+use utils::events::EventEnvelope;
+use utils::kafka_consumer::{EventHandler, HandlerError, RecordMeta};
+
+/// Evicts the cached profile of a newly activated user, which still shows it as pending
+pub struct UserActivatedHandler {
+    pub redis: Arc<RedisConnectionPool>,
+}
+
+impl UserActivatedHandler {
+    pub fn new(redis: Arc<RedisConnectionPool>) -> Self {
+        Self { redis }
+    }
+}
+
+#[async_trait]
+impl EventHandler for UserActivatedHandler {
+    type Event = UserActivatedEvent;
+
+    async fn handle(
+        &self,
+        _tx: &DatabaseTransaction,
+        event: EventEnvelope<UserActivatedEvent>,
+        _meta: &RecordMeta,
+    ) -> Result<(), HandlerError> {
+        self.redis
+            .delete_key(&format!("profile:user_id:{}", event.data.user_id))
+            .await
+            .map_err(|e| HandlerError::Transient(e.to_string()))?;
+        Ok(())
+    }
+}
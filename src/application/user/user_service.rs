@@ -6,28 +6,29 @@ use crate::presentation::user::user::{UserSerializer, CreateUserRequest, UpdateU
 use crate::api::domain::business_rule_interface::BusinessRuleInterface;
 use crate::domain::user::rules::*;
 use log::error;
-use rdkafka::producer::FutureProducer;
-use rdkafka::producer::FutureRecord;
 use sea_orm::{DatabaseTransaction, IntoActiveModel, Set};
 use std::sync::Arc;
-use std::time::Duration;
 use crate::application::authen::claim::hash;
 use crate::domain::user;
 use crate::domain::user::events::user_registered::UserRegisteredEvent;
 use crate::domain::user::events::user_activated::UserActivatedEvent;
+use crate::domain::user::events::user_created::UserCreatedEvent;
+use crate::domain::user::events::user_deleted::UserDeletedEvent;
+use crate::domain::user::events::user_updated::UserUpdatedEvent;
 use crate::domain::user::verification::generate_verification_token;
 use crate::infrastructure::error::{AppError, AppResult};
+use crate::domain::outbox::outbox;
+use crate::domain::outbox::outbox_repository_interface::OutboxRepositoryInterface;
 
 /// Application service - orchestrates domain logic, database, and external services
 #[derive()]
 pub struct UserService {
     pub redis: Arc<RedisConnectionPool>,
-    pub kafka_producer: Arc<FutureProducer>,
 }
 
 impl UserService {
-    pub fn new(redis: Arc<RedisConnectionPool>, kafka_producer: Arc<FutureProducer>) -> Self {
-        Self { redis, kafka_producer }
+    pub fn new(redis: Arc<RedisConnectionPool>) -> Self {
+        Self { redis }
     }
 }
 
@@ -71,28 +72,20 @@ impl UserServiceInterface for UserService {
 
         let created_user = active_user.insert(conn).await?;
 
-        // Publish UserRegistered event to Kafka
+        // Publish UserCreated for read models, then UserRegistered to send the verification email
+        outbox::Entity::enqueue_event(conn, UserCreatedEvent::from_user(&created_user)).await?;
+
         let event = UserRegisteredEvent::new(
             created_user.id,
             created_user.email.clone(),
             format!("{} {}", created_user.first_name, created_user.last_name),
             verification_token.clone(),
-            created_user.created_at.unwrap_or_else(|| chrono::Utc::now().naive_utc()),
+            created_user.created_at.map(|created_at| created_at.and_utc()).unwrap_or_else(chrono::Utc::now),
         );
 
-        let event_json = serde_json::to_string(&event)
-            .map_err(|e| AppError::BadRequestError(format!("Failed to serialize event: {}", e)))?;
-
-        let user_id_key = created_user.id.to_string();
-        let kafka_record = FutureRecord::to(UserRegisteredEvent::topic_name())
-            .payload(&event_json)
-            .key(&user_id_key);
-
-        // Send event asynchronously
-        match self.kafka_producer.send(kafka_record, Duration::from_secs(5)).await {
-            Ok(_) => log::info!("UserRegistered event published for user_id: {}", created_user.id),
-            Err(e) => log::error!("Failed to publish UserRegistered event: {:?}", e),
-        }
+        // Published by the outbox relay once the transaction commits
+        outbox::Entity::enqueue_event(conn, event).await?;
+        log::info!("UserRegistered event queued for user_id: {}", created_user.id);
 
         // Return response
         Ok(UserCreatedSerializer {
@@ -118,7 +111,7 @@ impl UserServiceInterface for UserService {
         // Verify email (domain layer enforces business rules)
         let verified_user = user.verify_email()?;
 
-        let verified_at = verified_user.email_verified_at.unwrap_or_else(|| chrono::Utc::now().naive_utc());
+        let verified_at = verified_user.email_verified_at.map(|verified_at| verified_at.and_utc()).unwrap_or_else(chrono::Utc::now);
         let user_id = verified_user.id;
         let user_email = verified_user.email.clone();
 
@@ -132,18 +125,9 @@ impl UserServiceInterface for UserService {
             verified_at,
         );
 
-        let event_json = serde_json::to_string(&event)
-            .map_err(|e| AppError::BadRequestError(format!("Failed to serialize event: {}", e)))?;
-
-        let user_id_key = user_id.to_string();
-        let kafka_record = FutureRecord::to(UserActivatedEvent::topic_name())
-            .payload(&event_json)
-            .key(&user_id_key);
-
-        match self.kafka_producer.send(kafka_record, Duration::from_secs(5)).await {
-            Ok(_) => log::info!("UserActivated event published for user_id: {}", user_id),
-            Err(e) => log::error!("Failed to publish UserActivated event: {:?}", e),
-        }
+        // Published by the outbox relay once the transaction commits
+        outbox::Entity::enqueue_event(conn, event).await?;
+        log::info!("UserActivated event queued for user_id: {}", user_id);
 
         Ok(true)
     }
@@ -175,21 +159,12 @@ impl UserServiceInterface for UserService {
             updated_user.email.clone(),
             format!("{} {}", updated_user.first_name, updated_user.last_name),
             new_token,
-            chrono::Utc::now().naive_utc(),
+            chrono::Utc::now(),
         );
 
-        let event_json = serde_json::to_string(&event)
-            .map_err(|e| AppError::BadRequestError(format!("Failed to serialize event: {}", e)))?;
-
-        let user_id_key = updated_user.id.to_string();
-        let kafka_record = FutureRecord::to(UserRegisteredEvent::topic_name())
-            .payload(&event_json)
-            .key(&user_id_key);
-
-        match self.kafka_producer.send(kafka_record, Duration::from_secs(5)).await {
-            Ok(_) => log::info!("Verification email resent for user_id: {}", updated_user.id),
-            Err(e) => log::error!("Failed to publish resend verification event: {:?}", e),
-        }
+        // Published by the outbox relay once the transaction commits
+        outbox::Entity::enqueue_event(conn, event).await?;
+        log::info!("Verification email queued for user_id: {}", updated_user.id);
 
         Ok(true)
     }
@@ -225,9 +200,9 @@ impl UserServiceInterface for UserService {
         // Infrastructure: Persist user (Model → ActiveModel in repository)
         let created_user = user::user::Entity::create_user(conn, user.into_active_model()).await?;
 
-
-        // TODO: External service - Kafka event publishing
-        // self.kafka_producer.send(...)
+        // Published by the outbox relay once the transaction commits
+        outbox::Entity::enqueue_event(conn, UserCreatedEvent::from_user(&created_user)).await?;
+        log::info!("UserCreated event queued for user_id: {}", created_user.id);
 
         Ok(true)
     }
@@ -258,18 +233,22 @@ impl UserServiceInterface for UserService {
         // Convert ModelEx to Model (remove relationships for update)
 
         // Domain: Update model with validation
-        let updated_model = existing_user.update_from(
+        let updated_model = existing_user.clone().update_from(
             &request
         )?;
+        let event = UserUpdatedEvent::from_change(&existing_user, &updated_model, chrono::Utc::now());
 
         // Infrastructure: Persist updated user (Model → ActiveModel in repository)
-        let updated_user = user::user::Entity::update_user(conn, updated_model.into_active_model()).await?;
+        user::user::Entity::update_user(conn, updated_model.into_active_model()).await?;
 
         // External service: Clear Redis cache
         // let _ = self.redis..delete_key(&format!("profile:user_id:{}", id).to_string().into()).await;
 
-        // TODO: External service - Kafka event publishing
-        // self.kafka_producer.send(...)
+        // Published by the outbox relay once the transaction commits, only if something changed
+        if let Some(event) = event {
+            outbox::Entity::enqueue_event(conn, event).await?;
+            log::info!("UserUpdated event queued for user_id: {}", id);
+        }
 
         Ok(true)
     }
@@ -339,8 +318,9 @@ impl UserServiceInterface for UserService {
         // External service: Clear Redis cache
         let _ = self.redis.delete_key(&format!("profile:user_id:{}", id)).await;
 
-        // TODO: External service - Kafka event publishing
-        // self.kafka_producer.send(...)
+        // Published by the outbox relay once the transaction commits
+        outbox::Entity::enqueue_event(conn, UserDeletedEvent::new(id, chrono::Utc::now())).await?;
+        log::info!("UserDeleted event queued for user_id: {}", id);
 
         Ok(true)
     }
@@ -0,0 +1,3 @@
+pub mod webhook_event_handler;
+pub mod webhook_service;
+pub mod webhook_service_interface;
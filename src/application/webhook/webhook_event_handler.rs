@@ -0,0 +1,62 @@
+use crate::domain::webhook::webhook_delivery;
+use crate::domain::webhook::webhook_payload::partner_payload;
+use crate::domain::webhook::webhook_repository_interface::{
+    WebhookDeliveryRepositoryInterface, WebhookSubscriptionRepositoryInterface,
+};
+use crate::domain::webhook::webhook_subscription;
+use async_trait::async_trait;
+use sea_orm::{DatabaseTransaction, Set};
+use serde_json::Value;
+use utils::events::EventEnvelope;
+use utils::kafka_consumer::{EventHandler, HandlerError, RecordMeta};
+
+/// Queues a webhook delivery of the event for every active subscription asking for it
+///
+/// Registered for each event in `[webhook] events` with the webhook consumer group; the
+/// deliveries are sent by the `WebhookDispatcher`. Only the `partner_payload` of the envelope
+/// is stored, so internal fields never reach a delivery or its signature.
+pub struct WebhookEventHandler;
+
+#[async_trait]
+impl EventHandler for WebhookEventHandler {
+    type Event = Value;
+
+    async fn handle(
+        &self,
+        tx: &DatabaseTransaction,
+        event: EventEnvelope<Value>,
+        _meta: &RecordMeta,
+    ) -> Result<(), HandlerError> {
+        let Some(event) = partner_payload(&event) else {
+            log::warn!("{} is not sent to partners, not queueing webhooks for {}", event.event_type, event.id);
+            return Ok(());
+        };
+        let subscriptions = webhook_subscription::Entity::find_active_subscriptions(tx)
+            .await
+            .map_err(|e| HandlerError::Transient(e.to_string()))?;
+        let payload = serde_json::to_string(&event)?;
+        let now = chrono::Utc::now().naive_utc();
+
+        for subscription in subscriptions.iter().filter(|subscription| subscription.matches(&event.event_type)) {
+            let delivery = webhook_delivery::ActiveModel {
+                subscription_id: Set(subscription.id),
+                event_id: Set(event.id),
+                event_type: Set(event.event_type.clone()),
+                payload: Set(payload.clone()),
+                status: Set(webhook_delivery::Status::PENDING),
+                attempts: Set(0),
+                response_status: Set(None),
+                last_error: Set(None),
+                next_attempt_at: Set(now),
+                created_at: Set(now),
+                delivered_at: Set(None),
+                ..Default::default()
+            };
+            webhook_delivery::Entity::create_delivery(tx, delivery)
+                .await
+                .map_err(|e| HandlerError::Transient(e.to_string()))?;
+            log::debug!("Queued {} {} for webhook subscription {}", event.event_type, event.id, subscription.id);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use crate::application::webhook::webhook_service_interface::WebhookServiceInterface;
+use crate::core::configure::webhook::WebhookConfig;
+use crate::domain::webhook::webhook_repository_interface::{
+    WebhookDeliveryRepositoryInterface, WebhookSubscriptionRepositoryInterface,
+};
+use crate::domain::webhook::{webhook_delivery, webhook_subscription};
+use crate::infrastructure::constant::WEBHOOK_MAX_DELIVERY_LIST;
+use crate::infrastructure::error::{AppError, AppResult};
+use crate::presentation::admin::webhook::{
+    CreateWebhookSubscriptionRequest, UpdateWebhookSubscriptionRequest, WebhookDeliverySerializer,
+    WebhookSubscriptionSerializer,
+};
+use reqwest::Url;
+use sea_orm::{DatabaseTransaction, Set};
+
+/// Application service - manages partners' webhook subscriptions and their delivery log
+pub struct WebhookService {
+    pub config: WebhookConfig,
+}
+
+impl WebhookService {
+    pub fn new(config: WebhookConfig) -> Self {
+        Self { config }
+    }
+
+    async fn find_subscription(&self, conn: &DatabaseTransaction, id: i64) -> AppResult<webhook_subscription::Model> {
+        webhook_subscription::Entity::find_subscription_by_id(conn, id)
+            .await?
+            .ok_or_else(|| AppError::EntityNotFoundError {
+                detail: format!("Webhook subscription with id {} not found", id),
+            })
+    }
+
+    fn validate_url(url: &str) -> AppResult<()> {
+        let parsed = Url::parse(url).map_err(|e| AppError::BadRequestError(format!("Invalid webhook URL: {}", e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(AppError::BadRequestError("Webhook URL must be http or https".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Every pattern has to match at least one event partners can subscribe to
+    fn validate_event_types(&self, event_types: &[String]) -> AppResult<()> {
+        if event_types.is_empty() {
+            return Err(AppError::BadRequestError("At least one event type is required".to_string()));
+        }
+        if let Some(unknown) = event_types.iter().find(|pattern| !self.config.is_known_pattern(pattern)) {
+            let known: Vec<&str> = self.config.events.iter().map(|event| event.event_type.as_str()).collect();
+            return Err(AppError::BadRequestError(format!(
+                "Unknown event type {}, expected one of {:?}",
+                unknown, known
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn generate_secret() -> String {
+    format!("whsec_{}", hex::encode(rand::random::<[u8; 32]>()))
+}
+
+impl WebhookServiceInterface for WebhookService {
+    async fn create_subscription(
+        &self,
+        conn: &DatabaseTransaction,
+        request: CreateWebhookSubscriptionRequest,
+    ) -> AppResult<WebhookSubscriptionSerializer> {
+        if request.owner.trim().is_empty() {
+            return Err(AppError::BadRequestError("Owner is required".to_string()));
+        }
+        Self::validate_url(&request.url)?;
+        self.validate_event_types(&request.event_types)?;
+
+        let secret = request.secret.filter(|secret| !secret.is_empty()).unwrap_or_else(generate_secret);
+        let now = chrono::Utc::now().naive_utc();
+        let subscription = webhook_subscription::ActiveModel {
+            owner: Set(request.owner),
+            url: Set(request.url),
+            secret: Set(secret.clone()),
+            event_types: Set(serde_json::to_value(&request.event_types)?),
+            status: Set(webhook_subscription::Status::ACTIVE),
+            consecutive_failures: Set(0),
+            disabled_reason: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        };
+        let subscription = webhook_subscription::Entity::create_subscription(conn, subscription).await?;
+        log::info!("Created webhook subscription {} for {}", subscription.id, subscription.owner);
+
+        // The secret is shown once, the partner needs it to verify signatures
+        Ok(WebhookSubscriptionSerializer { secret: Some(secret), ..subscription.into() })
+    }
+
+    async fn update_subscription(
+        &self,
+        conn: &DatabaseTransaction,
+        id: i64,
+        request: UpdateWebhookSubscriptionRequest,
+    ) -> AppResult<WebhookSubscriptionSerializer> {
+        let mut subscription: webhook_subscription::ActiveModel = self.find_subscription(conn, id).await?.into();
+
+        if let Some(url) = request.url {
+            Self::validate_url(&url)?;
+            subscription.url = Set(url);
+        }
+        if let Some(event_types) = request.event_types {
+            self.validate_event_types(&event_types)?;
+            subscription.event_types = Set(serde_json::to_value(&event_types)?);
+        }
+        match request.enabled {
+            Some(true) => {
+                subscription.status = Set(webhook_subscription::Status::ACTIVE);
+                subscription.consecutive_failures = Set(0);
+                subscription.disabled_reason = Set(None);
+            },
+            Some(false) => {
+                subscription.status = Set(webhook_subscription::Status::DISABLED);
+                subscription.disabled_reason = Set(Some("Disabled by an admin".to_string()));
+            },
+            None => {},
+        }
+        subscription.updated_at = Set(chrono::Utc::now().naive_utc());
+
+        let subscription = webhook_subscription::Entity::update_subscription(conn, subscription).await?;
+        Ok(WebhookSubscriptionSerializer::from(subscription))
+    }
+
+    async fn get_subscription(
+        &self,
+        conn: &DatabaseTransaction,
+        id: i64,
+    ) -> AppResult<WebhookSubscriptionSerializer> {
+        Ok(WebhookSubscriptionSerializer::from(self.find_subscription(conn, id).await?))
+    }
+
+    async fn list_subscriptions(
+        &self,
+        conn: &DatabaseTransaction,
+        owner: Option<&str>,
+    ) -> AppResult<Vec<WebhookSubscriptionSerializer>> {
+        let subscriptions = webhook_subscription::Entity::find_subscriptions(conn, owner).await?;
+        Ok(subscriptions.into_iter().map(WebhookSubscriptionSerializer::from).collect())
+    }
+
+    async fn delete_subscription(
+        &self,
+        conn: &DatabaseTransaction,
+        id: i64,
+    ) -> AppResult<bool> {
+        if !webhook_subscription::Entity::delete_subscription(conn, id).await? {
+            return Err(AppError::EntityNotFoundError {
+                detail: format!("Webhook subscription with id {} not found", id),
+            });
+        }
+        log::info!("Deleted webhook subscription {}", id);
+        Ok(true)
+    }
+
+    async fn list_deliveries(
+        &self,
+        conn: &DatabaseTransaction,
+        subscription_id: i64,
+        status: Option<webhook_delivery::Status>,
+        limit: u64,
+    ) -> AppResult<Vec<WebhookDeliverySerializer>> {
+        self.find_subscription(conn, subscription_id).await?;
+        let deliveries = webhook_delivery::Entity::find_deliveries_by_subscription(
+            conn,
+            subscription_id,
+            status,
+            limit.min(WEBHOOK_MAX_DELIVERY_LIST),
+        )
+        .await?;
+        Ok(deliveries.into_iter().map(WebhookDeliverySerializer::from).collect())
+    }
+
+    async fn redeliver(
+        &self,
+        conn: &DatabaseTransaction,
+        delivery_id: i64,
+    ) -> AppResult<bool> {
+        let delivery = webhook_delivery::Entity::find_delivery_by_id(conn, delivery_id)
+            .await?
+            .ok_or_else(|| AppError::EntityNotFoundError {
+                detail: format!("Webhook delivery with id {} not found", delivery_id),
+            })?;
+        if delivery.status == webhook_delivery::Status::PENDING {
+            return Err(AppError::ConflictError(format!(
+                "Webhook delivery {} is still pending",
+                delivery_id
+            )));
+        }
+        let subscription = self.find_subscription(conn, delivery.subscription_id).await?;
+        if subscription.status != webhook_subscription::Status::ACTIVE {
+            return Err(AppError::ConflictError(format!(
+                "Webhook subscription {} is disabled, enable it before redelivering",
+                subscription.id
+            )));
+        }
+
+        webhook_delivery::Entity::requeue_delivery(conn, delivery_id).await?;
+        log::info!("Queued webhook delivery {} for redelivery", delivery_id);
+        Ok(true)
+    }
+}
@@ -0,0 +1,54 @@
+use crate::domain::webhook::webhook_delivery;
+use crate::infrastructure::error::AppResult;
+use crate::presentation::admin::webhook::{
+    CreateWebhookSubscriptionRequest, UpdateWebhookSubscriptionRequest, WebhookDeliverySerializer,
+    WebhookSubscriptionSerializer,
+};
+use sea_orm::DatabaseTransaction;
+
+pub trait WebhookServiceInterface: Send + Sync + 'static {
+    async fn create_subscription(
+        &self,
+        conn: &DatabaseTransaction,
+        request: CreateWebhookSubscriptionRequest,
+    ) -> AppResult<WebhookSubscriptionSerializer>;
+
+    async fn update_subscription(
+        &self,
+        conn: &DatabaseTransaction,
+        id: i64,
+        request: UpdateWebhookSubscriptionRequest,
+    ) -> AppResult<WebhookSubscriptionSerializer>;
+
+    async fn get_subscription(
+        &self,
+        conn: &DatabaseTransaction,
+        id: i64,
+    ) -> AppResult<WebhookSubscriptionSerializer>;
+
+    async fn list_subscriptions(
+        &self,
+        conn: &DatabaseTransaction,
+        owner: Option<&str>,
+    ) -> AppResult<Vec<WebhookSubscriptionSerializer>>;
+
+    async fn delete_subscription(
+        &self,
+        conn: &DatabaseTransaction,
+        id: i64,
+    ) -> AppResult<bool>;
+
+    async fn list_deliveries(
+        &self,
+        conn: &DatabaseTransaction,
+        subscription_id: i64,
+        status: Option<webhook_delivery::Status>,
+        limit: u64,
+    ) -> AppResult<Vec<WebhookDeliverySerializer>>;
+
+    async fn redeliver(
+        &self,
+        conn: &DatabaseTransaction,
+        delivery_id: i64,
+    ) -> AppResult<bool>;
+}
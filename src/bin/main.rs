@@ -2,18 +2,118 @@ use argon2::password_hash::SaltString;
 use argon2::{Argon2, PasswordHasher};
 use api_gateway::infrastructure::error::{AppError, AppResult};
 use api_gateway::core::http::server::AppServer;
-use api_gateway::infrastructure::constant::CONFIG;
-use log::{error, info, LevelFilter};
+use api_gateway::application::projection::projections;
+use api_gateway::application::user::user_event_handler::UserActivatedHandler;
+use api_gateway::application::webhook::webhook_event_handler::WebhookEventHandler;
+use api_gateway::core::configure::app::AppConfig;
+use api_gateway::domain::user::events::user_activated::UserActivatedEvent;
+use api_gateway::infrastructure::constant::{CONFIG, EVENT_CONSUMER_GROUP_ID, WEBHOOK_CONSUMER_GROUP_ID};
+use api_gateway::infrastructure::persistence::postgres::{DatabaseClient, DatabaseClientExt};
+use api_gateway::infrastructure::persistence::redis_client::RedisConnectionPool;
+use clap::{Parser, Subcommand};
+use log::{error, info};
+use std::sync::Arc;
+use utils::events::DomainEvent;
+use utils::kafka_consumer::{ConsumerRuntime, EventReplay, ReplayArgs, ReplayError};
+use utils::metrics::init_metrics;
+use utils::telemetry::init_telemetry;
 use rand::rngs::OsRng;
+
+#[derive(Parser)]
+#[command(name = "api-gateway")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve HTTP and consume events (the default)
+    Serve,
+    /// Re-read a topic into a projection, e.g. to rebuild a read model
+    Replay(ReplayArgs),
+}
+
 #[tokio::main]
 async fn main() -> AppResult<()> {
-    env_logger::builder().filter_level(LevelFilter::Debug).format_target(true).init();
+    let cli = Cli::parse();
+    let config = CONFIG.clone();
+    let _telemetry = init_telemetry("api-gateway", &config.telemetry)
+        .map_err(|e| AppError::UnknownError(e.into()))?;
+    init_metrics("api-gateway");
 
     info!("The initialization of Tracing was successful!");
-    let config = CONFIG.clone();
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(config).await,
+        Command::Replay(args) => replay(config, args).await,
+    }
+}
+
+async fn serve(config: AppConfig) -> AppResult<()> {
     let server = AppServer::new(config).await?;
     let db = server.state.db.clone();
     let redis = server.state.redis.clone();
+
+    let health_state = server.state.clone();
+    tokio::spawn(async move {
+        let monitor = health_state.health_monitor.clone();
+        monitor.run(health_state).await;
+    });
+
+    // Without a producer there is no broker, so neither consumer is built
+    let kafka_producer = server.state.kafka_producer.clone();
+    let consumer = kafka_producer.clone().map(|producer| {
+        ConsumerRuntime::new(
+            server.state.config.kafka.consumer_client_config(),
+            EVENT_CONSUMER_GROUP_ID,
+            db.clone(),
+            producer,
+        )
+        .register(
+            UserActivatedEvent::TOPIC,
+            UserActivatedEvent::EVENT_TYPE,
+            UserActivatedHandler::new(redis.clone()),
+        )
+    });
+    // Its own group, so webhooks neither slow down nor share dead letters with the handlers above
+    let webhook_consumer = kafka_producer.filter(|_| server.state.config.webhook.enabled).map(|producer| {
+        server.state.config.webhook.events.iter().fold(
+            ConsumerRuntime::new(
+                server.state.config.kafka.consumer_client_config(),
+                WEBHOOK_CONSUMER_GROUP_ID,
+                db.clone(),
+                producer,
+            ),
+            |runtime, event| runtime.register(&event.topic, &event.event_type, WebhookEventHandler),
+        )
+    });
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let mut consumer_shutdown = shutdown_rx.clone();
+    let consumer_task = tokio::spawn(async move {
+        let shutdown = async {
+            let _ = consumer_shutdown.wait_for(|stopped| *stopped).await;
+        };
+        match consumer {
+            None => info!("Kafka is disabled, not consuming events"),
+            Some(consumer) => {
+                if let Err(e) = consumer.run(shutdown).await {
+                    error!("Kafka consumer error: {:?}", e);
+                }
+            },
+        }
+    });
+    let mut webhook_shutdown = shutdown_rx;
+    let webhook_consumer_task = tokio::spawn(async move {
+        let shutdown = async {
+            let _ = webhook_shutdown.wait_for(|stopped| *stopped).await;
+        };
+        if let Some(webhook_consumer) = webhook_consumer {
+            if let Err(e) = webhook_consumer.run(shutdown).await {
+                error!("Webhook consumer error: {:?}", e);
+            }
+        }
+    });
+
     info!("Starting server...");
 
     let server_task = tokio::spawn(async {
@@ -22,7 +122,56 @@ async fn main() -> AppResult<()> {
         }
     });
 
-    let _server_result = tokio::join!(server_task);
+    tokio::select! {
+        _ = server_task => {},
+        _ = tokio::signal::ctrl_c() => info!("Shutting down..."),
+    }
+
+    // Let in-flight events finish and their offsets commit before exiting
+    let _ = shutdown_tx.send(true);
+    let _ = consumer_task.await;
+    let _ = webhook_consumer_task.await;
+
+    Ok(())
+}
+
+async fn replay(config: AppConfig, args: ReplayArgs) -> AppResult<()> {
+    let db = Arc::new(DatabaseClient::build_from_config(&config).await?);
+    let redis = Arc::new(
+        RedisConnectionPool::new(&config.redis.get_url())
+            .await
+            .map_err(|e| AppError::BadRequestError(e.to_string()))?,
+    );
+
+    let projection = projections(redis)
+        .into_iter()
+        .find(|projection| projection.name() == args.projection)
+        .ok_or_else(|| AppError::BadRequestError(format!("Unknown projection {}", args.projection)))?;
+    if !projection.topics().contains(args.topic.as_str()) {
+        return Err(AppError::BadRequestError(format!(
+            "Projection {} does not read {}, it reads {:?}",
+            args.projection,
+            args.topic,
+            projection.topics()
+        )));
+    }
+
+    let report = EventReplay::new(config.kafka.consumer_client_config(), db)
+        .with_partition(args.partition)
+        .with_dry_run(args.dry_run)
+        .run(&projection, &args.topic, args.start())
+        .await
+        .map_err(|e| match e {
+            ReplayError::DryRunWithSideEffects(_) => AppError::BadRequestError(e.to_string()),
+            ReplayError::Kafka(e) => AppError::UnknownError(e.into()),
+        })?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
 
+    if !report.failed.is_empty() {
+        return Err(AppError::UnknownError(anyhow::anyhow!(
+            "{} records failed to replay",
+            report.failed.len()
+        )));
+    }
     Ok(())
 }
@@ -1,14 +1,23 @@
 use crate::core::configure::app::AppConfig;
-use crate::core::configure::kafka::KafkaConfig;
 use crate::infrastructure::persistence::postgres::{DatabaseClient, DatabaseClientExt};
 use crate::infrastructure::persistence::redis_client::RedisConnectionPool;
 use crate::application::user::user_service::UserService;
 use crate::application::authen::authen_service::AuthenService;
 use crate::application::address::address_service::AddressService;
+use crate::application::webhook::webhook_service::WebhookService;
+use crate::infrastructure::gateway::aggregation::CompositeRegistry;
+use crate::infrastructure::gateway::openapi_aggregator::OpenApiAggregator;
+use crate::infrastructure::gateway::grpc::GrpcGateway;
+use crate::infrastructure::gateway::resilience::ResilienceRegistry;
+use crate::infrastructure::gateway::response_cache::ResponseCache;
 use crate::infrastructure::gateway::service_registry::ServiceRegistry;
+use crate::infrastructure::health::health_monitor::HealthMonitor;
 
 use rdkafka::producer::FutureProducer;
 use std::sync::Arc;
+use utils::events::publisher::EventPublisher;
+use utils::internal_auth::InternalTokenSigner;
+use crate::infrastructure::constant::EXPIRE_INTERNAL_CONTEXT_SECS;
 use crate::infrastructure::error::{AppError, AppResult};
 
 #[derive(Clone)]
@@ -16,11 +25,22 @@ pub struct AppState {
     pub config: Arc<AppConfig>,
     pub db: Arc<DatabaseClient>,
     pub redis: Arc<RedisConnectionPool>,
-    pub kafka_producer: Arc<FutureProducer>,
+    /// Only built for the `kafka` publisher, the other profiles start without a broker
+    pub kafka_producer: Option<Arc<FutureProducer>>,
+    /// Used by the outbox relay; services write their events to the outbox instead
+    pub event_publisher: Arc<dyn EventPublisher>,
     pub user_service: Arc<UserService>,
     pub authen_service: Arc<AuthenService>,
     pub address_service: Arc<AddressService>,
+    pub webhook_service: Arc<WebhookService>,
     pub gateway_registry: Arc<ServiceRegistry>,
+    pub gateway_resilience: Arc<ResilienceRegistry>,
+    pub internal_token_signer: Arc<InternalTokenSigner>,
+    pub health_monitor: Arc<HealthMonitor>,
+    pub openapi_aggregator: Arc<OpenApiAggregator>,
+    pub response_cache: Arc<ResponseCache>,
+    pub grpc_gateway: Arc<GrpcGateway>,
+    pub composite_registry: Arc<CompositeRegistry>,
 }
 
 impl AppState {
@@ -33,14 +53,23 @@ impl AppState {
                 .await
                 .map_err(|e| AppError::BadRequestError(e.to_string()))?
         );
-        let kafka_producer = Arc::new(KafkaConfig::new().create_kafka_producer());
-        let authen_service =
-            Arc::new(AuthenService::new(redis.clone(), kafka_producer.clone()));
-        let user_service =
-            Arc::new(UserService::new(redis.clone(), kafka_producer.clone()));
-        let address_service =
-            Arc::new(AddressService::new(redis.clone(), kafka_producer.clone()));
+        let kafka_producer = config.kafka.enabled().then(|| Arc::new(config.kafka.create_kafka_producer()));
+        let event_publisher = config.kafka.create_event_publisher(kafka_producer.clone());
+        let authen_service = Arc::new(AuthenService::new(redis.clone()));
+        let user_service = Arc::new(UserService::new(redis.clone()));
+        let address_service = Arc::new(AddressService::new(redis.clone()));
+        let webhook_service = Arc::new(WebhookService::new(config.webhook.clone()));
         let gateway_registry = Arc::new(ServiceRegistry::with_defaults().await);
+        let gateway_resilience = Arc::new(ResilienceRegistry::new());
+        let internal_token_signer = Arc::new(InternalTokenSigner::new(
+            &config.secret.internal_token_secret,
+            EXPIRE_INTERNAL_CONTEXT_SECS,
+        ));
+        let health_monitor = Arc::new(HealthMonitor::new());
+        let openapi_aggregator = Arc::new(OpenApiAggregator::new());
+        let response_cache = Arc::new(ResponseCache::new(&redis));
+        let grpc_gateway = Arc::new(GrpcGateway::new());
+        let composite_registry = Arc::new(CompositeRegistry::with_defaults().await);
 
         Ok(Self {
             config,
@@ -48,15 +77,24 @@ impl AppState {
             redis,
             authen_service,
             kafka_producer,
+            event_publisher,
             user_service,
             address_service,
+            webhook_service,
             gateway_registry,
+            gateway_resilience,
+            internal_token_signer,
+            health_monitor,
+            openapi_aggregator,
+            response_cache,
+            grpc_gateway,
+            composite_registry,
         })
     }
 }
 
 impl AppState {
-    pub fn producer(&self) -> &FutureProducer {
-        &self.kafka_producer
+    pub fn producer(&self) -> Option<&FutureProducer> {
+        self.kafka_producer.as_deref()
     }
 }
@@ -1,13 +1,17 @@
 use crate::core::configure::db::DatabaseConfig;
 use crate::core::configure::env::get_env_source;
+use crate::core::configure::grpc::GrpcServerConfig;
 use crate::core::configure::http::HttpClientConfig;
-use crate::core::configure::kafka::KafkaConfig;
+use utils::kafka_config::KafkaConfig;
+use crate::core::configure::rate_limit::RateLimitConfig;
 use crate::core::configure::redis::RedisConfig;
 use crate::core::configure::secret::SecretConfig;
 use crate::core::configure::server::ServerConfig;
+use crate::core::configure::webhook::WebhookConfig;
 use config::{ConfigError, Environment};
 use serde::{Deserialize, Serialize};
 use utils::dir::get_project_root;
+use utils::telemetry::TelemetryConfig;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct AppConfig {
@@ -18,7 +22,16 @@ pub struct AppConfig {
     pub redis: RedisConfig,
     pub secret: SecretConfig,
     pub http: HttpClientConfig,
+    #[serde(default)]
     pub kafka: KafkaConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub grpc: GrpcServerConfig,
+    #[serde(default)]
+    pub webhook: WebhookConfig,
 }
 
 impl AppConfig {
@@ -29,7 +42,9 @@ impl AppConfig {
             .add_source(profile.env_source())
             .build()?;
         log::info!("Successfully read config profile: {profile}.");
-        config.try_deserialize()
+        let config: Self = config.try_deserialize()?;
+        config.kafka.validate()?;
+        Ok(config)
     }
 
     pub fn get_sentry_dsn(&self) -> &str {
@@ -0,0 +1,22 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Internal gRPC API served next to the HTTP server, on the same address
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct GrpcServerConfig {
+    pub enabled: bool,
+    pub port: u16,
+    /// Services allowed to call, each with the secret it signs its service tokens with
+    pub callers: HashMap<String, String>,
+}
+
+impl Default for GrpcServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            port: 50051,
+            callers: HashMap::new(),
+        }
+    }
+}
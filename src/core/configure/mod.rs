@@ -1,8 +1,10 @@
 pub mod app;
 pub mod db;
 pub mod env;
+pub mod grpc;
 pub mod http;
-pub mod kafka;
+pub mod rate_limit;
 pub mod redis;
 pub mod secret;
 pub mod server;
+pub mod webhook;
@@ -0,0 +1,64 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use utils::rate_limit::{RateLimitPolicy, RateLimitRule, RateLimitRules};
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// API keys counted on their own, with the authenticated policies
+    #[serde(default)]
+    pub api_keys: Vec<RateLimitApiKey>,
+    #[serde(flatten)]
+    pub rules: RateLimitRules,
+}
+
+/// A partner's API key, registered by the hex SHA-256 of the key so the key itself is not
+/// in the settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct RateLimitApiKey {
+    pub name: String,
+    pub sha256: String,
+}
+
+impl RateLimitConfig {
+    /// Key names by SHA-256, as `Identity::from_api_key` expects them
+    pub fn api_key_names(&self) -> HashMap<String, String> {
+        self.api_keys.iter().map(|key| (key.sha256.to_lowercase(), key.name.clone())).collect()
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            api_keys: Vec::new(),
+            rules: RateLimitRules {
+                rules: vec![
+                    RateLimitRule {
+                        name: "login".to_string(),
+                        path_prefix: "/v1/login_by_email".to_string(),
+                        methods: vec!["POST".to_string()],
+                        authenticated: RateLimitPolicy::new(10, 60),
+                        anonymous: RateLimitPolicy::new(5, 60),
+                    },
+                    RateLimitRule {
+                        name: "register".to_string(),
+                        path_prefix: "/v1/auth/register".to_string(),
+                        methods: vec!["POST".to_string()],
+                        authenticated: RateLimitPolicy::new(5, 3600),
+                        anonymous: RateLimitPolicy::new(3, 3600),
+                    },
+                    RateLimitRule {
+                        name: "resend_verification".to_string(),
+                        path_prefix: "/v1/auth/resend-verification".to_string(),
+                        methods: vec!["POST".to_string()],
+                        authenticated: RateLimitPolicy::new(3, 600),
+                        anonymous: RateLimitPolicy::new(3, 600),
+                    },
+                ],
+                default_authenticated: RateLimitPolicy::new(600, 60),
+                default_anonymous: RateLimitPolicy::new(120, 60),
+            },
+        }
+    }
+}
@@ -8,6 +8,8 @@ pub struct SecretConfig {
     pub public_access_key: PathBuf,
     pub private_refresh_key: PathBuf,
     pub public_refresh_key: PathBuf,
+    /// Shared with the gateway to sign and verify internal user context tokens
+    pub internal_token_secret: String,
 }
 
 impl SecretConfig {
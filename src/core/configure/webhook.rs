@@ -0,0 +1,56 @@
+use crate::domain::webhook::webhook_subscription::pattern_matches;
+use serde::Deserialize;
+
+/// Outbound webhooks sent to partners' subscriptions
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub enabled: bool,
+    /// Events partners can subscribe to, consumed from Kafka by the webhook consumer group;
+    /// only those with `partner_fields` are delivered
+    pub events: Vec<WebhookEventSource>,
+    pub timeout_ms: u64,
+    /// Attempts before a delivery is given up
+    pub max_attempts: i32,
+    /// Failed attempts in a row after which a subscription is disabled
+    pub disable_after_failures: i32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebhookEventSource {
+    pub topic: String,
+    pub event_type: String,
+}
+
+impl WebhookEventSource {
+    pub fn new(topic: &str, event_type: &str) -> Self {
+        Self { topic: topic.to_string(), event_type: event_type.to_string() }
+    }
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            events: vec![
+                WebhookEventSource::new("user_created", "user.created"),
+                WebhookEventSource::new("user_activated", "user.activated"),
+                WebhookEventSource::new("user_updated", "user.updated"),
+                WebhookEventSource::new("user_deleted", "user.deleted"),
+                WebhookEventSource::new("address_added", "address.added"),
+                WebhookEventSource::new("address_updated", "address.updated"),
+                WebhookEventSource::new("address_removed", "address.removed"),
+            ],
+            timeout_ms: 10000,
+            max_attempts: 10,
+            disable_after_failures: 50,
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Whether a subscription pattern can match any of the configured events
+    pub fn is_known_pattern(&self, pattern: &str) -> bool {
+        self.events.iter().any(|event| pattern_matches(pattern, &event.event_type))
+    }
+}
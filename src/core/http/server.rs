@@ -1,20 +1,35 @@
 use crate::api::build_routes;
+use crate::api::grpc;
 use crate::core::app_state::AppState;
 use crate::core::configure::app::AppConfig;
+use crate::infrastructure::constant::CACHE_INVALIDATION_GROUP_ID;
 use crate::infrastructure::error::AppResult;
-use axum::body::Bytes;
+use crate::infrastructure::gateway::grpc::grpc_passthrough;
+use crate::infrastructure::gateway::routes::aggregated_openapi;
+use crate::infrastructure::middleware::access_log::access_log;
+use crate::infrastructure::third_party::outbox_relay::OutboxRelay;
+use crate::infrastructure::third_party::webhook_dispatcher::WebhookDispatcher;
+use crate::infrastructure::middleware::rate_limit::rate_limit_layer;
+use axum::body::{Body, Bytes};
 use axum::extract::DefaultBodyLimit;
-use axum::http::{header, HeaderValue};
+use axum::http::{header, HeaderValue, Request};
+use axum::middleware::{from_fn, from_fn_with_state};
+use axum::routing::get;
 use tracing;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
 use tower_http::timeout::TimeoutLayer;
-use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
+use tower_http::trace::{DefaultOnResponse, TraceLayer};
 use tower_http::ServiceBuilderExt;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
 use utoipa_axum::router::OpenApiRouter;
-use utoipa_swagger_ui::SwaggerUi;
+use utoipa_swagger_ui::{Config, SwaggerUi, Url};
+use utils::metrics::track_http_metrics;
+use utils::request_id::propagate_request_id;
+use utils::telemetry::http_request_span;
 
 pub struct AppServer {
     pub state: AppState,
@@ -39,7 +54,7 @@ impl AppServer {
             .layer(
                 TraceLayer::new_for_http()
                     .on_body_chunk(|chunk: &Bytes, latency: Duration, _: &tracing::Span| {})
-                    .make_span_with(DefaultMakeSpan::new().include_headers(true))
+                    .make_span_with(|request: &Request<Body>| http_request_span(request))
                     .on_response(
                         DefaultOnResponse::new()
                             .include_headers(true)
@@ -54,18 +69,78 @@ impl AppServer {
                 HeaderValue::from_static("application/octet-stream"),
             );
 
-        let (router, api) = OpenApiRouter::new()
+        let (router, mut api) = OpenApiRouter::new()
             .merge(build_routes())
             .layer(DefaultBodyLimit::max(1024 * 1024 * 1000))
             .split_for_parts();
 
-        let app = router
-            .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", api.clone()))
+        // Referenced by `security(("jwt" = []))` on protected handlers
+        api.components.get_or_insert_with(Default::default).add_security_scheme(
+            "jwt",
+            SecurityScheme::Http(
+                HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build(),
+            ),
+        );
+
+        self.state.openapi_aggregator.set_gateway_spec(&api).await;
+        let aggregator = self.state.openapi_aggregator.clone();
+        let registry = self.state.gateway_registry.clone();
+        tokio::spawn(async move { aggregator.run(registry).await });
+
+        if self.state.config.kafka.enabled() {
+            let cache = self.state.response_cache.clone();
+            let registry = self.state.gateway_registry.clone();
+            let consumer = self.state.config.kafka.create_group_consumer(CACHE_INVALIDATION_GROUP_ID);
+            tokio::spawn(async move { cache.run_invalidation(registry, consumer).await });
+        }
+
+        let relay = OutboxRelay::new(self.state.db.clone(), self.state.event_publisher.clone());
+        tokio::spawn(relay.run());
+
+        if self.state.config.webhook.enabled {
+            let dispatcher = WebhookDispatcher::new(self.state.db.clone(), self.state.config.webhook.clone());
+            tokio::spawn(dispatcher.run());
+        }
+
+        if self.state.config.grpc.enabled {
+            let state = self.state.clone();
+            tokio::spawn(async move {
+                if let Err(e) = grpc::serve(state).await {
+                    log::error!("The gRPC server stopped: {}", e);
+                }
+            });
+        }
+
+        let swagger_config = Config::new([
+            Url::new("API Gateway", "/api-docs/openapi.json"),
+            Url::new("All services", "/api-docs/aggregated-openapi.json"),
+        ]);
+
+        let mut app = router
+            .route("/api-docs/aggregated-openapi.json", get(aggregated_openapi))
+            .merge(
+                SwaggerUi::new("/swagger-ui")
+                    .url("/api-docs/openapi.json", api.clone())
+                    .config(swagger_config),
+            );
+
+        // Native gRPC calls bypass the REST routes, but not rate limiting
+        app = app.layer(from_fn_with_state(self.state.clone(), grpc_passthrough));
+
+        if self.state.config.rate_limit.enabled {
+            app = app.layer(rate_limit_layer(&self.state));
+        }
+
+        // The request ID layer is outermost so every other layer sees the ID
+        let app = app
+            .layer(from_fn(track_http_metrics))
+            .layer(from_fn(access_log))
             .layer(CorsLayer::permissive())
             .layer(middleware)
+            .layer(from_fn(propagate_request_id))
             .with_state(self.state);
 
-        axum::serve(self.tcp, app.into_make_service()).await?;
+        axum::serve(self.tcp, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
         Ok(())
     }
 }
@@ -1,3 +1,4 @@
+use crate::infrastructure::health::health_monitor::ComponentHealth;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -18,6 +19,12 @@ pub struct ServiceStatusResponse {
     pub redis: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub components: Vec<ComponentHealth>,
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug, ToSchema)]
 pub struct EntityResponse<T> {
     pub message: String,
@@ -25,6 +32,15 @@ pub struct EntityResponse<T> {
     pub total: i64,
 }
 
+/// Error body sent to clients, tagged with the request ID so failures can be traced
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct ClientErrorBody {
+    #[serde(flatten)]
+    pub error: ClientResponseError,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
 #[serde(tag = "code_message", content = "message")]
 pub enum ClientResponseError {
@@ -39,4 +55,7 @@ pub enum ClientResponseError {
     PermissionDenied,
     InternalServerError,
     UnprocessableEntity { detail: String },
+    BadGateway { detail: String },
+    ServiceUnavailable { detail: String },
+    GatewayTimeout { detail: String },
 }
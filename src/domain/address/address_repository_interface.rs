@@ -6,7 +6,7 @@ use crate::domain::address::address::ActiveModelEx;
 
 #[async_trait]
 pub trait AddressRepositoryInterface: Send + Sync {
-    async fn create_address(conn: &DatabaseTransaction, model: ActiveModelEx) -> AppResult<bool>;
+    async fn create_address(conn: &DatabaseTransaction, model: ActiveModelEx) -> AppResult<address::ModelEx>;
     async fn update_address(conn: &DatabaseTransaction, model: ActiveModelEx) -> AppResult<bool>;
     async fn find_address_by_id(conn: &DatabaseTransaction, id: i64) -> AppResult<Option<address::ModelEx>>;
     async fn delete_address(conn: &DatabaseTransaction, id: i64) -> AppResult<()>;
@@ -0,0 +1,55 @@
+use crate::domain::address::address::ModelEx;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use sea_orm::ActiveEnum;
+use serde::{Deserialize, Serialize};
+use utils::events::DomainEvent;
+
+/// Published when a user adds an address, with the whole address
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct AddressAddedEvent {
+    pub address_id: i64,
+    pub user_id: i64,
+    pub title: Option<String>,
+    pub address_line_1: String,
+    pub address_line_2: Option<String>,
+    pub country: String,
+    pub city: String,
+    pub postal_code: Option<String>,
+    pub landmark: Option<String>,
+    pub phone_number: Option<String>,
+    /// `active` or `inactive`
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AddressAddedEvent {
+    pub fn from_address(address: &ModelEx) -> Self {
+        Self {
+            address_id: address.id,
+            user_id: address.user_id,
+            title: address.title.clone(),
+            address_line_1: address.address_line_1.clone(),
+            address_line_2: address.address_line_2.clone(),
+            country: address.country.clone(),
+            city: address.city.clone(),
+            postal_code: address.postal_code.clone(),
+            landmark: address.landmark.clone(),
+            phone_number: address.phone_number.clone(),
+            status: address.status.to_value(),
+            created_at: address.created_at.map(|created_at| created_at.and_utc()).unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+impl DomainEvent for AddressAddedEvent {
+    const EVENT_TYPE: &'static str = "address.added";
+    const SCHEMA_VERSION: u32 = 1;
+    const TOPIC: &'static str = "address_added";
+
+    /// Addresses belong to their user, so they share the user's key and stay ordered
+    /// with the user's events
+    fn aggregate_id(&self) -> String {
+        self.user_id.to_string()
+    }
+}
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use utils::events::DomainEvent;
+
+/// Published when an address is soft deleted
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct AddressRemovedEvent {
+    pub address_id: i64,
+    pub user_id: i64,
+    pub removed_at: DateTime<Utc>,
+}
+
+impl AddressRemovedEvent {
+    pub fn new(address_id: i64, user_id: i64, removed_at: DateTime<Utc>) -> Self {
+        Self { address_id, user_id, removed_at }
+    }
+}
+
+impl DomainEvent for AddressRemovedEvent {
+    const EVENT_TYPE: &'static str = "address.removed";
+    const SCHEMA_VERSION: u32 = 1;
+    const TOPIC: &'static str = "address_removed";
+
+    /// Keyed by user, like `AddressAddedEvent`
+    fn aggregate_id(&self) -> String {
+        self.user_id.to_string()
+    }
+}
@@ -0,0 +1,77 @@
+use crate::domain::address::address::ModelEx;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use sea_orm::ActiveEnum;
+use serde::{Deserialize, Serialize};
+use utils::events::DomainEvent;
+
+/// Published when an address changes, with the address after the change
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct AddressUpdatedEvent {
+    pub address_id: i64,
+    pub user_id: i64,
+    /// Names of the fields below that changed, e.g. `["city", "postal_code"]`
+    pub changed_fields: Vec<String>,
+    pub title: Option<String>,
+    pub address_line_1: String,
+    pub address_line_2: Option<String>,
+    pub country: String,
+    pub city: String,
+    pub postal_code: Option<String>,
+    pub landmark: Option<String>,
+    pub phone_number: Option<String>,
+    /// `active` or `inactive`
+    pub status: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AddressUpdatedEvent {
+    /// `None` when nothing consumers see changed
+    pub fn from_change(before: &ModelEx, after: &ModelEx, updated_at: DateTime<Utc>) -> Option<Self> {
+        let mut changed_fields = Vec::new();
+        let mut compare = |name: &str, changed: bool| {
+            if changed {
+                changed_fields.push(name.to_string());
+            }
+        };
+        compare("title", before.title != after.title);
+        compare("address_line_1", before.address_line_1 != after.address_line_1);
+        compare("address_line_2", before.address_line_2 != after.address_line_2);
+        compare("country", before.country != after.country);
+        compare("city", before.city != after.city);
+        compare("postal_code", before.postal_code != after.postal_code);
+        compare("landmark", before.landmark != after.landmark);
+        compare("phone_number", before.phone_number != after.phone_number);
+        compare("status", before.status != after.status);
+
+        if changed_fields.is_empty() {
+            return None;
+        }
+        Some(Self {
+            address_id: after.id,
+            user_id: after.user_id,
+            changed_fields,
+            title: after.title.clone(),
+            address_line_1: after.address_line_1.clone(),
+            address_line_2: after.address_line_2.clone(),
+            country: after.country.clone(),
+            city: after.city.clone(),
+            postal_code: after.postal_code.clone(),
+            landmark: after.landmark.clone(),
+            phone_number: after.phone_number.clone(),
+            status: after.status.to_value(),
+            updated_at,
+        })
+    }
+}
+
+impl DomainEvent for AddressUpdatedEvent {
+    const EVENT_TYPE: &'static str = "address.updated";
+    const SCHEMA_VERSION: u32 = 1;
+    const TOPIC: &'static str = "address_updated";
+
+    /// Keyed by user, like `AddressAddedEvent`
+    fn aggregate_id(&self) -> String {
+        self.user_id.to_string()
+    }
+}
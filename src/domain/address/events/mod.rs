@@ -0,0 +1,3 @@
+pub mod address_added;
+pub mod address_updated;
+pub mod address_removed;
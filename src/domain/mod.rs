@@ -1,2 +1,4 @@
 pub mod user;
 pub mod address;
+pub mod outbox;
+pub mod webhook;
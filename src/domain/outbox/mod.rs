@@ -0,0 +1,2 @@
+pub mod outbox;
+pub mod outbox_repository_interface;
@@ -0,0 +1,43 @@
+use chrono::NaiveDateTime;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Domain event waiting to be published to Kafka
+///
+/// Written in the same transaction as the change it describes, so an event exists
+/// exactly when its change was committed. The outbox relay publishes pending rows.
+#[sea_orm::model]
+#[derive(Clone, Debug, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "outbox_events")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub topic: String,
+    /// Kafka record key; events with the same key are published in insertion order
+    pub aggregate_key: String,
+    pub payload: String,
+    /// Request ID and trace context of the request that wrote the event
+    pub headers: Json,
+    pub status: Status,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    /// Not published before this time, pushed back after each failed attempt
+    pub available_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    pub published_at: Option<NaiveDateTime>,
+}
+
+#[derive(EnumIter, DeriveActiveEnum, Clone, Debug, Deserialize, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(10))")]
+#[derive(PartialEq)]
+pub enum Status {
+    #[sea_orm(string_value = "pending")]
+    PENDING,
+    #[sea_orm(string_value = "published")]
+    PUBLISHED,
+    /// Gave up after the maximum number of attempts
+    #[sea_orm(string_value = "failed")]
+    FAILED,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,27 @@
+use super::outbox;
+use crate::infrastructure::error::AppResult;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sea_orm::DatabaseTransaction;
+use utils::events::DomainEvent;
+
+#[async_trait]
+pub trait OutboxRepositoryInterface: Send + Sync {
+    /// Queue an event in its envelope in the caller's transaction, with the current request
+    /// and trace context
+    async fn enqueue_event<E: DomainEvent + 'static>(conn: &DatabaseTransaction, event: E) -> AppResult<()>;
+    /// Take the relay lock for the rest of the transaction, `false` if another relay holds it
+    async fn try_lock_relay(conn: &DatabaseTransaction) -> AppResult<bool>;
+    /// Oldest pending events due at `now`, without any queued behind an event of the same
+    /// key that is still backing off
+    async fn find_pending_events(conn: &DatabaseTransaction, now: NaiveDateTime, limit: u64) -> AppResult<Vec<outbox::Model>>;
+    async fn mark_event_published(conn: &DatabaseTransaction, id: i64) -> AppResult<()>;
+    /// Record a failed attempt, retrying at `retry_at` or giving up when it is `None`
+    async fn mark_event_failed(
+        conn: &DatabaseTransaction,
+        event: outbox::Model,
+        error: String,
+        retry_at: Option<NaiveDateTime>,
+    ) -> AppResult<()>;
+    async fn delete_published_events_before(conn: &DatabaseTransaction, cutoff: NaiveDateTime) -> AppResult<u64>;
+}
@@ -1,3 +1,6 @@
 pub mod user_registered;
 pub mod user_activated;
 pub mod user_logged_in;
+pub mod user_created;
+pub mod user_updated;
+pub mod user_deleted;
@@ -1,18 +1,20 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use chrono::NaiveDateTime;
+use utils::events::DomainEvent;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct UserActivatedEvent {
     pub user_id: i64,
     pub email: String,
-    pub verified_at: NaiveDateTime,
+    pub verified_at: DateTime<Utc>,
 }
 
 impl UserActivatedEvent {
     pub fn new(
         user_id: i64,
         email: String,
-        verified_at: NaiveDateTime,
+        verified_at: DateTime<Utc>,
     ) -> Self {
         Self {
             user_id,
@@ -20,8 +22,14 @@ impl UserActivatedEvent {
             verified_at,
         }
     }
+}
+
+impl DomainEvent for UserActivatedEvent {
+    const EVENT_TYPE: &'static str = "user.activated";
+    const SCHEMA_VERSION: u32 = 1;
+    const TOPIC: &'static str = "user_activated";
 
-    pub fn topic_name() -> &'static str {
-        "user_activated"
+    fn aggregate_id(&self) -> String {
+        self.user_id.to_string()
     }
 }
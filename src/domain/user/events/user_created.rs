@@ -0,0 +1,53 @@
+use crate::domain::user::user::ModelEx;
+use chrono::{DateTime, NaiveDate, Utc};
+use schemars::JsonSchema;
+use sea_orm::ActiveEnum;
+use serde::{Deserialize, Serialize};
+use utils::events::DomainEvent;
+
+/// Published for every new user, registered or created by an admin, so read models can
+/// insert the whole profile
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct UserCreatedEvent {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub avatar: Option<String>,
+    pub phone_number: Option<String>,
+    pub birth_of_date: Option<NaiveDate>,
+    /// `pending`, `active` or `inactive`
+    pub status: String,
+    /// `customer` or `admin`
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UserCreatedEvent {
+    pub fn from_user(user: &ModelEx) -> Self {
+        Self {
+            user_id: user.id,
+            username: user.username.clone(),
+            email: user.email.clone(),
+            first_name: user.first_name.clone(),
+            last_name: user.last_name.clone(),
+            avatar: user.avatar.clone(),
+            phone_number: user.phone_number.clone(),
+            birth_of_date: user.birth_of_date,
+            status: user.status.to_value(),
+            role: user.role.to_value(),
+            created_at: user.created_at.map(|created_at| created_at.and_utc()).unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+impl DomainEvent for UserCreatedEvent {
+    const EVENT_TYPE: &'static str = "user.created";
+    const SCHEMA_VERSION: u32 = 1;
+    const TOPIC: &'static str = "user_created";
+
+    fn aggregate_id(&self) -> String {
+        self.user_id.to_string()
+    }
+}
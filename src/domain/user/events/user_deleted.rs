@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use utils::events::DomainEvent;
+
+/// Published when a user is soft deleted; their addresses go with them
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct UserDeletedEvent {
+    pub user_id: i64,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl UserDeletedEvent {
+    pub fn new(user_id: i64, deleted_at: DateTime<Utc>) -> Self {
+        Self { user_id, deleted_at }
+    }
+}
+
+impl DomainEvent for UserDeletedEvent {
+    const EVENT_TYPE: &'static str = "user.deleted";
+    const SCHEMA_VERSION: u32 = 1;
+    const TOPIC: &'static str = "user_deleted";
+
+    fn aggregate_id(&self) -> String {
+        self.user_id.to_string()
+    }
+}
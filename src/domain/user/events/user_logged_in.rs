@@ -1,17 +1,19 @@
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use utils::events::DomainEvent;
 use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+#[derive(Debug, Serialize, Deserialize, ToSchema, JsonSchema, Clone)]
 pub struct UserLoggedInEvent {
     pub user_id: i64,
     pub email: String,
     pub session_id: String,
     pub device_info: Option<DeviceInfoEvent>,
-    pub logged_in_at: NaiveDateTime,
+    pub logged_in_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+#[derive(Debug, Serialize, Deserialize, ToSchema, JsonSchema, Clone)]
 pub struct DeviceInfoEvent {
     pub user_agent: Option<String>,
     pub ip_address: Option<String>,
@@ -23,7 +25,7 @@ impl UserLoggedInEvent {
         email: String,
         session_id: String,
         device_info: Option<DeviceInfoEvent>,
-        logged_in_at: NaiveDateTime,
+        logged_in_at: DateTime<Utc>,
     ) -> Self {
         Self {
             user_id,
@@ -33,8 +35,14 @@ impl UserLoggedInEvent {
             logged_in_at,
         }
     }
+}
+
+impl DomainEvent for UserLoggedInEvent {
+    const EVENT_TYPE: &'static str = "user.logged_in";
+    const SCHEMA_VERSION: u32 = 1;
+    const TOPIC: &'static str = "user_logged_in";
 
-    pub fn topic_name() -> &'static str {
-        "user_logged_in"
+    fn aggregate_id(&self) -> String {
+        self.user_id.to_string()
     }
 }
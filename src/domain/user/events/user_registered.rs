@@ -1,13 +1,15 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use chrono::NaiveDateTime;
+use utils::events::DomainEvent;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct UserRegisteredEvent {
     pub user_id: i64,
     pub email: String,
     pub full_name: String,
     pub verification_token: String,
-    pub created_at: NaiveDateTime,
+    pub created_at: DateTime<Utc>,
 }
 
 impl UserRegisteredEvent {
@@ -16,7 +18,7 @@ impl UserRegisteredEvent {
         email: String,
         full_name: String,
         verification_token: String,
-        created_at: NaiveDateTime,
+        created_at: DateTime<Utc>,
     ) -> Self {
         Self {
             user_id,
@@ -26,8 +28,14 @@ impl UserRegisteredEvent {
             created_at,
         }
     }
+}
+
+impl DomainEvent for UserRegisteredEvent {
+    const EVENT_TYPE: &'static str = "user.registered";
+    const SCHEMA_VERSION: u32 = 1;
+    const TOPIC: &'static str = "user_registered";
 
-    pub fn topic_name() -> &'static str {
-        "user_registered"
+    fn aggregate_id(&self) -> String {
+        self.user_id.to_string()
     }
 }
@@ -0,0 +1,71 @@
+use crate::domain::user::user::ModelEx;
+use chrono::{DateTime, NaiveDate, Utc};
+use schemars::JsonSchema;
+use sea_orm::ActiveEnum;
+use serde::{Deserialize, Serialize};
+use utils::events::DomainEvent;
+
+/// Published when a user's profile changes, with the profile after the change
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct UserUpdatedEvent {
+    pub user_id: i64,
+    /// Names of the fields below that changed, e.g. `["email", "phone_number"]`
+    pub changed_fields: Vec<String>,
+    pub username: String,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub avatar: Option<String>,
+    pub phone_number: Option<String>,
+    pub birth_of_date: Option<NaiveDate>,
+    /// `pending`, `active` or `inactive`
+    pub status: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl UserUpdatedEvent {
+    /// `None` when nothing consumers see changed
+    pub fn from_change(before: &ModelEx, after: &ModelEx, updated_at: DateTime<Utc>) -> Option<Self> {
+        let mut changed_fields = Vec::new();
+        let mut compare = |name: &str, changed: bool| {
+            if changed {
+                changed_fields.push(name.to_string());
+            }
+        };
+        compare("username", before.username != after.username);
+        compare("email", before.email != after.email);
+        compare("first_name", before.first_name != after.first_name);
+        compare("last_name", before.last_name != after.last_name);
+        compare("avatar", before.avatar != after.avatar);
+        compare("phone_number", before.phone_number != after.phone_number);
+        compare("birth_of_date", before.birth_of_date != after.birth_of_date);
+        compare("status", before.status != after.status);
+
+        if changed_fields.is_empty() {
+            return None;
+        }
+        Some(Self {
+            user_id: after.id,
+            changed_fields,
+            username: after.username.clone(),
+            email: after.email.clone(),
+            first_name: after.first_name.clone(),
+            last_name: after.last_name.clone(),
+            avatar: after.avatar.clone(),
+            phone_number: after.phone_number.clone(),
+            birth_of_date: after.birth_of_date,
+            status: after.status.to_value(),
+            updated_at,
+        })
+    }
+}
+
+impl DomainEvent for UserUpdatedEvent {
+    const EVENT_TYPE: &'static str = "user.updated";
+    const SCHEMA_VERSION: u32 = 1;
+    const TOPIC: &'static str = "user_updated";
+
+    fn aggregate_id(&self) -> String {
+        self.user_id.to_string()
+    }
+}
@@ -5,7 +5,7 @@ use sea_orm::DatabaseTransaction;
 
 #[async_trait]
 pub trait UserRepositoryInterface: Send + Sync {
-    async fn create_user(conn: &DatabaseTransaction, model: user::ActiveModelEx) -> AppResult<bool>;
+    async fn create_user(conn: &DatabaseTransaction, model: user::ActiveModelEx) -> AppResult<user::ModelEx>;
     async fn update_user(conn: &DatabaseTransaction, model: user::ActiveModelEx) -> AppResult<bool>;
     async fn find_user_by_id(conn: &DatabaseTransaction, id: i64) -> AppResult<Option<user::ModelEx>>;
     async fn find_user_by_username(conn: &DatabaseTransaction, username: &str) -> AppResult<Option<user::ModelEx>>;
@@ -16,4 +16,5 @@ pub trait UserRepositoryInterface: Send + Sync {
     async fn phone_exists(conn: &DatabaseTransaction, phone: &str) -> AppResult<bool>;
     async fn find_user_by_verification_token(conn: &DatabaseTransaction, token: &str) -> AppResult<Option<user::ModelEx>>;
     async fn list_users(conn: &DatabaseTransaction, page: u64, page_size: u64) -> AppResult<Vec<user::Model>>;
+    async fn find_users_by_ids(conn: &DatabaseTransaction, ids: &[i64]) -> AppResult<Vec<user::Model>>;
 }
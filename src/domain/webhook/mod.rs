@@ -0,0 +1,4 @@
+pub mod webhook_delivery;
+pub mod webhook_payload;
+pub mod webhook_repository_interface;
+pub mod webhook_subscription;
@@ -0,0 +1,45 @@
+use chrono::NaiveDateTime;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One event to send to one subscription, and how sending it went
+///
+/// Written by the webhook consumer for every matching active subscription, then sent by
+/// the webhook dispatcher. Kept as the delivery log until the retention passes.
+#[sea_orm::model]
+#[derive(Clone, Debug, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "webhook_deliveries")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub subscription_id: i64,
+    /// ID of the event's envelope, the same for every attempt and redelivery
+    pub event_id: Uuid,
+    pub event_type: String,
+    /// The event's envelope, sent as the request body
+    pub payload: String,
+    pub status: Status,
+    pub attempts: i32,
+    /// HTTP status of the last attempt, `None` when no response came back
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    /// Not sent before this time, pushed back after each failed attempt
+    pub next_attempt_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    pub delivered_at: Option<NaiveDateTime>,
+}
+
+#[derive(EnumIter, DeriveActiveEnum, Clone, Debug, Deserialize, Serialize, utoipa::ToSchema)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(10))")]
+#[derive(PartialEq)]
+pub enum Status {
+    #[sea_orm(string_value = "pending")]
+    PENDING,
+    #[sea_orm(string_value = "delivered")]
+    DELIVERED,
+    /// Gave up after the maximum number of attempts
+    #[sea_orm(string_value = "failed")]
+    FAILED,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,82 @@
+use crate::domain::address::events::address_added::AddressAddedEvent;
+use crate::domain::address::events::address_removed::AddressRemovedEvent;
+use crate::domain::address::events::address_updated::AddressUpdatedEvent;
+use crate::domain::user::events::user_activated::UserActivatedEvent;
+use crate::domain::user::events::user_created::UserCreatedEvent;
+use crate::domain::user::events::user_deleted::UserDeletedEvent;
+use crate::domain::user::events::user_updated::UserUpdatedEvent;
+use serde_json::{Map, Value};
+use utils::events::{DomainEvent, EventEnvelope};
+
+const ADDRESS_FIELDS: &[&str] = &[
+    "address_id",
+    "user_id",
+    "title",
+    "address_line_1",
+    "address_line_2",
+    "country",
+    "city",
+    "postal_code",
+    "landmark",
+    "phone_number",
+    "status",
+];
+
+/// Fields of an event's `data` partners receive
+///
+/// Anything not listed stays internal, e.g. a `verification_token`. Event types without an
+/// entry, like `user.registered`, are never sent to partners, even when `[webhook] events`
+/// lists them.
+pub fn partner_fields(event_type: &str) -> Option<Vec<&'static str>> {
+    let fields = match event_type {
+        UserCreatedEvent::EVENT_TYPE => vec![
+            "user_id",
+            "username",
+            "email",
+            "first_name",
+            "last_name",
+            "avatar",
+            "phone_number",
+            "birth_of_date",
+            "status",
+            "role",
+            "created_at",
+        ],
+        UserActivatedEvent::EVENT_TYPE => vec!["user_id", "email", "verified_at"],
+        UserUpdatedEvent::EVENT_TYPE => vec![
+            "user_id",
+            "changed_fields",
+            "username",
+            "email",
+            "first_name",
+            "last_name",
+            "avatar",
+            "phone_number",
+            "birth_of_date",
+            "status",
+            "updated_at",
+        ],
+        UserDeletedEvent::EVENT_TYPE => vec!["user_id", "deleted_at"],
+        AddressAddedEvent::EVENT_TYPE => [ADDRESS_FIELDS, &["created_at"]].concat(),
+        AddressUpdatedEvent::EVENT_TYPE => [ADDRESS_FIELDS, &["changed_fields", "updated_at"]].concat(),
+        AddressRemovedEvent::EVENT_TYPE => vec!["address_id", "user_id", "removed_at"],
+        _ => return None,
+    };
+    Some(fields)
+}
+
+/// The envelope as partners receive it, `None` for events they never receive
+///
+/// The envelope attributes are kept, `correlationid` included so deliveries can be traced
+/// back to the request that caused them; `data` only keeps the `partner_fields`.
+pub fn partner_payload(event: &EventEnvelope<Value>) -> Option<EventEnvelope<Value>> {
+    let fields = partner_fields(&event.event_type)?;
+    let data: Map<String, Value> = match &event.data {
+        Value::Object(data) => fields
+            .iter()
+            .filter_map(|field| data.get(*field).map(|value| (field.to_string(), value.clone())))
+            .collect(),
+        _ => Map::new(),
+    };
+    Some(EventEnvelope { data: Value::Object(data), ..event.clone() })
+}
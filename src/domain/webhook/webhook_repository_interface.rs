@@ -0,0 +1,73 @@
+use super::{webhook_delivery, webhook_subscription};
+use crate::infrastructure::error::AppResult;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sea_orm::DatabaseTransaction;
+
+#[async_trait]
+pub trait WebhookSubscriptionRepositoryInterface: Send + Sync {
+    async fn create_subscription(
+        conn: &DatabaseTransaction,
+        model: webhook_subscription::ActiveModel,
+    ) -> AppResult<webhook_subscription::Model>;
+    async fn update_subscription(
+        conn: &DatabaseTransaction,
+        model: webhook_subscription::ActiveModel,
+    ) -> AppResult<webhook_subscription::Model>;
+    async fn find_subscription_by_id(
+        conn: &DatabaseTransaction,
+        id: i64,
+    ) -> AppResult<Option<webhook_subscription::Model>>;
+    async fn find_subscriptions(
+        conn: &DatabaseTransaction,
+        owner: Option<&str>,
+    ) -> AppResult<Vec<webhook_subscription::Model>>;
+    async fn find_active_subscriptions(conn: &DatabaseTransaction) -> AppResult<Vec<webhook_subscription::Model>>;
+    /// Deletes the subscription's delivery log with it
+    async fn delete_subscription(conn: &DatabaseTransaction, id: i64) -> AppResult<bool>;
+    async fn reset_subscription_failures(conn: &DatabaseTransaction, id: i64) -> AppResult<()>;
+    /// Count a failed attempt, disabling the subscription once `disable_after` failed in a
+    /// row; returns whether this failure disabled it
+    async fn record_subscription_failure(
+        conn: &DatabaseTransaction,
+        id: i64,
+        disable_after: i32,
+    ) -> AppResult<bool>;
+}
+
+#[async_trait]
+pub trait WebhookDeliveryRepositoryInterface: Send + Sync {
+    async fn create_delivery(
+        conn: &DatabaseTransaction,
+        model: webhook_delivery::ActiveModel,
+    ) -> AppResult<webhook_delivery::Model>;
+    /// Take the dispatcher lock for the rest of the transaction, `false` if another
+    /// dispatcher holds it
+    async fn try_lock_dispatcher(conn: &DatabaseTransaction) -> AppResult<bool>;
+    /// Pending deliveries of active subscriptions due at `now`, oldest first
+    async fn find_due_deliveries(
+        conn: &DatabaseTransaction,
+        now: NaiveDateTime,
+        limit: u64,
+    ) -> AppResult<Vec<webhook_delivery::Model>>;
+    async fn find_delivery_by_id(conn: &DatabaseTransaction, id: i64) -> AppResult<Option<webhook_delivery::Model>>;
+    /// The subscription's delivery log, newest first
+    async fn find_deliveries_by_subscription(
+        conn: &DatabaseTransaction,
+        subscription_id: i64,
+        status: Option<webhook_delivery::Status>,
+        limit: u64,
+    ) -> AppResult<Vec<webhook_delivery::Model>>;
+    async fn mark_delivery_delivered(conn: &DatabaseTransaction, id: i64, response_status: i32) -> AppResult<()>;
+    /// Record a failed attempt, retrying at `retry_at` or giving up when it is `None`
+    async fn mark_delivery_failed(
+        conn: &DatabaseTransaction,
+        delivery: webhook_delivery::Model,
+        response_status: Option<i32>,
+        error: String,
+        retry_at: Option<NaiveDateTime>,
+    ) -> AppResult<()>;
+    /// Send the delivery again as soon as possible, with a fresh set of attempts
+    async fn requeue_delivery(conn: &DatabaseTransaction, id: i64) -> AppResult<()>;
+    async fn delete_deliveries_before(conn: &DatabaseTransaction, cutoff: NaiveDateTime) -> AppResult<u64>;
+}
@@ -0,0 +1,58 @@
+use chrono::NaiveDateTime;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A partner's or tenant's HTTP endpoint receiving the events it subscribed to
+#[sea_orm::model]
+#[derive(Clone, Debug, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "webhook_subscriptions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Tenant or partner the subscription belongs to
+    pub owner: String,
+    pub url: String,
+    /// Key of the HMAC signature sent with every delivery
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// Event type patterns, e.g. `user.created`, `user.*` or `*`
+    pub event_types: Json,
+    pub status: Status,
+    /// Failed attempts since the last successful one
+    pub consecutive_failures: i32,
+    pub disabled_reason: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(EnumIter, DeriveActiveEnum, Clone, Debug, Deserialize, Serialize, utoipa::ToSchema)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(10))")]
+#[derive(PartialEq)]
+pub enum Status {
+    #[sea_orm(string_value = "active")]
+    ACTIVE,
+    /// Disabled by an admin or after too many consecutive failures, nothing is delivered
+    #[sea_orm(string_value = "disabled")]
+    DISABLED,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn event_type_patterns(&self) -> Vec<String> {
+        serde_json::from_value(self.event_types.clone()).unwrap_or_default()
+    }
+
+    /// Whether `event_type` is one the subscription asked for
+    pub fn matches(&self, event_type: &str) -> bool {
+        self.event_type_patterns().iter().any(|pattern| pattern_matches(pattern, event_type))
+    }
+}
+
+/// `*` matches every event type, `user.*` every type starting with `user.`
+pub fn pattern_matches(pattern: &str, event_type: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => event_type.starts_with(prefix),
+        None => pattern == event_type,
+    }
+}
@@ -6,6 +6,8 @@ use crate::core::client::http::{ClientBuilder, HttpClient};
 use crate::core::configure;
 use crate::core::configure::app::Profile;
 
+/// Name the gateway reports as, e.g. as the `source` of its domain events
+pub const SERVICE_NAME: &str = "api-gateway";
 pub const MAX_RETRY: u32 = 10;
 pub const ENV_PREFIX: &str = "APP";
 pub const CODE_LEN: usize = 5;
@@ -16,6 +18,7 @@ pub const EXPIRE_BLOCKED_EMAIL_SECS: Duration = Duration::from_secs(300);
 pub const EXPIRE_FORGET_PASS_CODE_SECS: Duration = Duration::from_secs(300);
 pub const EXPIRE_BEARER_TOKEN_SECS: Duration = Duration::from_secs(36000);
 pub const EXPIRE_REFRESH_TOKEN_SECS: Duration = Duration::from_secs(86400);
+pub const EXPIRE_INTERNAL_CONTEXT_SECS: Duration = Duration::from_secs(60);
 pub const QUEUE_EMPTY_DELAY_SECS: Duration = Duration::from_secs(60);
 pub const COMPLETE_TASK_DELAY_SECS: Duration = Duration::from_secs(10);
 pub const CHECK_EMAIL_MESSAGE: &str = "Please check you email.";
@@ -24,6 +27,40 @@ pub const BEARER: &str = "Bearer";
 pub const APP_DOMAIN: &str = "";
 pub const APP_EMAIL_ADDR: &str = "";
 pub const MINIMUM_DELAY_TIME: Duration = Duration::from_millis(120);
+pub const HEALTH_CHECK_INTERVAL_SECS: u64 = 10;
+pub const HEALTH_CHECK_TIMEOUT_SECS: u64 = 2;
+pub const HEALTH_HISTORY_LIMIT: usize = 100;
+pub const OPENAPI_REFRESH_INTERVAL_SECS: u64 = 60;
+pub const OPENAPI_FETCH_TIMEOUT_SECS: u64 = 5;
+/// Shared by every gateway instance, the cache lives in Redis so one purge is enough
+pub const CACHE_INVALIDATION_GROUP_ID: &str = "api-gateway-cache-invalidation";
+/// Consumer group of the gateway's domain event handlers
+pub const EVENT_CONSUMER_GROUP_ID: &str = "api-gateway-events";
+/// Replayed and discarded dead letters are remembered at least as long as Kafka keeps them
+pub const DEAD_LETTER_RESOLUTION_TTL: Duration = Duration::from_secs(30 * 24 * 3600);
+pub const DEAD_LETTER_MAX_LIST: usize = 500;
+pub const OUTBOX_POLL_INTERVAL: Duration = Duration::from_millis(500);
+pub const OUTBOX_BATCH_SIZE: u64 = 100;
+/// Attempts before an outbox event is marked failed and stops being retried
+pub const OUTBOX_MAX_ATTEMPTS: i32 = 10;
+pub const OUTBOX_MAX_BACKOFF: Duration = Duration::from_secs(300);
+/// Published events are kept this long for inspection, then deleted
+pub const OUTBOX_RETENTION: Duration = Duration::from_secs(7 * 24 * 3600);
+pub const OUTBOX_CLEANUP_INTERVAL: Duration = Duration::from_secs(3600);
+/// Consumer group turning events into webhook deliveries
+pub const WEBHOOK_CONSUMER_GROUP_ID: &str = "api-gateway-webhooks";
+pub const WEBHOOK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+pub const WEBHOOK_BATCH_SIZE: u64 = 50;
+/// Wait before the second attempt of a delivery, doubled after every failed attempt
+pub const WEBHOOK_BASE_BACKOFF: Duration = Duration::from_secs(30);
+pub const WEBHOOK_MAX_BACKOFF: Duration = Duration::from_secs(6 * 3600);
+/// Delivered and failed deliveries are kept this long as the delivery log, then deleted
+pub const WEBHOOK_DELIVERY_RETENTION: Duration = Duration::from_secs(30 * 24 * 3600);
+pub const WEBHOOK_CLEANUP_INTERVAL: Duration = Duration::from_secs(3600);
+pub const WEBHOOK_MAX_DELIVERY_LIST: u64 = 500;
+/// Audience internal callers put in service tokens for the gRPC API
+pub const GRPC_SERVICE_AUDIENCE: &str = "api-gateway";
+pub const GRPC_MAX_BATCH_USERS: usize = 100;
 
 // Redis TTL Constants (in seconds)
 pub const REDIS_TTL_USER_PROFILE: i64 = 86400; // 24 hours
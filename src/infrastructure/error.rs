@@ -1,10 +1,11 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
-use crate::core::response::ClientResponseError;
+use crate::core::response::{ClientErrorBody, ClientResponseError};
+use utils::request_id::current_request_id;
 
 pub type AppResult<T = ()> = Result<T, AppError>;
 
@@ -30,6 +31,12 @@ pub enum AppError {
     UnauthorizedError(String),
     #[error("{0}")]
     AccountLockedError(String),
+    #[error("Bad gateway {0}")]
+    BadGatewayError(String),
+    #[error("Service unavailable {detail}")]
+    ServiceUnavailableError { detail: String, retry_after_secs: u64 },
+    #[error("Gateway timeout {0}")]
+    GatewayTimeoutError(String),
     #[error("Bad request {0}")]
     BadRequestError(String),
     #[error("{0}")]
@@ -76,8 +83,13 @@ pub enum AppError {
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status_code, body) = self.status_and_error();
-        (status_code, Json(body)).into_response()
+        let (status_code, error) = self.status_and_error();
+        let body = ClientErrorBody { error, request_id: current_request_id() };
+        let mut response = (status_code, Json(body)).into_response();
+        if let Some(retry_after) = self.retry_after_secs() {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        response
     }
 }
 
@@ -102,6 +114,14 @@ impl From<utils::redis_client::RedisError> for AppError {
 }
 
 impl AppError {
+    /// Seconds a client should wait before retrying, sent as the `Retry-After` header
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            AppError::ServiceUnavailableError { retry_after_secs, .. } => Some(*retry_after_secs),
+            _ => None,
+        }
+    }
+
     pub fn status_and_error(&self) -> (StatusCode, ClientResponseError) {
         use AppError::*;
         match self {
@@ -165,10 +185,25 @@ impl AppError {
             UnauthorizedError(_err) => {
                 (StatusCode::UNAUTHORIZED, ClientResponseError::Unauthorized)
             },
+            PermissionDeniedError(_err) => {
+                (StatusCode::FORBIDDEN, ClientResponseError::PermissionDenied)
+            },
             AccountLockedError(err) => (
                 StatusCode::LOCKED,
                 ClientResponseError::BadRequest { detail: err.to_string() },
             ),
+            BadGatewayError(err) => (
+                StatusCode::BAD_GATEWAY,
+                ClientResponseError::BadGateway { detail: err.to_string() },
+            ),
+            ServiceUnavailableError { detail, .. } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                ClientResponseError::ServiceUnavailable { detail: detail.to_string() },
+            ),
+            GatewayTimeoutError(err) => (
+                StatusCode::GATEWAY_TIMEOUT,
+                ClientResponseError::GatewayTimeout { detail: err.to_string() },
+            ),
             UuidError(_err) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, ClientResponseError::InternalServerError)
             },
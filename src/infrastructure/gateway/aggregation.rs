@@ -0,0 +1,250 @@
+use crate::core::configure::app::AppConfig;
+use crate::infrastructure::error::{AppError, AppResult};
+use crate::infrastructure::gateway::circuit_breaker::CircuitBreakerConfig;
+use crate::infrastructure::gateway::retry::RetryPolicy;
+use crate::infrastructure::gateway::rewrite::RewriteRules;
+use crate::infrastructure::gateway::service_registry::ServiceConfig;
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use utils::metrics::metrics;
+use utoipa::ToSchema;
+
+/// Service name parts use to call the gateway's own routes, e.g. `/v1/me`
+pub const SELF_SERVICE: &str = "api-gateway";
+
+/// Declarative endpoint merging several upstream responses into one
+///
+/// Served at `GET /gateway/compose/{name}`. Parts are fetched in parallel with the
+/// caller's credentials; a failed optional part is reported in `errors` and leaves
+/// its key `null` instead of failing the whole response.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CompositeEndpoint {
+    pub name: String,
+    #[serde(default = "default_require_auth")]
+    pub require_auth: bool,
+    pub parts: Vec<CompositePart>,
+}
+
+fn default_require_auth() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CompositePart {
+    /// Key of this part in the merged `data`
+    pub key: String,
+    /// Registered service name, or `api-gateway` for the gateway's own routes
+    pub service: String,
+    /// Upstream path and query; `{user_id}` and `{name}` for query parameters of the
+    /// composite request are substituted
+    pub path: String,
+    /// JSON pointer into the response, e.g. `/data` to unwrap an `EntityResponse`
+    #[serde(default)]
+    pub select: Option<String>,
+    #[serde(default = "default_part_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Fail the whole response when this part fails
+    #[serde(default)]
+    pub required: bool,
+}
+
+fn default_part_timeout_ms() -> u64 {
+    2000
+}
+
+impl CompositePart {
+    /// Upstream path with placeholders filled in
+    pub fn render_path(&self, user_id: Option<i64>, query: &HashMap<String, String>) -> AppResult<String> {
+        let mut path = String::with_capacity(self.path.len());
+        let mut rest = self.path.as_str();
+
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..].find('}').map(|end| start + end).ok_or_else(|| {
+                AppError::BadGatewayError(format!("Unclosed placeholder in part '{}'", self.key))
+            })?;
+            path.push_str(&rest[..start]);
+
+            let name = &rest[start + 1..end];
+            let value = match name {
+                "user_id" => user_id.map(|id| id.to_string()),
+                _ => query.get(name).map(|value| urlencoding::encode(value).into_owned()),
+            };
+            let value = value.ok_or_else(|| {
+                AppError::BadRequestError(format!("Missing '{}' for part '{}'", name, self.key))
+            })?;
+            path.push_str(&value);
+            rest = &rest[end + 1..];
+        }
+        path.push_str(rest);
+        Ok(path)
+    }
+
+    /// Value kept from a successful upstream response body
+    pub fn extract(&self, body: &[u8]) -> Result<Value, String> {
+        let value: Value =
+            serde_json::from_slice(body).map_err(|e| format!("Invalid JSON response: {}", e))?;
+        match &self.select {
+            Some(pointer) => value
+                .pointer(pointer)
+                .cloned()
+                .ok_or_else(|| format!("Response has no '{}'", pointer)),
+            None => Ok(value),
+        }
+    }
+}
+
+/// Why a part is missing from the merged response
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PartError {
+    pub key: String,
+    /// Upstream or gateway status, 504 when the part timed out
+    pub status: u16,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CompositeResponse {
+    /// One entry per part, `null` for parts that failed
+    #[schema(value_type = Object)]
+    pub data: BTreeMap<String, Value>,
+    pub errors: Vec<PartError>,
+    /// Whether any part failed
+    pub partial: bool,
+}
+
+impl CompositeResponse {
+    /// Merge part results, failing if a required part failed
+    pub fn merge(
+        endpoint: &CompositeEndpoint,
+        results: Vec<Result<Value, PartError>>,
+    ) -> AppResult<Self> {
+        let mut data = BTreeMap::new();
+        let mut errors = Vec::new();
+
+        for (part, result) in endpoint.parts.iter().zip(results) {
+            match result {
+                Ok(value) => {
+                    data.insert(part.key.clone(), value);
+                },
+                Err(error) if part.required => {
+                    return Err(AppError::BadGatewayError(format!(
+                        "Required part '{}' of '{}' failed: {}",
+                        part.key, endpoint.name, error.detail
+                    )));
+                },
+                Err(error) => {
+                    data.insert(part.key.clone(), Value::Null);
+                    errors.push(error);
+                },
+            }
+        }
+
+        let partial = !errors.is_empty();
+        Ok(Self { data, errors, partial })
+    }
+}
+
+impl PartError {
+    pub fn new(key: &str, status: StatusCode, detail: impl Into<String>) -> Self {
+        Self { key: key.to_string(), status: status.as_u16(), detail: detail.into() }
+    }
+}
+
+/// Loopback upstream for parts served by the gateway itself
+///
+/// The caller's `Authorization` header is forwarded, so these routes authenticate as usual.
+pub fn self_upstream(config: &AppConfig) -> ServiceConfig {
+    ServiceConfig {
+        name: SELF_SERVICE.to_string(),
+        base_url: config.server.get_http_addr(),
+        health_check_path: None,
+        timeout_secs: 30,
+        require_auth: false,
+        openapi_path: None,
+        circuit_breaker: CircuitBreakerConfig::default(),
+        retry: RetryPolicy { max_retries: 0, ..RetryPolicy::default() },
+        cache: Vec::new(),
+        canary: None,
+        mirror: None,
+        rewrite: RewriteRules::default(),
+        grpc: None,
+    }
+}
+
+/// Record the outcome of one part: ok, error or timeout
+pub fn record_part_result(endpoint: &str, part: &str, result: &str) {
+    metrics()
+        .composite_parts_total
+        .with_label_values(&[endpoint, part, result])
+        .inc();
+}
+
+#[derive(Debug, Clone)]
+pub struct CompositeRegistry {
+    endpoints: Arc<RwLock<HashMap<String, CompositeEndpoint>>>,
+}
+
+impl CompositeRegistry {
+    pub fn new() -> Self {
+        Self { endpoints: Arc::new(RwLock::new(HashMap::new())) }
+    }
+
+    pub async fn with_defaults() -> Self {
+        let registry = Self::new();
+
+        // The signed-in user's profile and addresses, in one round trip. Recent orders, cart
+        // and recommendations join as optional parts once order-service and product-service
+        // serve them; see "Composite Endpoints" in API_GATEWAY_GUIDE.md.
+        registry
+            .register(CompositeEndpoint {
+                name: "home".to_string(),
+                require_auth: true,
+                parts: vec![
+                    CompositePart {
+                        key: "profile".to_string(),
+                        service: SELF_SERVICE.to_string(),
+                        path: "/v1/me".to_string(),
+                        select: Some("/data".to_string()),
+                        timeout_ms: 1000,
+                        required: true,
+                    },
+                    CompositePart {
+                        key: "addresses".to_string(),
+                        service: SELF_SERVICE.to_string(),
+                        path: "/v1/addresses?user_id={user_id}".to_string(),
+                        select: Some("/data".to_string()),
+                        timeout_ms: 1000,
+                        required: false,
+                    },
+                ],
+            })
+            .await;
+
+        registry
+    }
+
+    pub async fn register(&self, endpoint: CompositeEndpoint) {
+        let mut endpoints = self.endpoints.write().await;
+        endpoints.insert(endpoint.name.clone(), endpoint);
+    }
+
+    pub async fn get(&self, name: &str) -> Option<CompositeEndpoint> {
+        let endpoints = self.endpoints.read().await;
+        endpoints.get(name).cloned()
+    }
+
+    pub async fn list_all(&self) -> Vec<CompositeEndpoint> {
+        let endpoints = self.endpoints.read().await;
+        endpoints.values().cloned().collect()
+    }
+}
+
+impl Default for CompositeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,180 @@
+use crate::infrastructure::gateway::service_registry::ServiceConfig;
+use axum::http::{HeaderMap, HeaderName};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utils::metrics::metrics;
+use utoipa::ToSchema;
+
+/// Response header naming the upstream version that served the request
+pub const UPSTREAM_VERSION_HEADER: HeaderName = HeaderName::from_static("x-upstream-version");
+/// Version reported for requests served by the service's regular `base_url`
+pub const STABLE_VERSION: &str = "stable";
+
+/// Alternate upstream version receiving part of a service's traffic
+///
+/// Users are bucketed by a hash of their ID, so each user keeps seeing the same
+/// version while `weight_percent` is unchanged. Anonymous requests stay on the stable
+/// version unless they carry one of `match_headers`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CanaryConfig {
+    /// Version label reported in metrics and the `x-upstream-version` header
+    pub version: String,
+    pub base_url: String,
+    /// Share of authenticated users routed to the canary, 0-100
+    #[serde(default)]
+    pub weight_percent: u8,
+    /// Requests carrying any of these header values always go to the canary
+    #[serde(default)]
+    pub match_headers: HashMap<String, String>,
+    /// Users always routed to the canary, e.g. internal testers
+    #[serde(default)]
+    pub user_ids: Vec<i64>,
+}
+
+impl CanaryConfig {
+    /// Whether a request should be served by the canary
+    pub fn selects(&self, service: &str, headers: &HeaderMap, user_id: Option<i64>) -> bool {
+        let header_match = self.match_headers.iter().any(|(name, expected)| {
+            headers
+                .get(name.as_str())
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value == expected)
+        });
+        if header_match {
+            return true;
+        }
+
+        match user_id {
+            Some(user_id) if self.user_ids.contains(&user_id) => true,
+            Some(user_id) => user_bucket(service, user_id) < self.weight_percent.min(100),
+            None => false,
+        }
+    }
+
+    /// Config used to reach the canary, with its own name so it gets its own circuit breaker
+    pub fn upstream(&self, stable: &ServiceConfig) -> ServiceConfig {
+        ServiceConfig {
+            name: format!("{}@{}", stable.name, self.version),
+            base_url: self.base_url.clone(),
+            canary: None,
+            mirror: None,
+            cache: Vec::new(),
+            ..stable.clone()
+        }
+    }
+}
+
+/// Stable 0-99 bucket of a user; FNV-1a so it does not change across releases
+fn user_bucket(service: &str, user_id: i64) -> u8 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in format!("{}:{}", service, user_id).bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    (hash % 100) as u8
+}
+
+/// Record which version served a request to a service with a canary
+pub fn record_upstream_version(service: &str, version: &str) {
+    metrics()
+        .upstream_version_requests_total
+        .with_label_values(&[service, version])
+        .inc();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::gateway::circuit_breaker::{CircuitBreakerConfig, CircuitState};
+    use crate::infrastructure::gateway::resilience::ResilienceRegistry;
+    use crate::infrastructure::gateway::retry::RetryPolicy;
+    use crate::infrastructure::gateway::rewrite::RewriteRules;
+    use axum::http::HeaderValue;
+
+    const SERVICE: &str = "product-service";
+
+    fn canary(weight_percent: u8) -> CanaryConfig {
+        CanaryConfig {
+            version: "v2".to_string(),
+            base_url: "http://product-service-v2:8080".to_string(),
+            weight_percent,
+            match_headers: HashMap::from([("x-canary".to_string(), "always".to_string())]),
+            user_ids: vec![42],
+        }
+    }
+
+    fn stable() -> ServiceConfig {
+        ServiceConfig {
+            name: SERVICE.to_string(),
+            base_url: "http://product-service:8080".to_string(),
+            health_check_path: None,
+            timeout_secs: 5,
+            require_auth: false,
+            openapi_path: None,
+            circuit_breaker: CircuitBreakerConfig { failure_threshold: 1, ..CircuitBreakerConfig::default() },
+            retry: RetryPolicy::default(),
+            cache: Vec::new(),
+            canary: Some(canary(10)),
+            mirror: None,
+            rewrite: RewriteRules::default(),
+            grpc: None,
+        }
+    }
+
+    fn selected_users(config: &CanaryConfig) -> usize {
+        (1..=1000).filter(|user_id| config.selects(SERVICE, &HeaderMap::new(), Some(*user_id))).count()
+    }
+
+    #[test]
+    fn matching_header_selects_the_canary() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-canary", HeaderValue::from_static("always"));
+
+        assert!(canary(0).selects(SERVICE, &headers, None));
+
+        headers.insert("x-canary", HeaderValue::from_static("never"));
+        assert!(!canary(0).selects(SERVICE, &headers, None));
+    }
+
+    #[test]
+    fn listed_users_always_get_the_canary() {
+        assert!(canary(0).selects(SERVICE, &HeaderMap::new(), Some(42)));
+    }
+
+    #[test]
+    fn weight_bounds_select_nobody_or_everybody() {
+        assert_eq!(selected_users(&CanaryConfig { user_ids: Vec::new(), ..canary(0) }), 0);
+        assert_eq!(selected_users(&canary(100)), 1000);
+        assert!(!canary(100).selects(SERVICE, &HeaderMap::new(), None), "anonymous requests stay stable");
+    }
+
+    #[test]
+    fn users_keep_their_bucket() {
+        let config = canary(50);
+        let first: Vec<bool> = (1..=100).map(|user_id| config.selects(SERVICE, &HeaderMap::new(), Some(user_id))).collect();
+
+        for _ in 0..3 {
+            let again: Vec<bool> =
+                (1..=100).map(|user_id| config.selects(SERVICE, &HeaderMap::new(), Some(user_id))).collect();
+            assert_eq!(again, first);
+        }
+        let selected = selected_users(&config);
+        assert!((400..=600).contains(&selected), "{} of 1000 users at 50%", selected);
+    }
+
+    #[tokio::test]
+    async fn canary_upstream_has_its_own_circuit_breaker() {
+        let stable = stable();
+        let upstream = stable.canary.as_ref().unwrap().upstream(&stable);
+        assert_eq!(upstream.name, "product-service@v2");
+        assert_eq!(upstream.base_url, "http://product-service-v2:8080");
+        assert!(upstream.canary.is_none());
+
+        let registry = ResilienceRegistry::new();
+        registry.guard_for(&upstream).await.circuit_breaker.try_acquire().unwrap().failure();
+
+        assert_eq!(registry.circuit_state(&upstream.name).await, CircuitState::Open);
+        assert_eq!(registry.circuit_state(&stable.name).await, CircuitState::Closed);
+        assert!(registry.guard_for(&stable).await.circuit_breaker.try_acquire().is_ok());
+    }
+}
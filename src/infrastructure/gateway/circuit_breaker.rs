@@ -0,0 +1,210 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures before the circuit opens
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before letting a probe through
+    pub open_duration_secs: u64,
+    /// Successful probes required in half-open state before closing again
+    pub half_open_success_threshold: u32,
+    /// Concurrent probes allowed while half-open
+    pub half_open_max_calls: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_duration_secs: 30,
+            half_open_success_threshold: 2,
+            half_open_max_calls: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct BreakerInner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    half_open_successes: u32,
+    half_open_in_flight: u32,
+    opened_at: Option<Instant>,
+    /// Bumped every time the circuit opens or closes, so permits taken in an earlier
+    /// period don't touch the current one's counters or slots
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct Shared {
+    config: CircuitBreakerConfig,
+    inner: Mutex<BreakerInner>,
+}
+
+/// Per-service circuit breaker
+///
+/// Closed lets every call through, Open rejects calls until `open_duration_secs`
+/// has elapsed, HalfOpen lets a limited number of probes decide whether to close again.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    shared: Arc<Shared>,
+}
+
+/// Permission for one upstream call, from `CircuitBreaker::try_acquire`
+///
+/// Report how the call went with `success` or `failure`. A permit dropped without either,
+/// e.g. because the request was cancelled, gives its half-open probe slot back without
+/// counting the call. Reports from a permit taken before the circuit last opened or closed
+/// are ignored.
+#[must_use = "report the call with `success` or `failure`"]
+#[derive(Debug)]
+pub struct CircuitPermit {
+    shared: Arc<Shared>,
+    /// Generation the permit was taken in
+    generation: u64,
+    /// Whether the permit holds a half-open probe slot
+    probe: bool,
+    reported: bool,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        let inner = BreakerInner {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            half_open_successes: 0,
+            half_open_in_flight: 0,
+            opened_at: None,
+            generation: 0,
+        };
+        Self { shared: Arc::new(Shared { config, inner: Mutex::new(inner) }) }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        let mut inner = self.shared.inner.lock().unwrap();
+        self.shared.refresh(&mut inner);
+        inner.state
+    }
+
+    /// Ask permission to call the upstream.
+    ///
+    /// Returns `Err(retry_after)` when the circuit is open or the half-open probe slots are taken.
+    pub fn try_acquire(&self) -> Result<CircuitPermit, Duration> {
+        let mut inner = self.shared.inner.lock().unwrap();
+        self.shared.refresh(&mut inner);
+
+        let probe = match inner.state {
+            CircuitState::Closed => false,
+            CircuitState::Open => return Err(self.shared.remaining_open_time(&inner)),
+            CircuitState::HalfOpen => {
+                if inner.half_open_in_flight >= self.shared.config.half_open_max_calls {
+                    return Err(Duration::from_secs(1));
+                }
+                inner.half_open_in_flight += 1;
+                true
+            },
+        };
+        Ok(CircuitPermit { shared: self.shared.clone(), generation: inner.generation, probe, reported: false })
+    }
+}
+
+impl CircuitPermit {
+    pub fn success(mut self) {
+        self.reported = true;
+        let shared = &self.shared;
+        let mut inner = shared.inner.lock().unwrap();
+        if self.generation != inner.generation {
+            return;
+        }
+        match inner.state {
+            CircuitState::HalfOpen => {
+                inner.half_open_in_flight = inner.half_open_in_flight.saturating_sub(1);
+                inner.half_open_successes += 1;
+                if inner.half_open_successes >= shared.config.half_open_success_threshold {
+                    Shared::close(&mut inner);
+                }
+            },
+            CircuitState::Closed => inner.consecutive_failures = 0,
+            CircuitState::Open => {},
+        }
+    }
+
+    pub fn failure(mut self) {
+        self.reported = true;
+        let shared = &self.shared;
+        let mut inner = shared.inner.lock().unwrap();
+        if self.generation != inner.generation {
+            return;
+        }
+        match inner.state {
+            CircuitState::HalfOpen => Shared::open(&mut inner),
+            CircuitState::Closed => {
+                inner.consecutive_failures += 1;
+                if inner.consecutive_failures >= shared.config.failure_threshold {
+                    Shared::open(&mut inner);
+                }
+            },
+            CircuitState::Open => {},
+        }
+    }
+}
+
+impl Drop for CircuitPermit {
+    fn drop(&mut self) {
+        if self.reported {
+            return;
+        }
+        let mut inner = self.shared.inner.lock().unwrap();
+        if self.probe && inner.state == CircuitState::HalfOpen && self.generation == inner.generation {
+            inner.half_open_in_flight = inner.half_open_in_flight.saturating_sub(1);
+        }
+    }
+}
+
+impl Shared {
+    fn refresh(&self, inner: &mut BreakerInner) {
+        if inner.state == CircuitState::Open
+            && self.remaining_open_time(inner) == Duration::ZERO
+        {
+            inner.state = CircuitState::HalfOpen;
+            inner.half_open_successes = 0;
+            inner.half_open_in_flight = 0;
+        }
+    }
+
+    fn remaining_open_time(&self, inner: &BreakerInner) -> Duration {
+        let open_duration = Duration::from_secs(self.config.open_duration_secs);
+        inner
+            .opened_at
+            .map(|opened_at| open_duration.saturating_sub(opened_at.elapsed()))
+            .unwrap_or(Duration::ZERO)
+    }
+
+    fn open(inner: &mut BreakerInner) {
+        inner.state = CircuitState::Open;
+        inner.opened_at = Some(Instant::now());
+        inner.half_open_successes = 0;
+        inner.half_open_in_flight = 0;
+        inner.generation += 1;
+    }
+
+    fn close(inner: &mut BreakerInner) {
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.half_open_successes = 0;
+        inner.half_open_in_flight = 0;
+        inner.opened_at = None;
+        inner.generation += 1;
+    }
+}
@@ -0,0 +1,577 @@
+use crate::application::authen::claim::UserClaims;
+use crate::core::app_state::AppState;
+use crate::infrastructure::error::{AppError, AppResult};
+use crate::infrastructure::gateway::circuit_breaker::CircuitPermit;
+use crate::infrastructure::gateway::proxy::{
+    acquire_permit, is_upstream_failure_status, record_upstream_metrics, UpstreamIdentity,
+    UpstreamTarget, HOP_BY_HOP_HEADERS,
+};
+use crate::infrastructure::gateway::resilience::UpstreamGuard;
+use crate::infrastructure::gateway::service_registry::ServiceConfig;
+use crate::infrastructure::middleware::authenticate::extract_bearer_claims;
+use axum::body::{Body, Bytes, HttpBody};
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, Method, Response, Uri};
+use axum::middleware::Next;
+use axum::Json;
+use axum::response::IntoResponse;
+use http_body::{Frame, SizeHint};
+use http_body_util::BodyExt;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage, MethodDescriptor};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tonic::{Code, Status};
+use utils::internal_auth::IDENTITY_HEADERS;
+use utils::request_id::{current_request_id, REQUEST_ID_HEADER};
+use utils::telemetry::inject_context;
+use utoipa::ToSchema;
+
+const GRPC_CONTENT_TYPE: &str = "application/grpc";
+/// gRPC length-prefixed message header: compression flag and big-endian length
+const GRPC_FRAME_HEADER_LEN: usize = 5;
+
+/// gRPC endpoint of a service, reached over HTTP/2 without TLS (h2c)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GrpcConfig {
+    /// e.g. `http://localhost:50051`
+    pub base_url: String,
+    /// Fully qualified gRPC services passed through natively, e.g. `administration.AdministrationService`
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Encoded `FileDescriptorSet` of the service's protos, needed for transcoding
+    #[serde(default)]
+    pub descriptor_set_path: Option<String>,
+    /// JSON routes transcoded to unary gRPC calls
+    #[serde(default)]
+    pub transcode: Vec<TranscodeRoute>,
+}
+
+/// REST route served by calling a unary gRPC method
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TranscodeRoute {
+    /// HTTP method, e.g. `GET`
+    pub method: String,
+    /// Path relative to `/gateway/{service}`; `{field}` segments set request fields
+    pub path: String,
+    /// `package.Service/Method`
+    pub grpc_method: String,
+}
+
+impl GrpcConfig {
+    /// Transcode route matching a request, with the values of its `{field}` segments
+    pub fn transcode_route(
+        &self,
+        method: &Method,
+        path: &str,
+    ) -> Option<(&TranscodeRoute, Vec<(String, String)>)> {
+        self.transcode.iter().find_map(|route| {
+            if !route.method.eq_ignore_ascii_case(method.as_str()) {
+                return None;
+            }
+            match_path_template(&route.path, path).map(|params| (route, params))
+        })
+    }
+}
+
+/// HTTP/2 client and descriptor cache shared by gRPC passthrough and transcoding
+pub struct GrpcGateway {
+    client: Client<HttpConnector, Body>,
+    descriptors: RwLock<HashMap<String, DescriptorPool>>,
+}
+
+impl GrpcGateway {
+    pub fn new() -> Self {
+        Self {
+            client: Client::builder(TokioExecutor::new()).http2_only(true).build_http(),
+            descriptors: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Forward a native gRPC call as is, streaming both ways with trailers intact
+    ///
+    /// Failures come back as gRPC statuses. The circuit breaker learns the outcome from
+    /// the `grpc-status` of the response, which for streamed responses is only known once
+    /// the trailers arrive.
+    pub async fn forward(
+        &self,
+        service_config: &ServiceConfig,
+        grpc: &GrpcConfig,
+        guard: &UpstreamGuard,
+        request: Request,
+        identity: Option<UpstreamIdentity>,
+    ) -> Response<Body> {
+        let (mut parts, body) = request.into_parts();
+        let path_and_query = parts.uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+        let upstream = upstream_uri(&grpc.base_url, path_and_query)
+            .and_then(|uri| Ok((uri, upstream_headers(&parts.headers, identity.as_ref())?)));
+        match upstream {
+            Ok((uri, headers)) => {
+                parts.uri = uri;
+                parts.headers = headers;
+            },
+            Err(e) => return grpc_status_response(Status::internal(e.to_string())),
+        }
+
+        let Ok(permit) = acquire_permit(service_config, guard) else {
+            return grpc_status_response(Status::unavailable(format!(
+                "Service '{}' is temporarily unavailable",
+                service_config.name
+            )));
+        };
+
+        log::info!("Proxying gRPC call {} (service: {})", parts.uri.path(), service_config.name);
+        match self.send(service_config, Request::from_parts(parts, body)).await {
+            Ok(response) => report_on_status(response, permit),
+            Err(e) => {
+                permit.failure();
+                grpc_status_response(Status::unavailable(e.to_string()))
+            },
+        }
+    }
+
+    /// Serve a JSON request by calling a unary gRPC method and returning its response as JSON
+    #[allow(clippy::too_many_arguments)]
+    pub async fn transcode(
+        &self,
+        service_config: &ServiceConfig,
+        grpc: &GrpcConfig,
+        guard: &UpstreamGuard,
+        route: &TranscodeRoute,
+        path_params: Vec<(String, String)>,
+        request: Request,
+        identity: Option<UpstreamIdentity>,
+    ) -> AppResult<Response<Body>> {
+        let method = self.method_descriptor(grpc, &route.grpc_method).await?;
+        if method.is_client_streaming() || method.is_server_streaming() {
+            return Err(AppError::BadGatewayError(format!(
+                "Only unary methods can be transcoded, {} is streaming",
+                route.grpc_method
+            )));
+        }
+
+        let (parts, body) = request.into_parts();
+        let body = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .map_err(|e| AppError::BadRequestError(format!("Failed to read request body: {}", e)))?;
+
+        let mut fields = if body.is_empty() {
+            Map::new()
+        } else {
+            match serde_json::from_slice(&body)? {
+                Value::Object(fields) => fields,
+                _ => {
+                    return Err(AppError::InvalidPayloadError(
+                        "Request body must be a JSON object".to_string(),
+                    ))
+                },
+            }
+        };
+        // Query parameters fill fields the body does not set, path parameters always win
+        if let Some(query) = parts.uri.query() {
+            for (name, value) in serde_urlencoded::from_str::<Vec<(String, String)>>(query)
+                .map_err(|e| AppError::BadRequestError(format!("Invalid query string: {}", e)))?
+            {
+                fields.entry(name).or_insert_with(|| query_value(value));
+            }
+        }
+        for (name, value) in path_params {
+            fields.insert(name, Value::String(value));
+        }
+
+        let input = DynamicMessage::deserialize(method.input(), Value::Object(fields))
+            .map_err(|e| AppError::InvalidPayloadError(format!("Invalid request: {}", e)))?;
+        let message = input.encode_to_vec();
+        let mut frame = Vec::with_capacity(GRPC_FRAME_HEADER_LEN + message.len());
+        frame.push(0);
+        frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&message);
+
+        let mut upstream = Request::new(Body::from(frame));
+        *upstream.method_mut() = Method::POST;
+        *upstream.uri_mut() = upstream_uri(&grpc.base_url, &format!("/{}", route.grpc_method))?;
+        let mut headers = upstream_headers(&parts.headers, identity.as_ref())?;
+        headers.remove(header::CONTENT_LENGTH);
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(GRPC_CONTENT_TYPE));
+        *upstream.headers_mut() = headers;
+
+        log::info!(
+            "Transcoding {} {} to gRPC {} (service: {})",
+            parts.method,
+            parts.uri.path(),
+            route.grpc_method,
+            service_config.name
+        );
+
+        let permit = acquire_permit(service_config, guard)?;
+        let timeout = Duration::from_secs(service_config.timeout_secs);
+        let collected = tokio::time::timeout(timeout, async {
+            let response = self.send(service_config, upstream).await?;
+            let (parts, body) = response.into_parts();
+            let collected = body
+                .collect()
+                .await
+                .map_err(|e| AppError::BadGatewayError(format!("Failed to read gRPC response: {}", e)))?;
+            Ok::<_, AppError>((parts.headers, collected))
+        })
+        .await
+        .map_err(|_| AppError::GatewayTimeoutError(format!("gRPC call {} timed out", route.grpc_method)))
+        .and_then(|collected| collected);
+        let (headers, collected) = match collected {
+            Ok(collected) => collected,
+            Err(e) => {
+                permit.failure();
+                return Err(e);
+            },
+        };
+
+        // Trailers-only responses carry the status in the headers
+        let status = collected
+            .trailers()
+            .and_then(Status::from_header_map)
+            .or_else(|| Status::from_header_map(&headers))
+            .unwrap_or_else(|| Status::unknown("gRPC response carried no status"));
+        report(permit, status.code());
+        if status.code() != Code::Ok {
+            return Err(status_to_error(&route.grpc_method, status));
+        }
+
+        let body = collected.to_bytes();
+        let message = body
+            .get(GRPC_FRAME_HEADER_LEN..)
+            .filter(|_| body[0] == 0)
+            .ok_or_else(|| {
+                AppError::BadGatewayError("Unsupported or compressed gRPC response".to_string())
+            })?;
+        let output = DynamicMessage::decode(method.output(), message).map_err(|e| {
+            AppError::BadGatewayError(format!("Failed to decode gRPC response: {}", e))
+        })?;
+
+        Ok(Json(serde_json::to_value(&output)?).into_response())
+    }
+
+    async fn send(
+        &self,
+        service_config: &ServiceConfig,
+        request: Request,
+    ) -> AppResult<Response<Body>> {
+        let started = Instant::now();
+        let result = self
+            .client
+            .request(request)
+            .await
+            .map(|response| response.map(Body::new))
+            .map_err(|e| {
+                log::error!("Failed to call gRPC upstream {}: {}", service_config.name, e);
+                AppError::BadGatewayError(format!("Failed to reach gRPC upstream: {}", e))
+            });
+        record_upstream_metrics(&service_config.name, started.elapsed(), &result);
+        result
+    }
+
+    async fn method_descriptor(
+        &self,
+        grpc: &GrpcConfig,
+        grpc_method: &str,
+    ) -> AppResult<MethodDescriptor> {
+        let path = grpc.descriptor_set_path.as_deref().ok_or_else(|| {
+            AppError::BadGatewayError("Transcoding requires a descriptor_set_path".to_string())
+        })?;
+
+        let cached = self.descriptors.read().await.get(path).cloned();
+        let pool = match cached {
+            Some(pool) => pool,
+            None => {
+                let bytes = tokio::fs::read(path).await?;
+                let pool = DescriptorPool::decode(bytes.as_slice()).map_err(|e| {
+                    AppError::BadGatewayError(format!("Invalid descriptor set {}: {}", path, e))
+                })?;
+                self.descriptors.write().await.insert(path.to_string(), pool.clone());
+                pool
+            },
+        };
+
+        let (service, method) = grpc_method.split_once('/').ok_or_else(|| {
+            AppError::BadGatewayError(format!("Invalid gRPC method '{}'", grpc_method))
+        })?;
+        pool.get_service_by_name(service)
+            .and_then(|service| service.methods().find(|m| m.name() == method))
+            .ok_or_else(|| {
+                AppError::BadGatewayError(format!("gRPC method '{}' not found in descriptors", grpc_method))
+            })
+    }
+}
+
+impl Default for GrpcGateway {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Middleware routing native gRPC calls (`/{package.Service}/{Method}`) to the service
+/// that declares them, before they reach the REST routes
+pub async fn grpc_passthrough(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response<Body> {
+    let is_grpc = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with(GRPC_CONTENT_TYPE));
+    if !is_grpc {
+        return next.run(request).await;
+    }
+
+    let grpc_service = request.uri().path().trim_start_matches('/').split('/').next().unwrap_or("");
+    let Some(service_config) = state.gateway_registry.find_by_grpc_service(grpc_service).await else {
+        return grpc_status_response(Status::unimplemented(format!(
+            "Unknown gRPC service '{}'",
+            grpc_service
+        )));
+    };
+    let Some(grpc) = service_config.grpc.clone() else {
+        return grpc_status_response(Status::internal("Service has no gRPC endpoint"));
+    };
+
+    let claims: Option<UserClaims> = extract_bearer_claims(request.headers());
+    if service_config.require_auth && claims.is_none() {
+        return grpc_status_response(Status::unauthenticated(format!(
+            "Service '{}' requires authentication",
+            service_config.name
+        )));
+    }
+
+    let identity = match claims.as_ref().map(|c| UpstreamIdentity::sign(&state.internal_token_signer, c)) {
+        Some(Ok(identity)) => Some(identity),
+        Some(Err(e)) => return grpc_status_response(Status::internal(e.to_string())),
+        None => None,
+    };
+
+    let guard = state.gateway_resilience.guard_for(&service_config).await;
+    let mut response = state.grpc_gateway.forward(&service_config, &grpc, &guard, request, identity).await;
+    response.extensions_mut().insert(UpstreamTarget(service_config.name));
+    response
+}
+
+/// Whether a gRPC status means the upstream itself is failing, as opposed to rejecting the call
+fn is_upstream_failure_code(code: Code) -> bool {
+    matches!(code, Code::Unavailable | Code::DeadlineExceeded)
+}
+
+fn report(permit: CircuitPermit, code: Code) {
+    if is_upstream_failure_code(code) {
+        permit.failure();
+    } else {
+        permit.success();
+    }
+}
+
+/// Report a passthrough call to the circuit breaker once its gRPC status is known
+fn report_on_status(response: Response<Body>, permit: CircuitPermit) -> Response<Body> {
+    if is_upstream_failure_status(response.status()) {
+        permit.failure();
+        return response;
+    }
+    // Trailers-only responses carry the status in the headers
+    if let Some(status) = Status::from_header_map(response.headers()) {
+        report(permit, status.code());
+        return response;
+    }
+    response.map(|body| Body::new(StatusReportingBody { inner: body, permit: Some(permit) }))
+}
+
+/// Response body that reports the call once the `grpc-status` trailer goes by
+///
+/// A stream that errors or ends without a status counts as a failure. A body dropped
+/// early, e.g. by a client cancelling the call, releases the permit without a report.
+struct StatusReportingBody {
+    inner: Body,
+    permit: Option<CircuitPermit>,
+}
+
+impl HttpBody for StatusReportingBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let polled = Pin::new(&mut self.inner).poll_frame(cx);
+        match &polled {
+            Poll::Ready(Some(Ok(frame))) => {
+                if let Some(status) = frame.trailers_ref().and_then(Status::from_header_map) {
+                    if let Some(permit) = self.permit.take() {
+                        report(permit, status.code());
+                    }
+                }
+            },
+            Poll::Ready(Some(Err(_))) | Poll::Ready(None) => {
+                if let Some(permit) = self.permit.take() {
+                    permit.failure();
+                }
+            },
+            Poll::Pending => {},
+        }
+        polled
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// gRPC error as a trailers-only response, which gRPC clients expect instead of JSON
+fn grpc_status_response(status: Status) -> Response<Body> {
+    status.into_http()
+}
+
+/// HTTP error for a failed transcoded call, following the usual gRPC to HTTP mapping
+fn status_to_error(grpc_method: &str, status: Status) -> AppError {
+    let detail = format!("{}: {}", grpc_method, status.message());
+    match status.code() {
+        Code::InvalidArgument | Code::OutOfRange | Code::FailedPrecondition => {
+            AppError::InvalidPayloadError(detail)
+        },
+        Code::NotFound => AppError::EntityNotFoundError { detail },
+        Code::AlreadyExists | Code::Aborted => AppError::EntityExistsError { detail },
+        Code::Unauthenticated => AppError::UnauthorizedError(detail),
+        Code::PermissionDenied => AppError::PermissionDeniedError(detail),
+        Code::Unavailable | Code::ResourceExhausted => {
+            AppError::ServiceUnavailableError { detail, retry_after_secs: 1 }
+        },
+        Code::DeadlineExceeded => AppError::GatewayTimeoutError(detail),
+        _ => AppError::BadGatewayError(detail),
+    }
+}
+
+fn upstream_uri(base_url: &str, path_and_query: &str) -> AppResult<Uri> {
+    format!("{}{}", base_url.trim_end_matches('/'), path_and_query)
+        .parse()
+        .map_err(|e| AppError::BadGatewayError(format!("Invalid gRPC upstream URL: {}", e)))
+}
+
+/// Client headers minus hop-by-hop and identity headers, plus request ID, trace context
+/// and the signed user context
+fn upstream_headers(
+    incoming: &HeaderMap,
+    identity: Option<&UpstreamIdentity>,
+) -> AppResult<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    for (key, value) in incoming.iter() {
+        let key_str = key.as_str();
+        if key != header::HOST
+            && !HOP_BY_HOP_HEADERS.contains(&key_str)
+            && !IDENTITY_HEADERS.contains(&key_str)
+        {
+            headers.append(key.clone(), value.clone());
+        }
+    }
+    // Required by gRPC servers, dropped above as a hop-by-hop header
+    headers.insert(header::TE, HeaderValue::from_static("trailers"));
+
+    if let Some(request_id) = current_request_id() {
+        if let Ok(value) = HeaderValue::from_str(&request_id) {
+            headers.insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+        }
+    }
+    inject_context(&mut headers);
+    if let Some(identity) = identity {
+        identity.apply(&mut headers)?;
+    }
+    Ok(headers)
+}
+
+/// Values of `{field}` segments if `path` matches `template`
+fn match_path_template(template: &str, path: &str) -> Option<Vec<(String, String)>> {
+    let mut template_segments = template.trim_matches('/').split('/');
+    let mut path_segments = path.trim_matches('/').split('/');
+    let mut params = Vec::new();
+
+    loop {
+        match (template_segments.next(), path_segments.next()) {
+            (None, None) => return Some(params),
+            (Some(expected), Some(actual)) => {
+                match expected.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                    Some(name) if !actual.is_empty() => params.push((name.to_string(), actual.to_string())),
+                    Some(_) => return None,
+                    None if expected == actual => {},
+                    None => return None,
+                }
+            },
+            _ => return None,
+        }
+    }
+}
+
+/// Query values are strings, except booleans which the JSON mapping needs as literals
+fn query_value(value: String) -> Value {
+    match value.as_str() {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::String(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::gateway::circuit_breaker::{
+        CircuitBreaker, CircuitBreakerConfig, CircuitState,
+    };
+    use http_body_util::StreamBody;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig { failure_threshold: 1, ..CircuitBreakerConfig::default() })
+    }
+
+    /// Streamed response: one message frame, then the given trailers if any
+    fn streamed(breaker: &CircuitBreaker, trailers: Option<Code>) -> Body {
+        let mut frames: Vec<Result<Frame<Bytes>, axum::Error>> = vec![Ok(Frame::data(Bytes::from_static(&[0, 0, 0, 0, 0])))];
+        if let Some(code) = trailers {
+            let mut headers = HeaderMap::new();
+            Status::new(code, "").add_header(&mut headers).unwrap();
+            frames.push(Ok(Frame::trailers(headers)));
+        }
+        let response = Response::new(Body::new(StreamBody::new(futures::stream::iter(frames))));
+        report_on_status(response, breaker.try_acquire().unwrap()).into_body()
+    }
+
+    #[tokio::test]
+    async fn reports_the_status_from_the_trailers() {
+        let breaker = breaker();
+        streamed(&breaker, Some(Code::NotFound)).collect().await.unwrap();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        streamed(&breaker, Some(Code::Unavailable)).collect().await.unwrap();
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn stream_ending_without_a_status_is_a_failure() {
+        let breaker = breaker();
+        streamed(&breaker, None).collect().await.unwrap();
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn dropped_stream_is_not_reported() {
+        let breaker = breaker();
+        drop(streamed(&breaker, Some(Code::Unavailable)));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
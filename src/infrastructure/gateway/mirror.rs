@@ -0,0 +1,366 @@
+use crate::infrastructure::error::{AppError, AppResult};
+use crate::infrastructure::gateway::retry::RetryPolicy;
+use crate::infrastructure::gateway::service_registry::ServiceConfig;
+use axum::body::{Body, Bytes};
+use axum::extract::Request;
+use axum::http::{Method, Response, StatusCode};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use utils::metrics::metrics;
+use utils::request_id;
+use utoipa::ToSchema;
+
+/// Shadow upstream receiving a sampled copy of a service's requests
+///
+/// Shadow responses are discarded; only their status, latency and body hash are
+/// compared with the primary response and logged.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MirrorConfig {
+    pub base_url: String,
+    /// Share of eligible requests mirrored, 0-100
+    #[serde(default = "default_sample_percent")]
+    pub sample_percent: u8,
+    /// Also mirror POST/PATCH; only safe when the shadow has its own data stores
+    #[serde(default)]
+    pub include_non_idempotent: bool,
+    #[serde(default = "default_mirror_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_sample_percent() -> u8 {
+    100
+}
+
+fn default_mirror_timeout_secs() -> u64 {
+    10
+}
+
+impl MirrorConfig {
+    /// Whether this request should be copied to the shadow
+    pub fn samples(&self, method: &Method) -> bool {
+        if !self.include_non_idempotent && !RetryPolicy::is_retryable_method(method) {
+            return false;
+        }
+        rand::thread_rng().gen_range(0..100) < self.sample_percent.min(100)
+    }
+
+    /// Config used to reach the shadow, with its own circuit breaker and no retries
+    pub fn upstream(&self, primary: &ServiceConfig) -> ServiceConfig {
+        ServiceConfig {
+            name: format!("{}@shadow", primary.name),
+            base_url: self.base_url.clone(),
+            timeout_secs: self.timeout_secs,
+            retry: RetryPolicy { max_retries: 0, ..primary.retry.clone() },
+            cache: Vec::new(),
+            canary: None,
+            mirror: None,
+            ..primary.clone()
+        }
+    }
+}
+
+/// What the comparison looks at in a primary or shadow response
+#[derive(Debug, Clone, Copy)]
+pub struct MirrorObservation {
+    pub status: StatusCode,
+    pub latency: Duration,
+    pub body_hash: u64,
+}
+
+impl MirrorObservation {
+    pub fn new(status: StatusCode, latency: Duration, body: &Bytes) -> Self {
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+        Self { status, latency, body_hash: hasher.finish() }
+    }
+}
+
+/// Send a request with `send`, copying it to the shadow upstream when sampled
+///
+/// `send` calls whichever upstream config it is given. The shadow call runs in the background
+/// and waits for the primary outcome to compare with; the caller never waits on it.
+pub async fn send_mirrored<S, F>(
+    mirror: &MirrorConfig,
+    primary_config: &ServiceConfig,
+    request: Request,
+    send: S,
+) -> AppResult<Response<Body>>
+where
+    S: Fn(ServiceConfig, Request) -> F,
+    F: Future<Output = AppResult<Response<Body>>> + Send + 'static,
+{
+    if !mirror.samples(request.method()) {
+        return send(primary_config.clone(), request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| AppError::BadRequestError(format!("Failed to read request body: {}", e)))?;
+
+    let mut shadow_request = Request::new(Body::from(body.clone()));
+    *shadow_request.method_mut() = parts.method.clone();
+    *shadow_request.uri_mut() = parts.uri.clone();
+    *shadow_request.headers_mut() = parts.headers.clone();
+
+    let (primary_tx, primary_rx) = oneshot::channel();
+    let shadow_config = mirror.upstream(primary_config);
+    request_id::spawn(mirror_request(
+        shadow_config.name.clone(),
+        send(shadow_config, shadow_request),
+        parts.method.clone(),
+        parts.uri.path().to_string(),
+        primary_rx,
+    ));
+
+    let started = Instant::now();
+    let response = send(primary_config.clone(), Request::from_parts(parts, Body::from(body))).await?;
+    let latency = started.elapsed();
+
+    // Proxied responses are already buffered, so this does not delay the caller
+    let (parts, body) = response.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| AppError::BadGatewayError(format!("Failed to read upstream response: {}", e)))?;
+    let _ = primary_tx.send(MirrorObservation::new(parts.status, latency, &body));
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+async fn mirror_request(
+    shadow: String,
+    call: impl Future<Output = AppResult<Response<Body>>>,
+    method: Method,
+    path: String,
+    primary: oneshot::Receiver<MirrorObservation>,
+) {
+    let started = Instant::now();
+    let observation = match call.await {
+        Ok(response) => {
+            let latency = started.elapsed();
+            let (parts, body) = response.into_parts();
+            axum::body::to_bytes(body, usize::MAX)
+                .await
+                .ok()
+                .map(|body| MirrorObservation::new(parts.status, latency, &body))
+        },
+        Err(e) => {
+            log::debug!("Shadow request to {} failed: {}", shadow, e);
+            None
+        },
+    };
+
+    // Nothing to compare when the primary request failed
+    if let Ok(primary) = primary.await {
+        report_mirror_result(&shadow, &method, &path, &primary, observation.as_ref());
+    }
+}
+
+/// Metric label of the comparison, a status mismatch taking precedence over a body one
+fn comparison(primary: &MirrorObservation, shadow: Option<&MirrorObservation>) -> &'static str {
+    match shadow {
+        None => "shadow_error",
+        Some(shadow) if shadow.status != primary.status => "status_mismatch",
+        Some(shadow) if shadow.body_hash != primary.body_hash => "body_mismatch",
+        Some(_) => "match",
+    }
+}
+
+/// Compare a shadow response with the primary one, logging and counting differences
+///
+/// `shadow` is `None` when the shadow request failed without a response.
+pub fn report_mirror_result(
+    service: &str,
+    method: &Method,
+    path: &str,
+    primary: &MirrorObservation,
+    shadow: Option<&MirrorObservation>,
+) {
+    let result = comparison(primary, shadow);
+    metrics()
+        .mirror_comparisons_total
+        .with_label_values(&[service, result])
+        .inc();
+
+    match shadow {
+        None => log::warn!(
+            "Mirror {} {} {}: shadow request failed (primary {} in {}ms)",
+            service, method, path, primary.status, primary.latency.as_millis()
+        ),
+        Some(shadow) if result == "match" => log::debug!(
+            "Mirror {} {} {}: match, primary {}ms, shadow {}ms",
+            service, method, path, primary.latency.as_millis(), shadow.latency.as_millis()
+        ),
+        Some(shadow) => log::warn!(
+            "Mirror {} {} {}: {}, status {} vs {}, body {:016x} vs {:016x}, latency {}ms vs {}ms",
+            service,
+            method,
+            path,
+            result,
+            primary.status,
+            shadow.status,
+            primary.body_hash,
+            shadow.body_hash,
+            primary.latency.as_millis(),
+            shadow.latency.as_millis()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::gateway::circuit_breaker::CircuitBreakerConfig;
+    use crate::infrastructure::gateway::rewrite::RewriteRules;
+    use std::sync::{Arc, Mutex};
+
+    /// How the stub shadow upstream answers
+    #[derive(Clone, Copy)]
+    enum Shadow {
+        Same,
+        Fails,
+        Hangs,
+    }
+
+    fn mirror(sample_percent: u8, include_non_idempotent: bool) -> MirrorConfig {
+        MirrorConfig {
+            base_url: "http://shadow:8080".to_string(),
+            sample_percent,
+            include_non_idempotent,
+            timeout_secs: 10,
+        }
+    }
+
+    fn primary() -> ServiceConfig {
+        ServiceConfig {
+            name: "product-service".to_string(),
+            base_url: "http://product-service:8080".to_string(),
+            health_check_path: None,
+            timeout_secs: 5,
+            require_auth: false,
+            openapi_path: None,
+            circuit_breaker: CircuitBreakerConfig::default(),
+            retry: RetryPolicy::default(),
+            cache: Vec::new(),
+            canary: None,
+            mirror: None,
+            rewrite: RewriteRules::default(),
+            grpc: None,
+        }
+    }
+
+    fn request(method: Method) -> Request {
+        let mut request = Request::new(Body::from("{}"));
+        *request.method_mut() = method;
+        *request.uri_mut() = "/gateway/product-service/products".parse().unwrap();
+        request
+    }
+
+    /// Send through stub upstreams, returning the response and the upstreams called
+    async fn send(
+        mirror: &MirrorConfig,
+        method: Method,
+        shadow: Shadow,
+    ) -> (AppResult<Response<Body>>, Vec<String>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded = calls.clone();
+        let response = send_mirrored(mirror, &primary(), request(method), move |upstream, _request| {
+            recorded.lock().unwrap().push(upstream.name.clone());
+            let is_shadow = upstream.name.ends_with("@shadow");
+            async move {
+                match shadow {
+                    Shadow::Fails if is_shadow => {
+                        Err(AppError::BadGatewayError("shadow is down".to_string()))
+                    },
+                    Shadow::Hangs if is_shadow => {
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                        Ok(Response::new(Body::from("late")))
+                    },
+                    _ => Ok(Response::new(Body::from("primary"))),
+                }
+            }
+        })
+        .await;
+        let calls = calls.lock().unwrap().clone();
+        (response, calls)
+    }
+
+    fn shadow_calls(calls: &[String]) -> usize {
+        calls.iter().filter(|name| name.as_str() == "product-service@shadow").count()
+    }
+
+    fn observation(status: StatusCode, body: &'static str) -> MirrorObservation {
+        MirrorObservation::new(status, Duration::from_millis(5), &Bytes::from_static(body.as_bytes()))
+    }
+
+    #[tokio::test]
+    async fn non_idempotent_methods_are_only_mirrored_when_enabled() {
+        for method in [Method::POST, Method::PATCH] {
+            let (_, calls) = send(&mirror(100, false), method.clone(), Shadow::Same).await;
+            assert_eq!(calls, vec!["product-service".to_string()], "{} mirrored", method);
+
+            let (_, calls) = send(&mirror(100, true), method.clone(), Shadow::Same).await;
+            assert_eq!(shadow_calls(&calls), 1, "{} not mirrored", method);
+        }
+
+        let (_, calls) = send(&mirror(100, false), Method::GET, Shadow::Same).await;
+        assert_eq!(shadow_calls(&calls), 1);
+    }
+
+    #[tokio::test]
+    async fn sample_percent_bounds_mirror_never_or_always() {
+        let (never, always) = (mirror(0, true), mirror(100, true));
+
+        for _ in 0..50 {
+            assert_eq!(shadow_calls(&send(&never, Method::GET, Shadow::Same).await.1), 0);
+            assert_eq!(shadow_calls(&send(&always, Method::GET, Shadow::Same).await.1), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn failing_or_slow_shadow_leaves_the_response_alone() {
+        let mirror = mirror(100, false);
+        for shadow in [Shadow::Fails, Shadow::Hangs] {
+            let sent = tokio::time::timeout(Duration::from_secs(1), send(&mirror, Method::GET, shadow));
+            let (response, calls) = sent.await.expect("the caller does not wait for the shadow");
+
+            let response = response.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            assert_eq!(body, "primary");
+            assert_eq!(shadow_calls(&calls), 1);
+        }
+    }
+
+    #[test]
+    fn status_and_body_mismatches_are_reported_apart() {
+        let primary = observation(StatusCode::OK, r#"{"id":1}"#);
+
+        assert_eq!(comparison(&primary, Some(&observation(StatusCode::OK, r#"{"id":1}"#))), "match");
+        assert_eq!(comparison(&primary, Some(&observation(StatusCode::OK, r#"{"id":2}"#))), "body_mismatch");
+        assert_eq!(comparison(&primary, Some(&observation(StatusCode::NOT_FOUND, r#"{"id":1}"#))), "status_mismatch");
+        assert_eq!(
+            comparison(&primary, Some(&observation(StatusCode::NOT_FOUND, "{}"))),
+            "status_mismatch",
+            "a status mismatch is not also counted as a body one"
+        );
+        assert_eq!(comparison(&primary, None), "shadow_error");
+    }
+
+    #[test]
+    fn report_counts_each_result_under_its_label() {
+        let count = |result: &str| metrics().mirror_comparisons_total.with_label_values(&["report-test@shadow", result]).get();
+        let primary = observation(StatusCode::OK, "a");
+
+        report_mirror_result("report-test@shadow", &Method::GET, "/", &primary, Some(&observation(StatusCode::OK, "b")));
+        report_mirror_result("report-test@shadow", &Method::GET, "/", &primary, Some(&observation(StatusCode::BAD_GATEWAY, "a")));
+
+        assert_eq!(count("body_mismatch"), 1);
+        assert_eq!(count("status_mismatch"), 1);
+        assert_eq!(count("match"), 0);
+    }
+}
@@ -1,3 +1,13 @@
+pub mod aggregation;
+pub mod canary;
+pub mod circuit_breaker;
+pub mod grpc;
+pub mod mirror;
+pub mod openapi_aggregator;
 pub mod proxy;
+pub mod resilience;
+pub mod response_cache;
+pub mod retry;
+pub mod rewrite;
 pub mod routes;
 pub mod service_registry;
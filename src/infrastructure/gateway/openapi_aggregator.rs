@@ -0,0 +1,367 @@
+use crate::infrastructure::constant::{OPENAPI_FETCH_TIMEOUT_SECS, OPENAPI_REFRESH_INTERVAL_SECS};
+use crate::infrastructure::gateway::service_registry::{ServiceConfig, ServiceRegistry};
+use futures::future::join_all;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Component sections whose entries are namespaced per service to avoid collisions
+const NAMESPACED_COMPONENTS: &[&str] = &[
+    "schemas",
+    "responses",
+    "parameters",
+    "examples",
+    "requestBodies",
+    "headers",
+    "links",
+    "callbacks",
+];
+
+const HTTP_METHODS: &[&str] = &["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// Builds one OpenAPI document out of the gateway spec and every downstream service spec
+///
+/// Downstream paths are exposed under the `/gateway/{service}` path the service's rewrite
+/// rules map to them, component names are prefixed with the service name and security
+/// schemes are merged into the gateway's.
+#[derive(Debug, Clone)]
+pub struct OpenApiAggregator {
+    gateway_spec: Arc<RwLock<Value>>,
+    /// Last spec fetched from each service, kept when a later fetch fails
+    service_specs: Arc<RwLock<HashMap<String, Value>>>,
+    merged: Arc<RwLock<Value>>,
+    client: reqwest::Client,
+}
+
+impl OpenApiAggregator {
+    pub fn new() -> Self {
+        Self {
+            gateway_spec: Arc::new(RwLock::new(json!({}))),
+            service_specs: Arc::new(RwLock::new(HashMap::new())),
+            merged: Arc::new(RwLock::new(json!({}))),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn set_gateway_spec(&self, spec: &utoipa::openapi::OpenApi) {
+        match serde_json::to_value(spec) {
+            Ok(value) => {
+                *self.gateway_spec.write().await = value.clone();
+                *self.merged.write().await = value;
+            },
+            Err(e) => log::error!("Failed to serialize gateway OpenAPI spec: {}", e),
+        }
+    }
+
+    pub async fn merged_spec(&self) -> Value {
+        self.merged.read().await.clone()
+    }
+
+    /// Refresh whenever the registry changes, and periodically so services that come up later are picked up
+    pub async fn run(&self, registry: Arc<ServiceRegistry>) {
+        let mut changes = registry.subscribe();
+        let mut interval = tokio::time::interval(Duration::from_secs(OPENAPI_REFRESH_INTERVAL_SECS));
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                changed = changes.changed() => {
+                    if changed.is_err() {
+                        log::warn!("Service registry dropped, stopping OpenAPI aggregation");
+                        return;
+                    }
+                    log::info!("Service registry changed, refreshing aggregated OpenAPI spec");
+                },
+            }
+            self.refresh(&registry).await;
+        }
+    }
+
+    pub async fn refresh(&self, registry: &ServiceRegistry) {
+        let services = registry.list_all().await;
+        let fetched = join_all(services.iter().map(|service| self.fetch_spec(service))).await;
+
+        let mut service_specs = self.service_specs.write().await;
+        service_specs.retain(|name, _| services.iter().any(|s| &s.name == name));
+        for (service, spec) in services.iter().zip(fetched) {
+            if let Some(spec) = spec {
+                service_specs.insert(service.name.clone(), spec);
+            }
+        }
+
+        let mut merged = self.gateway_spec.read().await.clone();
+        let mut names: Vec<_> = services.iter().filter(|s| service_specs.contains_key(&s.name)).collect();
+        names.sort_by(|a, b| a.name.cmp(&b.name));
+        for service in names {
+            merge_service_spec(&mut merged, service, &service_specs[&service.name]);
+        }
+        drop(service_specs);
+
+        *self.merged.write().await = merged;
+    }
+
+    async fn fetch_spec(&self, service: &ServiceConfig) -> Option<Value> {
+        let path = service.openapi_path.as_deref()?;
+        let url = format!("{}{}", service.base_url, path);
+
+        let response = self
+            .client
+            .get(&url)
+            .timeout(Duration::from_secs(OPENAPI_FETCH_TIMEOUT_SECS))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+
+        match response {
+            Ok(response) => match response.json::<Value>().await {
+                Ok(spec) => Some(spec),
+                Err(e) => {
+                    log::warn!("Invalid OpenAPI spec from {}: {}", url, e);
+                    None
+                },
+            },
+            Err(e) => {
+                log::warn!("Failed to fetch OpenAPI spec from {}: {}", url, e);
+                None
+            },
+        }
+    }
+}
+
+impl Default for OpenApiAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn merge_service_spec(merged: &mut Value, service: &ServiceConfig, spec: &Value) {
+    let prefix = &service.name;
+    let mut spec = spec.clone();
+    rewrite_refs(&mut spec, prefix);
+
+    let scheme_renames = merge_security_schemes(merged, prefix, &spec);
+
+    if let Some(components) = spec.get("components").and_then(Value::as_object) {
+        for section in NAMESPACED_COMPONENTS {
+            let Some(entries) = components.get(*section).and_then(Value::as_object) else {
+                continue;
+            };
+            let target = object_at(merged, &["components", section]);
+            for (name, entry) in entries {
+                target.insert(format!("{}_{}", prefix, name), entry.clone());
+            }
+        }
+    }
+
+    let global_security = spec.get("security").cloned();
+    let Some(paths) = spec.get("paths").and_then(Value::as_object) else {
+        return;
+    };
+
+    let merged_paths = object_at(merged, &["paths"]);
+    for (path, item) in paths {
+        // Published under the path the gateway routes to it, after the service's rewrite rules
+        let Some(public_path) = service.gateway_path(path) else {
+            log::debug!("No gateway route reaches {} {}, leaving it out of the spec", prefix, path);
+            continue;
+        };
+        let mut item = item.clone();
+        if let Some(operations) = item.as_object_mut() {
+            for (method, operation) in operations.iter_mut() {
+                if HTTP_METHODS.contains(&method.as_str()) {
+                    rewrite_operation(operation, service, global_security.as_ref(), &scheme_renames);
+                }
+            }
+        }
+        merged_paths.insert(public_path, item);
+    }
+
+    let tag = json!({ "name": prefix, "description": format!("Proxied through /gateway/{}", prefix) });
+    if let Some(merged) = merged.as_object_mut() {
+        if let Some(tags) = merged.entry("tags").or_insert_with(|| json!([])).as_array_mut() {
+            tags.push(tag);
+        }
+    }
+}
+
+fn rewrite_operation(
+    operation: &mut Value,
+    service: &ServiceConfig,
+    global_security: Option<&Value>,
+    scheme_renames: &HashMap<String, String>,
+) {
+    let Some(operation) = operation.as_object_mut() else {
+        return;
+    };
+
+    if let Some(Value::String(id)) = operation.get_mut("operationId") {
+        *id = format!("{}_{}", service.name, id);
+    }
+
+    let mut tags = vec![Value::String(service.name.clone())];
+    if let Some(Value::Array(existing)) = operation.get("tags") {
+        tags.extend(existing.iter().cloned());
+    }
+    operation.insert("tags".to_string(), Value::Array(tags));
+
+    // Operation security overrides the document's, keep that precedence after merging
+    let security = operation.get("security").or(global_security).cloned();
+    let security = match security {
+        Some(Value::Array(requirements)) => Some(Value::Array(
+            requirements
+                .into_iter()
+                .map(|requirement| rename_requirement(requirement, scheme_renames))
+                .collect(),
+        )),
+        other => other,
+    };
+
+    match security {
+        Some(security) => {
+            operation.insert("security".to_string(), security);
+        },
+        // The gateway enforces its own bearer token on protected services
+        None if service.require_auth => {
+            operation.insert("security".to_string(), json!([{ "jwt": [] }]));
+        },
+        None => {},
+    }
+}
+
+fn rename_requirement(requirement: Value, scheme_renames: &HashMap<String, String>) -> Value {
+    match requirement {
+        Value::Object(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(name, scopes)| (scheme_renames.get(&name).cloned().unwrap_or(name), scopes))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Merge the service's security schemes, renaming those that clash with a different definition
+fn merge_security_schemes(merged: &mut Value, prefix: &str, spec: &Value) -> HashMap<String, String> {
+    let mut renames = HashMap::new();
+    let Some(schemes) = spec.pointer("/components/securitySchemes").and_then(Value::as_object) else {
+        return renames;
+    };
+
+    let target = object_at(merged, &["components", "securitySchemes"]);
+    for (name, scheme) in schemes {
+        match target.get(name) {
+            Some(existing) if existing == scheme => {},
+            Some(_) => {
+                let renamed = format!("{}_{}", prefix, name);
+                target.insert(renamed.clone(), scheme.clone());
+                renames.insert(name.clone(), renamed);
+            },
+            None => {
+                target.insert(name.clone(), scheme.clone());
+            },
+        }
+    }
+    renames
+}
+
+/// Point every local `$ref` to the namespaced component
+fn rewrite_refs(value: &mut Value, prefix: &str) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                match child {
+                    Value::String(reference) if key == "$ref" => {
+                        if let Some(rewritten) = namespaced_ref(reference, prefix) {
+                            *reference = rewritten;
+                        }
+                    },
+                    _ => rewrite_refs(child, prefix),
+                }
+            }
+        },
+        Value::Array(items) => items.iter_mut().for_each(|item| rewrite_refs(item, prefix)),
+        _ => {},
+    }
+}
+
+fn namespaced_ref(reference: &str, prefix: &str) -> Option<String> {
+    let rest = reference.strip_prefix("#/components/")?;
+    let (section, name) = rest.split_once('/')?;
+    NAMESPACED_COMPONENTS
+        .contains(&section)
+        .then(|| format!("#/components/{}/{}_{}", section, prefix, name))
+}
+
+/// Get the object at `path`, creating empty objects along the way
+fn object_at<'a>(value: &'a mut Value, path: &[&str]) -> &'a mut Map<String, Value> {
+    let mut current = value;
+    for key in path {
+        if !current.is_object() {
+            *current = json!({});
+        }
+        current = current
+            .as_object_mut()
+            .map(|m| m.entry(key.to_string()).or_insert_with(|| json!({})))
+            .expect("value was just made an object");
+    }
+    if !current.is_object() {
+        *current = json!({});
+    }
+    current.as_object_mut().expect("value was just made an object")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::gateway::rewrite::RewriteRules;
+
+    fn service(rewrite: RewriteRules) -> ServiceConfig {
+        serde_json::from_value(json!({
+            "name": "product-service",
+            "base_url": "http://localhost:3002",
+            "health_check_path": null,
+            "timeout_secs": 30,
+            "require_auth": true,
+            "rewrite": rewrite,
+        }))
+        .unwrap()
+    }
+
+    fn merged_paths(service: &ServiceConfig, paths: Value) -> Vec<String> {
+        let mut merged = json!({ "paths": {} });
+        merge_service_spec(&mut merged, service, &json!({ "paths": paths }));
+        let mut paths: Vec<String> = merged["paths"].as_object().unwrap().keys().cloned().collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn paths_are_published_under_the_route_the_rewrite_maps_them_to() {
+        let rewrite = RewriteRules { add_prefix: Some("/v1".to_string()), ..RewriteRules::strip_gateway_prefix("product-service") };
+        let paths = json!({
+            "/v1/products": { "get": {} },
+            "/v1/products/{id}": { "get": {} },
+            "/health": { "get": {} },
+        });
+
+        assert_eq!(
+            merged_paths(&service(rewrite), paths),
+            vec!["/gateway/product-service/products", "/gateway/product-service/products/{id}"]
+        );
+    }
+
+    #[test]
+    fn service_without_rules_publishes_only_paths_under_its_route() {
+        let paths = json!({
+            "/gateway/product-service/api/v1/products": { "get": {} },
+            "/api/v1/products": { "get": {} },
+        });
+
+        assert_eq!(
+            merged_paths(&service(RewriteRules::default()), paths),
+            vec!["/gateway/product-service/api/v1/products"]
+        );
+    }
+}
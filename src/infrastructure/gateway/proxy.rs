@@ -1,13 +1,20 @@
-use crate::core::app_state::AppState;
+use crate::application::authen::claim::UserClaims;
 use crate::infrastructure::error::{AppError, AppResult};
+use crate::infrastructure::gateway::circuit_breaker::CircuitPermit;
+use crate::infrastructure::gateway::resilience::UpstreamGuard;
+use crate::infrastructure::gateway::retry::RetryPolicy;
 use crate::infrastructure::gateway::service_registry::ServiceConfig;
-use axum::body::Body;
-use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, Request, Response};
-use log::{error, info};
+use axum::body::{Body, Bytes};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, Request, Response, StatusCode};
+use log::{debug, error, info, warn};
 use reqwest::Client;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use utils::metrics::metrics;
+use utils::internal_auth::{InternalTokenSigner, IDENTITY_HEADERS, INTERNAL_CONTEXT_HEADER};
+use utils::request_id::{current_request_id, REQUEST_ID_HEADER};
+use utils::telemetry::inject_context;
 
-const HOP_BY_HOP_HEADERS: &[&str] = &[
+pub(crate) const HOP_BY_HOP_HEADERS: &[&str] = &[
     "connection",
     "keep-alive",
     "proxy-authenticate",
@@ -18,6 +25,47 @@ const HOP_BY_HOP_HEADERS: &[&str] = &[
     "upgrade",
 ];
 
+/// Upstream service a response came from, read by the access log
+#[derive(Debug, Clone)]
+pub struct UpstreamTarget(pub String);
+
+/// Authenticated caller forwarded to the upstream
+pub struct UpstreamIdentity {
+    pub user_id: i64,
+    pub session_id: String,
+    /// Signed internal token downstream services verify with `InternalUserContext`
+    pub context_token: String,
+}
+
+impl UpstreamIdentity {
+    /// Sign the caller's claims into the internal context token
+    pub fn sign(signer: &InternalTokenSigner, claims: &UserClaims) -> AppResult<Self> {
+        let session_id = claims.sid.to_string();
+        let context_token = signer.sign(claims.user_id, &session_id, &claims.roles)?;
+        Ok(Self { user_id: claims.user_id, session_id, context_token })
+    }
+
+    /// Add the user context headers, replacing any sent by the client
+    pub fn apply(&self, headers: &mut HeaderMap) -> AppResult<()> {
+        headers.insert(
+            HeaderName::from_static("x-user-id"),
+            HeaderValue::from_str(&self.user_id.to_string())
+                .map_err(|e| AppError::BadRequestError(format!("Invalid user ID: {}", e)))?,
+        );
+        headers.insert(
+            HeaderName::from_static("x-session-id"),
+            HeaderValue::from_str(&self.session_id)
+                .map_err(|e| AppError::BadRequestError(format!("Invalid session ID: {}", e)))?,
+        );
+        headers.insert(
+            HeaderName::from_static(INTERNAL_CONTEXT_HEADER),
+            HeaderValue::from_str(&self.context_token)
+                .map_err(|e| AppError::BadRequestError(format!("Invalid user context: {}", e)))?,
+        );
+        Ok(())
+    }
+}
+
 pub struct ProxyClient {
     client: Client,
 }
@@ -35,15 +83,18 @@ impl ProxyClient {
     pub async fn forward_request(
         &self,
         service_config: &ServiceConfig,
+        guard: &UpstreamGuard,
         original_request: Request<Body>,
-        user_id: Option<i64>,
-        session_id: Option<String>,
+        identity: Option<UpstreamIdentity>,
     ) -> AppResult<Response<Body>> {
         let method = original_request.method().clone();
         let uri = original_request.uri();
 
-        // Extract path after service name
-        let path = uri.path();
+        // Rewrite the incoming path into the service's own path
+        let path = service_config.rewrite.rewrite_path(uri.path());
+        if path != uri.path() {
+            debug!("Rewrote path {} -> {} (service: {})", uri.path(), path, service_config.name);
+        }
         let query = uri.query().unwrap_or("");
 
         // Build target URL
@@ -58,46 +109,91 @@ impl ProxyClient {
             method, target_url, service_config.name
         );
 
-        // Build headers
-        let mut headers = self.filter_headers(original_request.headers());
-
-        // Add user context headers if authenticated
-        if let Some(uid) = user_id {
-            headers.insert(
-                HeaderName::from_static("x-user-id"),
-                HeaderValue::from_str(&uid.to_string())
-                    .map_err(|e| AppError::BadRequestError(format!("Invalid user ID: {}", e)))?,
+        // Build headers after the rewrite rules, client-supplied identity headers are dropped
+        let mut incoming_headers = original_request.headers().clone();
+        if !service_config.rewrite.headers.is_empty() {
+            service_config.rewrite.rewrite_headers(&mut incoming_headers)?;
+            debug!(
+                "Rewrote headers with {} rules (service: {})",
+                service_config.rewrite.headers.len(),
+                service_config.name
             );
         }
+        let mut headers = self.filter_headers(&incoming_headers);
 
-        if let Some(sid) = session_id {
-            headers.insert(
-                HeaderName::from_static("x-session-id"),
-                HeaderValue::from_str(&sid)
-                    .map_err(|e| AppError::BadRequestError(format!("Invalid session ID: {}", e)))?,
-            );
+        // Correlate the upstream call with the gateway request
+        if let Some(request_id) = current_request_id() {
+            if let Ok(value) = HeaderValue::from_str(&request_id) {
+                headers.insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+            }
         }
 
-        // Get request body
+        // Add user context headers if authenticated
+        if let Some(identity) = identity {
+            identity.apply(&mut headers)?;
+        }
+
+        // Get request body, buffered so it can be replayed on retry
         let body_bytes = axum::body::to_bytes(original_request.into_body(), usize::MAX)
             .await
             .map_err(|e| AppError::BadRequestError(format!("Failed to read request body: {}", e)))?;
 
-        // Forward request
-        let response = self
-            .send_request(&method, &target_url, headers, body_bytes.to_vec())
-            .await?;
+        guard.retry_budget.record_request();
+        let retryable_method = RetryPolicy::is_retryable_method(&method);
+        let mut attempt: u32 = 0;
+
+        loop {
+            let permit = acquire_permit(service_config, guard)?;
+
+            let started = Instant::now();
+            let result = self
+                .send_request(&method, &target_url, headers.clone(), body_bytes.clone())
+                .await;
+            record_upstream_metrics(&service_config.name, started.elapsed(), &result);
 
-        Ok(response)
+            let upstream_failed = match &result {
+                Ok(response) => is_upstream_failure_status(response.status()),
+                Err(_) => true,
+            };
+
+            if !upstream_failed {
+                permit.success();
+                return result;
+            }
+            permit.failure();
+
+            attempt += 1;
+            let can_retry = retryable_method
+                && attempt <= service_config.retry.max_retries
+                && guard.retry_budget.try_withdraw();
+            if !can_retry {
+                return result;
+            }
+
+            let delay = service_config.retry.backoff(attempt);
+            warn!(
+                "Retrying {} {} (service: {}, attempt {}) in {:?}",
+                method, target_url, service_config.name, attempt, delay
+            );
+            tokio::time::sleep(delay).await;
+        }
     }
 
+    #[tracing::instrument(
+        name = "proxy.request",
+        skip_all,
+        fields(otel.kind = "client", http.request.method = %method, url.full = %url)
+    )]
     async fn send_request(
         &self,
         method: &Method,
         url: &str,
-        headers: HeaderMap,
-        body: Vec<u8>,
+        mut headers: HeaderMap,
+        body: Bytes,
     ) -> AppResult<Response<Body>> {
+        // Each attempt is its own span, so the upstream continues from it
+        inject_context(&mut headers);
+
         let mut request_builder = match method.as_str() {
             "GET" => self.client.get(url),
             "POST" => self.client.post(url),
@@ -134,7 +230,7 @@ impl ProxyClient {
             .await
             .map_err(|e| {
                 error!("Failed to proxy request: {}", e);
-                AppError::BadRequestError(format!("Failed to proxy request: {}", e))
+                map_upstream_error(e)
             })?;
 
         // Convert reqwest::Response to axum::Response
@@ -147,7 +243,7 @@ impl ProxyClient {
         let body_bytes = response
             .bytes()
             .await
-            .map_err(|e| AppError::BadRequestError(format!("Failed to read response body: {}", e)))?;
+            .map_err(map_upstream_error)?;
 
         let mut builder = Response::builder().status(status);
 
@@ -167,7 +263,9 @@ impl ProxyClient {
         let mut filtered = HeaderMap::new();
         for (key, value) in headers.iter() {
             let key_str = key.as_str().to_lowercase();
-            if !HOP_BY_HOP_HEADERS.contains(&key_str.as_str()) {
+            if !HOP_BY_HOP_HEADERS.contains(&key_str.as_str())
+                && !IDENTITY_HEADERS.contains(&key_str.as_str())
+            {
                 filtered.insert(key.clone(), value.clone());
             }
         }
@@ -175,6 +273,58 @@ impl ProxyClient {
     }
 }
 
+/// Circuit breaker permit for one upstream call, a 503 while the circuit is open
+pub(crate) fn acquire_permit(service_config: &ServiceConfig, guard: &UpstreamGuard) -> AppResult<CircuitPermit> {
+    guard.circuit_breaker.try_acquire().map_err(|retry_after| {
+        warn!("Circuit open for service {}, rejecting request", service_config.name);
+        metrics()
+            .upstream_errors_total
+            .with_label_values(&[service_config.name.as_str(), "circuit_open"])
+            .inc();
+        AppError::ServiceUnavailableError {
+            detail: format!("Service '{}' is temporarily unavailable", service_config.name),
+            retry_after_secs: retry_after.as_secs().max(1),
+        }
+    })
+}
+
+/// Upstream statuses that count against the circuit breaker and are worth retrying
+pub(crate) fn is_upstream_failure_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+pub(crate) fn record_upstream_metrics(service: &str, elapsed: Duration, result: &AppResult<Response<Body>>) {
+    let metrics = metrics();
+    let (status, error_kind) = match result {
+        Ok(response) => {
+            let status = response.status();
+            let kind = is_upstream_failure_status(status).then_some("status");
+            (status.as_u16().to_string(), kind)
+        },
+        Err(AppError::GatewayTimeoutError(_)) => ("error".to_string(), Some("timeout")),
+        Err(_) => ("error".to_string(), Some("connect")),
+    };
+
+    metrics
+        .upstream_request_duration_seconds
+        .with_label_values(&[service, status.as_str()])
+        .observe(elapsed.as_secs_f64());
+    if let Some(kind) = error_kind {
+        metrics.upstream_errors_total.with_label_values(&[service, kind]).inc();
+    }
+}
+
+fn map_upstream_error(e: reqwest::Error) -> AppError {
+    if e.is_timeout() {
+        AppError::GatewayTimeoutError(format!("Upstream request timed out: {}", e))
+    } else {
+        AppError::BadGatewayError(format!("Failed to proxy request: {}", e))
+    }
+}
+
 pub async fn check_service_health(
     client: &Client,
     base_url: &str,
@@ -187,3 +337,156 @@ pub async fn check_service_health(
         Err(_) => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::gateway::circuit_breaker::CircuitBreakerConfig;
+    use crate::infrastructure::gateway::rewrite::RewriteRules;
+    use axum::extract::State;
+    use axum::routing::any;
+    use axum::Router;
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// Upstream answering with the queued statuses in order, then 200
+    #[derive(Clone, Default)]
+    struct Upstream {
+        statuses: Arc<Mutex<VecDeque<StatusCode>>>,
+        hits: Arc<AtomicUsize>,
+        request_ids: Arc<Mutex<Vec<Option<String>>>>,
+    }
+
+    async fn respond(State(upstream): State<Upstream>, headers: HeaderMap) -> StatusCode {
+        upstream.hits.fetch_add(1, Ordering::SeqCst);
+        let request_id = headers.get(REQUEST_ID_HEADER).and_then(|value| value.to_str().ok());
+        upstream.request_ids.lock().unwrap().push(request_id.map(str::to_string));
+        upstream.statuses.lock().unwrap().pop_front().unwrap_or(StatusCode::OK)
+    }
+
+    async fn spawn_upstream(statuses: &[StatusCode]) -> (String, Upstream) {
+        let upstream = Upstream::default();
+        upstream.statuses.lock().unwrap().extend(statuses);
+        let app = Router::new().fallback(any(respond)).with_state(upstream.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), upstream)
+    }
+
+    fn service(base_url: String, retry: RetryPolicy) -> ServiceConfig {
+        ServiceConfig {
+            name: "test-service".to_string(),
+            base_url,
+            health_check_path: None,
+            timeout_secs: 5,
+            require_auth: false,
+            openapi_path: None,
+            circuit_breaker: CircuitBreakerConfig { failure_threshold: 100, ..CircuitBreakerConfig::default() },
+            retry,
+            cache: Vec::new(),
+            canary: None,
+            mirror: None,
+            rewrite: RewriteRules::default(),
+            grpc: None,
+        }
+    }
+
+    fn retry_policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy { max_retries, base_delay_ms: 1, max_delay_ms: 1, jitter: 0.0, ..RetryPolicy::default() }
+    }
+
+    async fn send(config: &ServiceConfig, guard: &UpstreamGuard, method: Method) -> StatusCode {
+        let request = Request::builder().method(method).uri("/items").body(Body::empty()).unwrap();
+        ProxyClient::new(5)
+            .unwrap()
+            .forward_request(config, guard, request, None)
+            .await
+            .expect("upstream answered")
+            .status()
+    }
+
+    #[tokio::test]
+    async fn retries_gateway_failure_statuses() {
+        for status in [StatusCode::BAD_GATEWAY, StatusCode::SERVICE_UNAVAILABLE, StatusCode::GATEWAY_TIMEOUT] {
+            let (base_url, upstream) = spawn_upstream(&[status, status]).await;
+            let config = service(base_url, retry_policy(2));
+            let guard = UpstreamGuard::new(&config);
+
+            assert_eq!(send(&config, &guard, Method::GET).await, StatusCode::OK, "{}", status);
+            assert_eq!(upstream.hits.load(Ordering::SeqCst), 3, "{}", status);
+        }
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_other_error_statuses() {
+        let (base_url, upstream) = spawn_upstream(&[StatusCode::INTERNAL_SERVER_ERROR]).await;
+        let config = service(base_url, retry_policy(2));
+        let guard = UpstreamGuard::new(&config);
+
+        assert_eq!(send(&config, &guard, Method::GET).await, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(upstream.hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_non_retryable_methods() {
+        for method in [Method::POST, Method::PATCH] {
+            let (base_url, upstream) = spawn_upstream(&[StatusCode::SERVICE_UNAVAILABLE]).await;
+            let config = service(base_url, retry_policy(2));
+            let guard = UpstreamGuard::new(&config);
+
+            assert_eq!(send(&config, &guard, method.clone()).await, StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(upstream.hits.load(Ordering::SeqCst), 1, "{}", method);
+        }
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let (base_url, upstream) = spawn_upstream(&[StatusCode::BAD_GATEWAY; 5]).await;
+        let config = service(base_url, retry_policy(2));
+        let guard = UpstreamGuard::new(&config);
+
+        assert_eq!(send(&config, &guard, Method::GET).await, StatusCode::BAD_GATEWAY);
+        assert_eq!(upstream.hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn stops_retrying_when_the_budget_is_exhausted() {
+        let (base_url, upstream) = spawn_upstream(&[StatusCode::SERVICE_UNAVAILABLE; 5]).await;
+        let retry = RetryPolicy { budget_ratio: 0.0, budget_min_retries: 1, ..retry_policy(3) };
+        let config = service(base_url, retry);
+        let guard = UpstreamGuard::new(&config);
+
+        // One retry in the window, then the budget is spent
+        assert_eq!(send(&config, &guard, Method::GET).await, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(upstream.hits.load(Ordering::SeqCst), 2);
+
+        assert_eq!(send(&config, &guard, Method::GET).await, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(upstream.hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn forwards_the_request_id_on_every_attempt() {
+        let (base_url, upstream) = spawn_upstream(&[StatusCode::SERVICE_UNAVAILABLE]).await;
+        let config = service(base_url, retry_policy(2));
+        let guard = UpstreamGuard::new(&config);
+
+        let status = utils::request_id::with_request_id("req-7".to_string(), send(&config, &guard, Method::GET)).await;
+
+        assert_eq!(status, StatusCode::OK);
+        let expected = vec![Some("req-7".to_string()); 2];
+        assert_eq!(*upstream.request_ids.lock().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn sends_no_request_id_outside_a_request() {
+        let (base_url, upstream) = spawn_upstream(&[]).await;
+        let config = service(base_url, retry_policy(0));
+        let guard = UpstreamGuard::new(&config);
+
+        send(&config, &guard, Method::GET).await;
+
+        assert_eq!(*upstream.request_ids.lock().unwrap(), vec![None]);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::infrastructure::gateway::circuit_breaker::{CircuitBreaker, CircuitState};
+use crate::infrastructure::gateway::retry::RetryBudget;
+use crate::infrastructure::gateway::service_registry::ServiceConfig;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Runtime resilience state kept for one downstream service
+#[derive(Debug)]
+pub struct UpstreamGuard {
+    pub circuit_breaker: CircuitBreaker,
+    pub retry_budget: RetryBudget,
+}
+
+impl UpstreamGuard {
+    pub fn new(config: &ServiceConfig) -> Self {
+        Self {
+            circuit_breaker: CircuitBreaker::new(config.circuit_breaker.clone()),
+            retry_budget: RetryBudget::new(&config.retry),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResilienceRegistry {
+    guards: Arc<RwLock<HashMap<String, Arc<UpstreamGuard>>>>,
+}
+
+impl ResilienceRegistry {
+    pub fn new() -> Self {
+        Self {
+            guards: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Get the guard for a service, creating it from the service config on first use
+    pub async fn guard_for(&self, config: &ServiceConfig) -> Arc<UpstreamGuard> {
+        if let Some(guard) = self.guards.read().await.get(&config.name) {
+            return guard.clone();
+        }
+
+        let mut guards = self.guards.write().await;
+        guards
+            .entry(config.name.clone())
+            .or_insert_with(|| Arc::new(UpstreamGuard::new(config)))
+            .clone()
+    }
+
+    /// Circuit state of a service, `Closed` if it has not been called yet
+    pub async fn circuit_state(&self, service_name: &str) -> CircuitState {
+        self.guards
+            .read()
+            .await
+            .get(service_name)
+            .map(|guard| guard.circuit_breaker.state())
+            .unwrap_or(CircuitState::Closed)
+    }
+}
+
+impl Default for ResilienceRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,594 @@
+use crate::infrastructure::gateway::service_registry::{ServiceConfig, ServiceRegistry};
+use crate::infrastructure::persistence::redis_client::RedisConnectionPool;
+use axum::body::{Body, Bytes};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, Response, StatusCode, Uri};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::Message;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use utils::metrics::metrics;
+use utils::rate_limit::API_KEY_HEADER;
+use utoipa::ToSchema;
+
+const CACHE_STATUS_HEADER: HeaderName = HeaderName::from_static("x-cache");
+
+/// Headers carrying caller credentials, a request with any of them is not anonymous
+const CREDENTIAL_HEADERS: [HeaderName; 3] =
+    [header::AUTHORIZATION, header::COOKIE, HeaderName::from_static(API_KEY_HEADER)];
+
+/// Who a cached response may be served to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheScope {
+    /// One entry served to every anonymous caller, requests with credentials bypass the cache
+    #[default]
+    Shared,
+    /// One entry per authenticated user
+    User,
+}
+
+/// Opt-in caching of upstream GET responses under a path prefix
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CacheRule {
+    /// Upstream path prefix, relative to `/gateway/{service}`
+    pub path_prefix: String,
+    #[serde(default)]
+    pub scope: CacheScope,
+    /// Freshness when the upstream sends no `max-age`/`s-maxage`
+    pub ttl_secs: u64,
+    /// How long a stale entry may be served while it is refreshed in the background
+    #[serde(default)]
+    pub stale_while_revalidate_secs: u64,
+    /// Kafka topics whose records purge the entries of this rule
+    #[serde(default)]
+    pub invalidate_on: Vec<String>,
+}
+
+impl CacheRule {
+    /// Whether a request may be answered from, and stored in, the cache under this rule
+    ///
+    /// Shared entries are keyed without the caller, so only anonymous requests use them;
+    /// the upstream leaving out `Cache-Control: private` is not enough to share a
+    /// response to an authenticated request. User entries need the caller resolved.
+    pub fn applies_to(&self, request_headers: &HeaderMap, user_id: Option<i64>) -> bool {
+        let has_credentials = CREDENTIAL_HEADERS.iter().any(|name| request_headers.contains_key(name));
+        match self.scope {
+            CacheScope::Shared => !has_credentials && user_id.is_none(),
+            CacheScope::User => user_id.is_some() || !has_credentials,
+        }
+    }
+}
+
+/// Identifies the cached responses of one request URI for one scope
+#[derive(Debug, Clone)]
+pub struct CacheKey {
+    base: String,
+}
+
+impl CacheKey {
+    pub fn new(service: &str, rule: &CacheRule, path: &str, uri: &Uri, user_id: Option<i64>) -> Self {
+        let scope = match (rule.scope, user_id) {
+            (CacheScope::Shared, _) => "shared".to_string(),
+            (CacheScope::User, Some(user_id)) => format!("user:{}", user_id),
+            (CacheScope::User, None) => "user:anonymous".to_string(),
+        };
+        let query = uri.query().map(|q| format!("?{}", q)).unwrap_or_default();
+        Self { base: format!("{}:{}{}|{}", service, path, query, scope) }
+    }
+
+    fn vary_key(&self) -> String {
+        format!("{}|vary", self.base)
+    }
+
+    fn variant_key(&self, vary: &[String], request_headers: &HeaderMap) -> String {
+        let values: Vec<String> = vary
+            .iter()
+            .map(|name| {
+                let value = request_headers
+                    .get(name.as_str())
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default();
+                format!("{}={}", name, value)
+            })
+            .collect();
+        format!("{}|{}", self.base, values.join("&"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    /// Base64 encoded body
+    body: String,
+    etag: Option<String>,
+    stored_at: i64,
+    fresh_secs: u64,
+    stale_secs: u64,
+    vary: Vec<String>,
+}
+
+impl CachedResponse {
+    fn age_secs(&self) -> u64 {
+        (chrono::Utc::now().timestamp() - self.stored_at).max(0) as u64
+    }
+
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    /// Build the client response, answering `If-None-Match` with 304 when the ETag matches
+    pub fn to_response(&self, request_headers: &HeaderMap, cache_status: &'static str) -> Response<Body> {
+        let not_modified = match (&self.etag, request_headers.get(header::IF_NONE_MATCH)) {
+            (Some(etag), Some(condition)) => condition
+                .to_str()
+                .map(|c| c.split(',').any(|t| t.trim() == etag || t.trim() == "*"))
+                .unwrap_or(false),
+            _ => false,
+        };
+
+        let mut builder = Response::builder().status(if not_modified {
+            StatusCode::NOT_MODIFIED
+        } else {
+            StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK)
+        });
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        builder = builder
+            .header(header::AGE, self.age_secs())
+            .header(CACHE_STATUS_HEADER, cache_status);
+
+        let body = if not_modified {
+            Body::empty()
+        } else {
+            Body::from(BASE64.decode(&self.body).unwrap_or_default())
+        };
+        builder.body(body).unwrap_or_else(|_| Response::new(Body::empty()))
+    }
+}
+
+pub enum CacheLookup {
+    Fresh(CachedResponse),
+    /// Past its freshness but inside the stale-while-revalidate window
+    Stale(CachedResponse),
+    Miss,
+}
+
+/// Request `Cache-Control` directives the cache honours
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RequestDirectives {
+    /// Skip the lookup but still store the fresh response
+    pub no_cache: bool,
+    /// Neither read nor write the cache
+    pub no_store: bool,
+}
+
+impl RequestDirectives {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let directives = cache_control(headers);
+        Self {
+            no_cache: directives.iter().any(|(name, _)| name == "no-cache"),
+            no_store: directives.iter().any(|(name, _)| name == "no-store"),
+        }
+    }
+}
+
+/// Upstream GET responses cached in Redis, shared by every gateway instance
+#[derive(Clone)]
+pub struct ResponseCache {
+    redis: RedisConnectionPool,
+    /// Entries being refreshed by this instance, so concurrent stale hits trigger one refresh
+    revalidating: Arc<Mutex<HashSet<String>>>,
+}
+
+impl ResponseCache {
+    pub fn new(redis: &RedisConnectionPool) -> Self {
+        Self {
+            redis: redis.with_prefix("response_cache"),
+            revalidating: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    pub async fn lookup(&self, key: &CacheKey, request_headers: &HeaderMap) -> CacheLookup {
+        let vary = match self.redis.get(&key.vary_key()).await {
+            Ok(Some(vary)) => serde_json::from_str::<Vec<String>>(&vary).unwrap_or_default(),
+            Ok(None) => return CacheLookup::Miss,
+            Err(e) => {
+                log::warn!("Response cache unavailable: {}", e);
+                return CacheLookup::Miss;
+            },
+        };
+
+        let entry = match self.redis.get(&key.variant_key(&vary, request_headers)).await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => return CacheLookup::Miss,
+            Err(e) => {
+                log::warn!("Response cache unavailable: {}", e);
+                return CacheLookup::Miss;
+            },
+        };
+
+        match serde_json::from_str::<CachedResponse>(&entry) {
+            Ok(entry) if entry.age_secs() < entry.fresh_secs => CacheLookup::Fresh(entry),
+            Ok(entry) if entry.age_secs() < entry.fresh_secs + entry.stale_secs => {
+                CacheLookup::Stale(entry)
+            },
+            Ok(_) => CacheLookup::Miss,
+            Err(e) => {
+                log::warn!("Discarding unreadable cache entry: {}", e);
+                CacheLookup::Miss
+            },
+        }
+    }
+
+    /// Store a cacheable upstream response and hand it back, buffered, to the caller
+    ///
+    /// Also returns the `x-cache` status: `MISS` when stored, `BYPASS` otherwise.
+    pub async fn store(
+        &self,
+        key: &CacheKey,
+        rule: &CacheRule,
+        request_headers: &HeaderMap,
+        response: Response<Body>,
+    ) -> (Response<Body>, &'static str) {
+        let (mut parts, body) = response.into_parts();
+        let body = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(e) => {
+                log::warn!("Failed to buffer upstream response for caching: {}", e);
+                return (Response::from_parts(parts, Body::empty()), "BYPASS");
+            },
+        };
+
+        let cache_status = match cacheable_entry(rule, parts.status, &parts.headers, &body) {
+            Some(entry) => {
+                self.write(key, request_headers, &entry).await;
+                "MISS"
+            },
+            None => "BYPASS",
+        };
+        parts
+            .headers
+            .insert(CACHE_STATUS_HEADER, HeaderValue::from_static(cache_status));
+        (Response::from_parts(parts, Body::from(body)), cache_status)
+    }
+
+    /// Restart the freshness of an entry after the upstream answered 304 to its ETag
+    pub async fn touch(&self, key: &CacheKey, request_headers: &HeaderMap, mut entry: CachedResponse) {
+        entry.stored_at = chrono::Utc::now().timestamp();
+        self.write(key, request_headers, &entry).await;
+    }
+
+    async fn write(&self, key: &CacheKey, request_headers: &HeaderMap, entry: &CachedResponse) {
+        let ttl = Duration::from_secs(entry.fresh_secs + entry.stale_secs);
+        let vary = serde_json::to_string(&entry.vary).unwrap_or_else(|_| "[]".to_string());
+        let variant_key = key.variant_key(&entry.vary, request_headers);
+
+        let result = match serde_json::to_string(entry) {
+            Ok(serialized) => match self.redis.set(&key.vary_key(), &vary, ttl).await {
+                Ok(()) => self.redis.set(&variant_key, &serialized, ttl).await,
+                Err(e) => Err(e),
+            },
+            Err(e) => {
+                log::warn!("Failed to serialize cache entry: {}", e);
+                return;
+            },
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to store response in cache: {}", e);
+        }
+    }
+
+    /// Claim the refresh of an entry, `false` if another task is already refreshing it
+    pub fn begin_revalidation(&self, key: &CacheKey) -> bool {
+        self.revalidating
+            .lock()
+            .map(|mut keys| keys.insert(key.base.clone()))
+            .unwrap_or(false)
+    }
+
+    pub fn end_revalidation(&self, key: &CacheKey) {
+        if let Ok(mut keys) = self.revalidating.lock() {
+            keys.remove(&key.base);
+        }
+    }
+
+    /// Remove the cached responses of a service, optionally only under a path prefix
+    pub async fn purge(&self, service: &str, path_prefix: Option<&str>) -> usize {
+        let pattern = format!("{}:{}*", service, escape_glob(path_prefix.unwrap_or("")));
+        let keys = match self.redis.scan(&pattern, None, None).await {
+            Ok(keys) => keys,
+            Err(e) => {
+                log::error!("Failed to scan response cache for {}: {}", pattern, e);
+                return 0;
+            },
+        };
+
+        let mut purged = 0;
+        for key in keys {
+            // Scanned keys carry the pool prefix, which delete_key adds again
+            let key = key
+                .strip_prefix(&format!("{}:", self.redis.key_prefix))
+                .unwrap_or(&key);
+            if let Ok(true) = self.redis.delete_key(key).await {
+                purged += 1;
+            }
+        }
+        log::info!("Purged {} response cache keys matching {}", purged, pattern);
+        purged
+    }
+
+    /// Purge cached responses when a record arrives on a topic listed in `invalidate_on`
+    ///
+    /// Subscriptions follow the registry, so rules of newly registered services apply.
+    pub async fn run_invalidation(&self, registry: Arc<ServiceRegistry>, consumer: StreamConsumer) {
+        let mut changes = registry.subscribe();
+
+        loop {
+            let services = registry.list_all().await;
+            let topics: HashSet<&str> = services
+                .iter()
+                .flat_map(|s| s.cache.iter())
+                .flat_map(|r| r.invalidate_on.iter().map(String::as_str))
+                .collect();
+
+            let subscribed = if topics.is_empty() {
+                consumer.unsubscribe();
+                false
+            } else {
+                let topics: Vec<&str> = topics.into_iter().collect();
+                consumer
+                    .subscribe(&topics)
+                    .inspect_err(|e| log::error!("Failed to subscribe to cache invalidation topics: {}", e))
+                    .is_ok()
+            };
+
+            loop {
+                tokio::select! {
+                    changed = changes.changed() => {
+                        if changed.is_err() {
+                            return;
+                        }
+                        break;
+                    },
+                    message = consumer.recv(), if subscribed => {
+                        match message {
+                            Ok(message) => self.invalidate_for_topic(&services, message.topic()).await,
+                            Err(e) => log::warn!("Cache invalidation consumer error: {}", e),
+                        }
+                    },
+                }
+            }
+        }
+    }
+
+    async fn invalidate_for_topic(&self, services: &[ServiceConfig], topic: &str) {
+        for service in services {
+            for rule in service.cache.iter().filter(|r| r.invalidate_on.iter().any(|t| t == topic)) {
+                self.purge(&service.name, Some(&rule.path_prefix)).await;
+            }
+        }
+    }
+}
+
+/// Entry for a response, or `None` when the response or the rule forbid caching it
+fn cacheable_entry(
+    rule: &CacheRule,
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Option<CachedResponse> {
+    if status != StatusCode::OK || headers.contains_key(header::SET_COOKIE) {
+        return None;
+    }
+
+    let directives = cache_control(headers);
+    let has = |name: &str| directives.iter().any(|(n, _)| n == name);
+    let seconds = |name: &str| {
+        directives
+            .iter()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, v)| v.as_deref()?.parse::<u64>().ok())
+    };
+
+    if has("no-store") || has("no-cache") || (rule.scope == CacheScope::Shared && has("private")) {
+        return None;
+    }
+
+    let vary: Vec<String> = headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect();
+    if vary.iter().any(|name| name == "*") {
+        return None;
+    }
+
+    let max_age = match rule.scope {
+        CacheScope::Shared => seconds("s-maxage").or_else(|| seconds("max-age")),
+        CacheScope::User => seconds("max-age"),
+    };
+    let fresh_secs = max_age.unwrap_or(rule.ttl_secs);
+    let stale_secs = seconds("stale-while-revalidate").unwrap_or(rule.stale_while_revalidate_secs);
+    if fresh_secs + stale_secs == 0 {
+        return None;
+    }
+
+    let stored_headers = headers
+        .iter()
+        .filter(|(name, _)| *name != header::AGE && *name != CACHE_STATUS_HEADER)
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+
+    Some(CachedResponse {
+        status: status.as_u16(),
+        headers: stored_headers,
+        body: BASE64.encode(body),
+        etag: headers
+            .get(header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+        stored_at: chrono::Utc::now().timestamp(),
+        fresh_secs,
+        stale_secs,
+        vary,
+    })
+}
+
+/// Parsed `Cache-Control` directives, names lowercased
+fn cache_control(headers: &HeaderMap) -> Vec<(String, Option<String>)> {
+    headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|directive| {
+            let directive = directive.trim();
+            if directive.is_empty() {
+                return None;
+            }
+            Some(match directive.split_once('=') {
+                Some((name, value)) => (
+                    name.trim().to_ascii_lowercase(),
+                    Some(value.trim().trim_matches('"').to_string()),
+                ),
+                None => (directive.to_ascii_lowercase(), None),
+            })
+        })
+        .collect()
+}
+
+fn escape_glob(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Record the cache outcome of a proxied request
+pub fn record_cache_result(service: &str, cache_status: &str) {
+    metrics()
+        .response_cache_total
+        .with_label_values(&[service, cache_status])
+        .inc();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(scope: CacheScope) -> CacheRule {
+        CacheRule {
+            path_prefix: "/api/v1/products".to_string(),
+            scope,
+            ttl_secs: 60,
+            stale_while_revalidate_secs: 30,
+            invalidate_on: Vec::new(),
+        }
+    }
+
+    fn key(scope: CacheScope, uri: &str, user_id: Option<i64>) -> CacheKey {
+        let uri: Uri = uri.parse().unwrap();
+        CacheKey::new("product-service", &rule(scope), uri.path(), &uri, user_id)
+    }
+
+    fn headers(pairs: &[(HeaderName, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::from_str(value).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn shared_key_includes_the_query_but_not_the_caller() {
+        let key = key(CacheScope::Shared, "/api/v1/products?page=2", Some(7));
+
+        assert_eq!(key.base, "product-service:/api/v1/products?page=2|shared");
+        assert_eq!(key.vary_key(), "product-service:/api/v1/products?page=2|shared|vary");
+    }
+
+    #[test]
+    fn user_keys_are_separate_per_user() {
+        assert_eq!(key(CacheScope::User, "/api/v1/products", Some(7)).base, "product-service:/api/v1/products|user:7");
+        assert_eq!(key(CacheScope::User, "/api/v1/products", Some(8)).base, "product-service:/api/v1/products|user:8");
+        assert_eq!(
+            key(CacheScope::User, "/api/v1/products", None).base,
+            "product-service:/api/v1/products|user:anonymous"
+        );
+    }
+
+    #[test]
+    fn variant_key_includes_the_vary_header_values() {
+        let key = key(CacheScope::Shared, "/api/v1/products", None);
+        let vary = vec!["accept-language".to_string(), "accept".to_string()];
+
+        let german = key.variant_key(&vary, &headers(&[(header::ACCEPT_LANGUAGE, "de")]));
+        let english = key.variant_key(&vary, &headers(&[(header::ACCEPT_LANGUAGE, "en")]));
+
+        assert_eq!(german, "product-service:/api/v1/products|shared|accept-language=de&accept=");
+        assert_ne!(german, english);
+    }
+
+    #[test]
+    fn shared_rule_is_only_for_anonymous_requests() {
+        let shared = rule(CacheScope::Shared);
+
+        assert!(shared.applies_to(&HeaderMap::new(), None));
+        assert!(!shared.applies_to(&HeaderMap::new(), Some(7)), "resolved identity");
+        for credential in CREDENTIAL_HEADERS {
+            assert!(!shared.applies_to(&headers(&[(credential.clone(), "secret")]), None), "{}", credential);
+        }
+    }
+
+    #[test]
+    fn user_rule_needs_the_caller_resolved_when_credentials_are_sent() {
+        let per_user = rule(CacheScope::User);
+        let bearer = headers(&[(header::AUTHORIZATION, "Bearer token")]);
+
+        assert!(per_user.applies_to(&bearer, Some(7)));
+        assert!(per_user.applies_to(&HeaderMap::new(), None));
+        assert!(!per_user.applies_to(&bearer, None), "unresolved credentials must not share the anonymous entry");
+    }
+
+    #[test]
+    fn upstream_directives_decide_what_is_stored() {
+        let body = Bytes::from_static(b"[]");
+        let cacheable = |scope, pairs: &[(HeaderName, &str)]| {
+            cacheable_entry(&rule(scope), StatusCode::OK, &headers(pairs), &body).is_some()
+        };
+
+        assert!(cacheable(CacheScope::Shared, &[]));
+        assert!(!cacheable(CacheScope::Shared, &[(header::CACHE_CONTROL, "private")]));
+        assert!(cacheable(CacheScope::User, &[(header::CACHE_CONTROL, "private")]));
+        assert!(!cacheable(CacheScope::User, &[(header::CACHE_CONTROL, "no-store")]));
+        assert!(!cacheable(CacheScope::Shared, &[(header::SET_COOKIE, "session=1")]));
+        assert!(!cacheable(CacheScope::Shared, &[(header::VARY, "*")]));
+        assert!(cacheable_entry(&rule(CacheScope::Shared), StatusCode::NOT_FOUND, &HeaderMap::new(), &body).is_none());
+    }
+
+    #[test]
+    fn shared_entries_prefer_s_maxage() {
+        let entry = cacheable_entry(
+            &rule(CacheScope::Shared),
+            StatusCode::OK,
+            &headers(&[(header::CACHE_CONTROL, "max-age=10, s-maxage=20")]),
+            &Bytes::new(),
+        )
+        .unwrap();
+
+        assert_eq!(entry.fresh_secs, 20);
+        assert_eq!(entry.stale_secs, 30);
+    }
+}
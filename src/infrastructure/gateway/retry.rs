@@ -0,0 +1,122 @@
+use axum::http::Method;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 disables retrying)
+    pub max_retries: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Fraction of the computed delay that is randomised, between 0.0 and 1.0
+    pub jitter: f64,
+    /// Retries allowed as a fraction of requests seen in the current budget window
+    pub budget_ratio: f64,
+    /// Retries always allowed per budget window, regardless of traffic
+    pub budget_min_retries: u32,
+    pub budget_window_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay_ms: 100,
+            max_delay_ms: 2000,
+            jitter: 0.5,
+            budget_ratio: 0.2,
+            budget_min_retries: 10,
+            budget_window_secs: 10,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Only methods that are safe to replay are retried
+    pub fn is_retryable_method(method: &Method) -> bool {
+        matches!(
+            *method,
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+        )
+    }
+
+    /// Exponential backoff for the given retry attempt (1-based) with jitter applied
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let delay_ms = self
+            .base_delay_ms
+            .saturating_mul(1u64 << exponent)
+            .min(self.max_delay_ms);
+
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 || delay_ms == 0 {
+            return Duration::from_millis(delay_ms);
+        }
+
+        let spread = (delay_ms as f64 * jitter) as u64;
+        let jittered = delay_ms - spread + rand::thread_rng().gen_range(0..=spread);
+        Duration::from_millis(jittered)
+    }
+}
+
+#[derive(Debug)]
+struct BudgetWindow {
+    started_at: Instant,
+    requests: u64,
+    retries: u64,
+}
+
+/// Caps retries to a share of recent traffic so retries cannot amplify an outage
+#[derive(Debug)]
+pub struct RetryBudget {
+    ratio: f64,
+    min_retries: u32,
+    window: Duration,
+    inner: Mutex<BudgetWindow>,
+}
+
+impl RetryBudget {
+    pub fn new(policy: &RetryPolicy) -> Self {
+        Self {
+            ratio: policy.budget_ratio.max(0.0),
+            min_retries: policy.budget_min_retries,
+            window: Duration::from_secs(policy.budget_window_secs.max(1)),
+            inner: Mutex::new(BudgetWindow {
+                started_at: Instant::now(),
+                requests: 0,
+                retries: 0,
+            }),
+        }
+    }
+
+    pub fn record_request(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        self.roll_window(&mut inner);
+        inner.requests += 1;
+    }
+
+    /// Withdraw one retry from the budget, returning false when it is exhausted
+    pub fn try_withdraw(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        self.roll_window(&mut inner);
+
+        let allowed = self.min_retries as u64 + (inner.requests as f64 * self.ratio) as u64;
+        if inner.retries < allowed {
+            inner.retries += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn roll_window(&self, inner: &mut BudgetWindow) {
+        if inner.started_at.elapsed() >= self.window {
+            inner.started_at = Instant::now();
+            inner.requests = 0;
+            inner.retries = 0;
+        }
+    }
+}
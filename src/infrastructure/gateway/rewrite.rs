@@ -0,0 +1,167 @@
+use crate::infrastructure::error::{AppError, AppResult};
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// How a request is rewritten before it is sent to a service
+///
+/// The path is rewritten in order: `strip_prefix`, each of `replace`, then `add_prefix`.
+/// Header rules run on the incoming headers before identity headers are dropped, so
+/// they cannot be used to spoof the user context.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct RewriteRules {
+    /// Removed from the start of the path, e.g. `/gateway/product-service`
+    pub strip_prefix: Option<String>,
+    pub replace: Vec<PathReplace>,
+    /// Prepended to the path, e.g. `/v1`
+    pub add_prefix: Option<String>,
+    pub headers: Vec<HeaderRewrite>,
+}
+
+/// Regex replacement applied to the whole path, `$1`-style captures allowed
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PathReplace {
+    #[schema(value_type = String)]
+    pub pattern: PathPattern,
+    pub replacement: String,
+}
+
+impl PathReplace {
+    pub fn new(pattern: &str, replacement: &str) -> Result<Self, regex::Error> {
+        Ok(Self { pattern: PathPattern::new(pattern)?, replacement: replacement.to_string() })
+    }
+}
+
+/// Path regex, compiled once when the service config is built or deserialized
+///
+/// An invalid pattern is rejected with the config instead of failing every request.
+#[derive(Debug, Clone)]
+pub struct PathPattern(Regex);
+
+impl PathPattern {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Regex::new(pattern).map(Self)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl Serialize for PathPattern {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for PathPattern {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Self::new(&pattern).map_err(|e| {
+            serde::de::Error::custom(format!("invalid rewrite pattern '{}': {}", pattern, e))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum HeaderRewrite {
+    /// Set a header, replacing any value sent by the client
+    Add { name: String, value: String },
+    Remove { name: String },
+    Rename { from: String, to: String },
+}
+
+impl RewriteRules {
+    /// Strip the `/gateway/{service}` route prefix so the service sees its own paths
+    ///
+    /// Opt-in: a service without rewrite rules receives the full gateway path.
+    pub fn strip_gateway_prefix(service: &str) -> Self {
+        Self {
+            strip_prefix: Some(format!("/gateway/{}", service)),
+            ..Self::default()
+        }
+    }
+
+    pub fn rewrite_path(&self, path: &str) -> String {
+        let mut path = path.to_string();
+
+        if let Some(prefix) = &self.strip_prefix {
+            if let Some(rest) = path.strip_prefix(prefix.as_str()) {
+                if rest.is_empty() || rest.starts_with('/') {
+                    path = if rest.is_empty() { "/".to_string() } else { rest.to_string() };
+                }
+            }
+        }
+
+        for rule in &self.replace {
+            path = rule.pattern.0.replace_all(&path, rule.replacement.as_str()).into_owned();
+        }
+
+        if let Some(prefix) = &self.add_prefix {
+            let prefix = prefix.trim_end_matches('/');
+            path = if path == "/" { format!("{}/", prefix) } else { format!("{}{}", prefix, path) };
+        }
+
+        if !path.starts_with('/') {
+            path.insert(0, '/');
+        }
+        path
+    }
+
+    /// Incoming path that `rewrite_path` turns into `service_path`, the inverse of the rules
+    ///
+    /// `None` when no incoming path maps to it, e.g. it lacks the `add_prefix`, or a
+    /// `replace` rule changes the path so it cannot be reversed.
+    pub fn original_path(&self, service_path: &str) -> Option<String> {
+        let mut path = service_path.to_string();
+        if let Some(prefix) = &self.add_prefix {
+            let rest = path.strip_prefix(prefix.trim_end_matches('/'))?;
+            if !rest.starts_with('/') {
+                return None;
+            }
+            path = rest.to_string();
+        }
+        if let Some(prefix) = &self.strip_prefix {
+            path = format!("{}{}", prefix.trim_end_matches('/'), path);
+        }
+        (self.rewrite_path(&path) == service_path).then_some(path)
+    }
+
+    pub fn rewrite_headers(&self, headers: &mut HeaderMap) -> AppResult<()> {
+        for rule in &self.headers {
+            match rule {
+                HeaderRewrite::Add { name, value } => {
+                    headers.insert(header_name(name)?, header_value(value)?);
+                },
+                HeaderRewrite::Remove { name } => {
+                    headers.remove(header_name(name)?);
+                },
+                HeaderRewrite::Rename { from, to } => {
+                    let to = header_name(to)?;
+                    let values: Vec<HeaderValue> = match headers.entry(header_name(from)?) {
+                        axum::http::header::Entry::Occupied(entry) => entry.remove_entry_mult().1.collect(),
+                        axum::http::header::Entry::Vacant(_) => continue,
+                    };
+                    headers.remove(&to);
+                    for value in values {
+                        headers.append(to.clone(), value);
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+fn header_name(name: &str) -> AppResult<HeaderName> {
+    HeaderName::from_bytes(name.as_bytes())
+        .map_err(|e| AppError::BadGatewayError(format!("Invalid rewrite header '{}': {}", name, e)))
+}
+
+fn header_value(value: &str) -> AppResult<HeaderValue> {
+    HeaderValue::from_str(value)
+        .map_err(|e| AppError::BadGatewayError(format!("Invalid rewrite header value: {}", e)))
+}
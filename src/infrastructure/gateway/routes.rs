@@ -1,24 +1,40 @@
 use crate::core::app_state::AppState;
 use crate::infrastructure::error::{AppError, AppResult};
-use crate::core::response::EntityResponse;
-use crate::infrastructure::gateway::proxy::{check_service_health, ProxyClient};
+use crate::core::response::{EntityResponse, MessageResponse};
+use crate::infrastructure::gateway::aggregation::{
+    record_part_result, self_upstream, CompositePart, CompositeResponse, PartError, SELF_SERVICE,
+};
+use crate::infrastructure::gateway::canary::{
+    record_upstream_version, STABLE_VERSION, UPSTREAM_VERSION_HEADER,
+};
+use crate::infrastructure::gateway::circuit_breaker::CircuitState;
+use crate::infrastructure::gateway::mirror::send_mirrored;
+use crate::infrastructure::gateway::proxy::{ProxyClient, UpstreamIdentity, UpstreamTarget};
+use crate::infrastructure::gateway::response_cache::{
+    record_cache_result, CacheKey, CacheLookup, CacheRule, CachedResponse, RequestDirectives,
+};
 use crate::infrastructure::gateway::service_registry::ServiceConfig;
 use axum::body::Body;
-use axum::extract::{Request, State};
-use axum::http::Response;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, Response, StatusCode, Uri};
 use axum::response::IntoResponse;
 use axum::Json;
 use log::{error, info};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use utoipa::{IntoParams, ToSchema};
+use utils::request_id;
 use crate::application::authen::claim::UserClaims;
-use crate::infrastructure::constant::ACCESS_TOKEN_DECODE_KEY;
+use crate::infrastructure::middleware::authenticate::extract_bearer_claims;
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ServiceHealth {
     pub name: String,
     pub base_url: String,
     pub healthy: bool,
+    pub circuit_state: CircuitState,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -29,7 +45,8 @@ pub struct GatewayHealth {
 
 /// Gateway health check
 ///
-/// Check the health of the API gateway and all registered downstream services.
+/// Check the health of the API gateway and all registered downstream services,
+/// as last observed by the background health monitor.
 #[utoipa::path(
     get,
     path = "/gateway/health",
@@ -42,20 +59,21 @@ pub async fn gateway_health_check(
     State(state): State<AppState>,
 ) -> AppResult<Json<EntityResponse<GatewayHealth>>> {
     let services = state.gateway_registry.list_all().await;
-    let client = reqwest::Client::new();
 
     let mut service_healths = Vec::new();
     let mut all_healthy = true;
 
     for service in services {
-        let healthy = check_service_health(
-            &client,
-            &service.base_url,
-            service.health_check_path.as_deref(),
-        )
-        .await;
-
-        if !healthy {
+        // Served from the background health monitor, never probed inline
+        let healthy = state
+            .health_monitor
+            .get(&service.name)
+            .await
+            .is_some_and(|component| component.healthy);
+
+        let circuit_state = state.gateway_resilience.circuit_state(&service.name).await;
+
+        if !healthy || circuit_state != CircuitState::Closed {
             all_healthy = false;
         }
 
@@ -63,6 +81,7 @@ pub async fn gateway_health_check(
             name: service.name.clone(),
             base_url: service.base_url.clone(),
             healthy,
+            circuit_state,
         });
     }
 
@@ -88,7 +107,7 @@ pub async fn gateway_health_check(
     path = "/gateway/services",
     tag = "Gateway",
     security(
-        ("bearer_auth" = [])
+        ("jwt" = [])
     ),
     responses(
         (status = 200, description = "List of services", body = EntityResponse<Vec<ServiceConfig>>),
@@ -109,11 +128,232 @@ pub async fn list_services(
     }))
 }
 
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PurgeCacheQuery {
+    /// Only purge responses under this upstream path prefix
+    pub path_prefix: Option<String>,
+}
+
+/// Purge cached responses
+///
+/// Remove the cached GET responses of a service, optionally only under a path prefix.
+/// Requires the admin role.
+#[utoipa::path(
+    delete,
+    path = "/gateway/cache/{service}",
+    tag = "Gateway",
+    params(
+        ("service" = String, Path, description = "Registered service name"),
+        PurgeCacheQuery
+    ),
+    security(
+        ("jwt" = [])
+    ),
+    responses(
+        (status = 200, description = "Cache purged", body = MessageResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin role required"),
+        (status = 400, description = "Service not found"),
+    )
+)]
+pub async fn purge_cache(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Path(service): Path<String>,
+    Query(query): Query<PurgeCacheQuery>,
+) -> AppResult<Json<MessageResponse>> {
+    if !claims.roles.iter().any(|role| role == "admin") {
+        return Err(AppError::PermissionDeniedError(
+            "Purging the response cache requires the admin role".to_string(),
+        ));
+    }
+    if state.gateway_registry.get(&service).await.is_none() {
+        return Err(AppError::EntityNotFoundError {
+            detail: format!("Service '{}' not found", service),
+        });
+    }
+
+    info!("User {} purging response cache of {}", claims.user_id, service);
+    let purged = state.response_cache.purge(&service, query.path_prefix.as_deref()).await;
+    Ok(Json(MessageResponse::new(format!("Purged {} cached responses", purged))))
+}
+
+/// Composite endpoint
+///
+/// Fetch every part of a declared composite endpoint in parallel and merge the JSON
+/// results. Optional parts that fail or time out are `null` and listed in `errors`.
+#[utoipa::path(
+    get,
+    path = "/gateway/compose/{name}",
+    tag = "Gateway",
+    params(
+        ("name" = String, Path, description = "Composite endpoint name, e.g. `home`"),
+    ),
+    security(
+        ("jwt" = [])
+    ),
+    responses(
+        (status = 200, description = "Merged response", body = EntityResponse<CompositeResponse>),
+        (status = 400, description = "Composite endpoint not found, or a path placeholder is missing from the query"),
+        (status = 401, description = "Unauthorized"),
+        (status = 502, description = "A required part failed"),
+    )
+)]
+pub async fn compose(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> AppResult<Json<EntityResponse<CompositeResponse>>> {
+    let endpoint = state
+        .composite_registry
+        .get(&name)
+        .await
+        .ok_or_else(|| AppError::EntityNotFoundError {
+            detail: format!("Composite endpoint '{}' not found", name),
+        })?;
+
+    let claims = extract_bearer_claims(&headers);
+    if endpoint.require_auth && claims.is_none() {
+        return Err(AppError::UnauthorizedError(format!(
+            "Composite endpoint '{}' requires authentication",
+            name
+        )));
+    }
+
+    // Bad placeholders fail the whole request before anything is sent
+    let user_id = claims.as_ref().map(|c| c.user_id);
+    let requests = endpoint
+        .parts
+        .iter()
+        .map(|part| part_request(part, user_id, &query, &headers))
+        .collect::<AppResult<Vec<_>>>()?;
+
+    let results = futures::future::join_all(
+        endpoint
+            .parts
+            .iter()
+            .zip(requests)
+            .map(|(part, request)| fetch_part(&state, &endpoint.name, part, claims.as_ref(), request)),
+    )
+    .await;
+
+    let response = CompositeResponse::merge(&endpoint, results)?;
+    Ok(Json(EntityResponse {
+        message: format!("Composite '{}' retrieved", name),
+        data: Some(response),
+        total: endpoint.parts.len() as i64,
+    }))
+}
+
+fn part_request(
+    part: &CompositePart,
+    user_id: Option<i64>,
+    query: &HashMap<String, String>,
+    headers: &HeaderMap,
+) -> AppResult<Request> {
+    let path = part.render_path(user_id, query)?;
+    let uri = if part.service == SELF_SERVICE {
+        path
+    } else {
+        format!("/gateway/{}{}", part.service, path)
+    };
+
+    // Bodies are parsed here, so ask for plain JSON
+    let mut part_headers = headers.clone();
+    part_headers.remove(header::CONTENT_LENGTH);
+    part_headers.remove(header::CONTENT_TYPE);
+    part_headers.remove(header::ACCEPT_ENCODING);
+    part_headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+
+    let mut request = Request::new(Body::empty());
+    *request.uri_mut() = uri.parse().map_err(|e| {
+        AppError::BadGatewayError(format!("Invalid path for part '{}': {}", part.key, e))
+    })?;
+    *request.headers_mut() = part_headers;
+    Ok(request)
+}
+
+/// Fetch one part within its timeout, turning any failure into a `PartError`
+async fn fetch_part(
+    state: &AppState,
+    endpoint: &str,
+    part: &CompositePart,
+    claims: Option<&UserClaims>,
+    request: Request,
+) -> Result<Value, PartError> {
+    let send = async {
+        if part.service == SELF_SERVICE {
+            send_upstream(state, &self_upstream(&state.config), None, request).await
+        } else {
+            forward_to_service(&part.service, state.clone(), claims.cloned(), request).await
+        }
+    };
+
+    let result = match tokio::time::timeout(Duration::from_millis(part.timeout_ms), send).await {
+        Ok(result) => result,
+        Err(_) => {
+            record_part_result(endpoint, &part.key, "timeout");
+            log::warn!("Part {} of {} timed out after {}ms", part.key, endpoint, part.timeout_ms);
+            return Err(PartError::new(
+                &part.key,
+                StatusCode::GATEWAY_TIMEOUT,
+                format!("Timed out after {}ms", part.timeout_ms),
+            ));
+        },
+    };
+
+    let outcome = match result {
+        Ok(response) if response.status().is_success() => {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .map_err(|e| format!("Failed to read response: {}", e));
+            body.and_then(|body| part.extract(&body))
+                .map_err(|detail| PartError::new(&part.key, StatusCode::BAD_GATEWAY, detail))
+        },
+        Ok(response) => {
+            let status = response.status();
+            Err(PartError::new(&part.key, status, format!("Upstream responded with {}", status)))
+        },
+        Err(e) => {
+            let detail = e.to_string();
+            Err(PartError::new(&part.key, e.into_response().status(), detail))
+        },
+    };
+
+    match &outcome {
+        Ok(_) => record_part_result(endpoint, &part.key, "ok"),
+        Err(error) => {
+            record_part_result(endpoint, &part.key, "error");
+            log::warn!("Part {} of {} failed: {}", part.key, endpoint, error.detail);
+        },
+    }
+    outcome
+}
+
+/// Aggregated OpenAPI document covering the gateway and every downstream service
+pub async fn aggregated_openapi(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(state.openapi_aggregator.merged_spec().await)
+}
+
 async fn proxy_to_service(
     service_name: &str,
     state: AppState,
     claims: Option<UserClaims>,
     request: Request,
+) -> Response<Body> {
+    let mut response = forward_to_service(service_name, state, claims, request)
+        .await
+        .into_response();
+    response.extensions_mut().insert(UpstreamTarget(service_name.to_string()));
+    response
+}
+
+async fn forward_to_service(
+    service_name: &str,
+    state: AppState,
+    claims: Option<UserClaims>,
+    request: Request,
 ) -> AppResult<Response<Body>> {
     // Get service configuration
     let service_config = state
@@ -132,43 +372,184 @@ async fn proxy_to_service(
         )));
     }
 
-    // Extract user context
-    let (user_id, session_id) = match claims {
-        Some(ref c) => (Some(c.user_id), Some(c.sid.to_string())),
-        None => (None, None),
+    if let Some(grpc) = &service_config.grpc {
+        let path = service_config.relative_path(request.uri().path());
+        if let Some((route, params)) = grpc.transcode_route(request.method(), path) {
+            let identity = claims
+                .as_ref()
+                .map(|c| UpstreamIdentity::sign(&state.internal_token_signer, c))
+                .transpose()?;
+            let guard = state.gateway_resilience.guard_for(&service_config).await;
+            return state
+                .grpc_gateway
+                .transcode(&service_config, grpc, &guard, route, params, request, identity)
+                .await;
+        }
+    }
+
+    let Some(canary) = service_config.canary.clone() else {
+        return forward_with_cache(state, service_config, claims, request).await;
+    };
+
+    let user_id = claims.as_ref().map(|c| c.user_id);
+    let use_canary = canary.selects(&service_config.name, request.headers(), user_id);
+    let version = if use_canary { canary.version.as_str() } else { STABLE_VERSION };
+    record_upstream_version(&service_config.name, version);
+
+    let result = if use_canary {
+        // Canary responses are never cached, so a bad build cannot poison shared entries
+        let upstream = canary.upstream(&service_config);
+        send_upstream(&state, &upstream, claims.as_ref(), request).await
+    } else {
+        forward_with_cache(state, service_config, claims, request).await
+    };
+
+    let mut response = result.into_response();
+    if let Ok(value) = HeaderValue::from_str(version) {
+        response.headers_mut().insert(UPSTREAM_VERSION_HEADER, value);
+    }
+    Ok(response)
+}
+
+async fn forward_with_cache(
+    state: AppState,
+    service_config: ServiceConfig,
+    claims: Option<UserClaims>,
+    request: Request,
+) -> AppResult<Response<Body>> {
+    let path = service_config.relative_path(request.uri().path());
+    let rule = match *request.method() {
+        Method::GET => service_config.cache_rule_for(path).cloned(),
+        _ => None,
+    };
+    let Some(rule) = rule else {
+        return send_primary(&state, &service_config, claims.as_ref(), request).await;
+    };
+
+    let user_id = claims.as_ref().map(|c| c.user_id);
+    let directives = RequestDirectives::from_headers(request.headers());
+    if directives.no_store || !rule.applies_to(request.headers(), user_id) {
+        record_cache_result(&service_config.name, "BYPASS");
+        return send_primary(&state, &service_config, claims.as_ref(), request).await;
+    }
+
+    let key = CacheKey::new(&service_config.name, &rule, path, request.uri(), user_id);
+    let request_headers = request.headers().clone();
+
+    if !directives.no_cache {
+        match state.response_cache.lookup(&key, &request_headers).await {
+            CacheLookup::Fresh(entry) => {
+                record_cache_result(&service_config.name, "HIT");
+                return Ok(entry.to_response(&request_headers, "HIT"));
+            },
+            CacheLookup::Stale(entry) => {
+                record_cache_result(&service_config.name, "STALE");
+                let response = entry.to_response(&request_headers, "STALE");
+                let uri = request.uri().clone();
+                spawn_revalidation(state, service_config, claims, rule, key, uri, request_headers, entry);
+                return Ok(response);
+            },
+            CacheLookup::Miss => {},
+        }
+    }
+
+    let response = send_primary(&state, &service_config, claims.as_ref(), request).await?;
+    let (response, cache_status) =
+        state.response_cache.store(&key, &rule, &request_headers, response).await;
+    record_cache_result(&service_config.name, cache_status);
+    Ok(response)
+}
+
+/// Refresh a stale cache entry in the background, revalidating with its ETag
+#[allow(clippy::too_many_arguments)]
+fn spawn_revalidation(
+    state: AppState,
+    service_config: ServiceConfig,
+    claims: Option<UserClaims>,
+    rule: CacheRule,
+    key: CacheKey,
+    uri: Uri,
+    request_headers: HeaderMap,
+    entry: CachedResponse,
+) {
+    let cache = state.response_cache.clone();
+    if !cache.begin_revalidation(&key) {
+        return;
+    }
+
+    request_id::spawn(async move {
+        let mut upstream_headers = request_headers.clone();
+        upstream_headers.remove(header::IF_NONE_MATCH);
+        if let Some(etag) = entry.etag().and_then(|etag| HeaderValue::from_str(etag).ok()) {
+            upstream_headers.insert(header::IF_NONE_MATCH, etag);
+        }
+
+        let mut request = Request::new(Body::empty());
+        *request.uri_mut() = uri;
+        *request.headers_mut() = upstream_headers;
+
+        match send_upstream(&state, &service_config, claims.as_ref(), request).await {
+            Ok(response) if response.status() == StatusCode::NOT_MODIFIED => {
+                cache.touch(&key, &request_headers, entry).await;
+            },
+            Ok(response) => {
+                cache.store(&key, &rule, &request_headers, response).await;
+            },
+            Err(e) => log::warn!("Failed to revalidate cached response: {}", e),
+        }
+        cache.end_revalidation(&key);
+    });
+}
+
+/// Send a request to the service, copying it to the shadow upstream when sampled
+async fn send_primary(
+    state: &AppState,
+    service_config: &ServiceConfig,
+    claims: Option<&UserClaims>,
+    request: Request,
+) -> AppResult<Response<Body>> {
+    let Some(mirror) = service_config.mirror.as_ref() else {
+        return send_upstream(state, service_config, claims, request).await;
     };
 
+    send_mirrored(mirror, service_config, request, |upstream, request| {
+        let (state, claims) = (state.clone(), claims.cloned());
+        async move { send_upstream(&state, &upstream, claims.as_ref(), request).await }
+    })
+    .await
+}
+
+async fn send_upstream(
+    state: &AppState,
+    service_config: &ServiceConfig,
+    claims: Option<&UserClaims>,
+    request: Request,
+) -> AppResult<Response<Body>> {
+    // Extract user context and sign it for the upstream
+    let identity = claims
+        .map(|c| UpstreamIdentity::sign(&state.internal_token_signer, c))
+        .transpose()?;
+
     // Create proxy client
     let proxy_client = ProxyClient::new(service_config.timeout_secs)?;
+    let guard = state.gateway_resilience.guard_for(service_config).await;
 
     // Forward request
     proxy_client
-        .forward_request(&service_config, request, user_id, session_id)
+        .forward_request(service_config, &guard, request, identity)
         .await
 }
 
 // Helper function to extract user claims from request
 fn extract_claims_from_request(request: &Request) -> Option<UserClaims> {
-    // Try to extract Authorization header
-    request
-        .headers()
-        .get("authorization")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|auth_str| {
-            if let Some(token) = auth_str.strip_prefix("Bearer ") {
-                &ACCESS_TOKEN_DECODE_KEY;
-                UserClaims::decode(token, &ACCESS_TOKEN_DECODE_KEY).ok().map(|td| td.claims)
-            } else {
-                None
-            }
-        })
+    extract_bearer_claims(request.headers())
 }
 
 // Proxy handlers for each service
 pub async fn proxy_to_product_service(
     State(state): State<AppState>,
     request: Request,
-) -> AppResult<Response<Body>> {
+) -> Response<Body> {
     let claims = extract_claims_from_request(&request);
     proxy_to_service("product-service", state, claims, request).await
 }
@@ -176,7 +557,7 @@ pub async fn proxy_to_product_service(
 pub async fn proxy_to_order_service(
     State(state): State<AppState>,
     request: Request,
-) -> AppResult<Response<Body>> {
+) -> Response<Body> {
     let claims = extract_claims_from_request(&request);
     proxy_to_service("order-service", state, claims, request).await
 }
@@ -184,7 +565,7 @@ pub async fn proxy_to_order_service(
 pub async fn proxy_to_inventory_service(
     State(state): State<AppState>,
     request: Request,
-) -> AppResult<Response<Body>> {
+) -> Response<Body> {
     let claims = extract_claims_from_request(&request);
     proxy_to_service("inventory-service", state, claims, request).await
 }
@@ -192,7 +573,7 @@ pub async fn proxy_to_inventory_service(
 pub async fn proxy_to_notification_service(
     State(state): State<AppState>,
     request: Request,
-) -> AppResult<Response<Body>> {
+) -> Response<Body> {
     let claims = extract_claims_from_request(&request);
     proxy_to_service("notification-service", state, claims, request).await
 }
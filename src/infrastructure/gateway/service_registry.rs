@@ -1,7 +1,14 @@
+use crate::infrastructure::gateway::canary::CanaryConfig;
+use crate::infrastructure::gateway::circuit_breaker::CircuitBreakerConfig;
+use crate::infrastructure::gateway::grpc::GrpcConfig;
+use crate::infrastructure::gateway::mirror::MirrorConfig;
+use crate::infrastructure::gateway::response_cache::{CacheRule, CacheScope};
+use crate::infrastructure::gateway::retry::RetryPolicy;
+use crate::infrastructure::gateway::rewrite::RewriteRules;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use utoipa::ToSchema;
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -11,17 +18,70 @@ pub struct ServiceConfig {
     pub health_check_path: Option<String>,
     pub timeout_secs: u64,
     pub require_auth: bool,
+    /// Path of the service's OpenAPI document, merged into the gateway's aggregated spec
+    #[serde(default)]
+    pub openapi_path: Option<String>,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Opt-in GET response caching, by upstream path prefix
+    #[serde(default)]
+    pub cache: Vec<CacheRule>,
+    /// Alternate upstream version receiving part of the traffic
+    #[serde(default)]
+    pub canary: Option<CanaryConfig>,
+    /// Shadow upstream receiving a sampled copy of requests, responses discarded
+    #[serde(default)]
+    pub mirror: Option<MirrorConfig>,
+    /// Path and header rewriting before the request is forwarded
+    #[serde(default)]
+    pub rewrite: RewriteRules,
+    /// gRPC endpoint for native passthrough and JSON transcoding
+    #[serde(default)]
+    pub grpc: Option<GrpcConfig>,
+}
+
+impl ServiceConfig {
+    /// Path as seen by the service, without the `/gateway/{service}` route prefix
+    pub fn relative_path<'a>(&self, path: &'a str) -> &'a str {
+        path.strip_prefix("/gateway/")
+            .and_then(|rest| rest.strip_prefix(self.name.as_str()))
+            .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+            .unwrap_or(path)
+    }
+
+    /// Public gateway path reaching `service_path` once the rewrite rules are applied
+    ///
+    /// `None` when the rules cannot produce it from a `/gateway/{service}/...` path.
+    pub fn gateway_path(&self, service_path: &str) -> Option<String> {
+        let route_prefix = format!("/gateway/{}/", self.name);
+        self.rewrite
+            .original_path(service_path)
+            .filter(|path| path.starts_with(&route_prefix))
+    }
+
+    /// Cache rule with the longest prefix matching `path`
+    pub fn cache_rule_for(&self, path: &str) -> Option<&CacheRule> {
+        self.cache
+            .iter()
+            .filter(|rule| path.starts_with(&rule.path_prefix))
+            .max_by_key(|rule| rule.path_prefix.len())
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ServiceRegistry {
     services: Arc<RwLock<HashMap<String, ServiceConfig>>>,
+    /// Bumped on every register/remove so listeners can react to changes
+    version: Arc<watch::Sender<u64>>,
 }
 
 impl ServiceRegistry {
     pub fn new() -> Self {
         Self {
             services: Arc::new(RwLock::new(HashMap::new())),
+            version: Arc::new(watch::Sender::new(0)),
         }
     }
 
@@ -32,44 +92,107 @@ impl ServiceRegistry {
         registry
             .register(ServiceConfig {
                 name: "product-service".to_string(),
+                rewrite: default_rewrite("product-service", "PRODUCT_SERVICE"),
                 base_url: std::env::var("PRODUCT_SERVICE_URL")
                     .unwrap_or_else(|_| "http://localhost:3002".to_string()),
                 health_check_path: Some("/health".to_string()),
                 timeout_secs: 30,
                 require_auth: true,
+                openapi_path: Some("/api-docs/openapi.json".to_string()),
+                circuit_breaker: CircuitBreakerConfig::default(),
+                retry: RetryPolicy::default(),
+                cache: vec![CacheRule {
+                    path_prefix: "/api/v1/products".to_string(),
+                    // Callers are authenticated, so entries are kept per user
+                    scope: CacheScope::User,
+                    ttl_secs: 60,
+                    stale_while_revalidate_secs: 30,
+                    invalidate_on: vec![
+                        "product_created".to_string(),
+                        "product_updated".to_string(),
+                        "product_deleted".to_string(),
+                    ],
+                }],
+                canary: None,
+                // Shadow traffic for testing rewrites, off unless a shadow URL is set
+                mirror: std::env::var("PRODUCT_SERVICE_SHADOW_URL").ok().map(|base_url| MirrorConfig {
+                    base_url,
+                    sample_percent: std::env::var("PRODUCT_SERVICE_SHADOW_SAMPLE_PERCENT")
+                        .ok()
+                        .and_then(|percent| percent.parse().ok())
+                        .unwrap_or(10),
+                    include_non_idempotent: false,
+                    timeout_secs: 10,
+                }),
+                grpc: None,
             })
             .await;
 
         registry
             .register(ServiceConfig {
                 name: "order-service".to_string(),
+                rewrite: default_rewrite("order-service", "ORDER_SERVICE"),
                 base_url: std::env::var("ORDER_SERVICE_URL")
                     .unwrap_or_else(|_| "http://localhost:3003".to_string()),
                 health_check_path: Some("/health".to_string()),
                 timeout_secs: 30,
                 require_auth: true,
+                openapi_path: Some("/api-docs/openapi.json".to_string()),
+                circuit_breaker: CircuitBreakerConfig::default(),
+                retry: RetryPolicy::default(),
+                cache: Vec::new(),
+                // Gradual rollout of new builds, off unless a canary URL is set
+                canary: std::env::var("ORDER_SERVICE_CANARY_URL").ok().map(|base_url| CanaryConfig {
+                    version: std::env::var("ORDER_SERVICE_CANARY_VERSION")
+                        .unwrap_or_else(|_| "canary".to_string()),
+                    base_url,
+                    weight_percent: std::env::var("ORDER_SERVICE_CANARY_WEIGHT")
+                        .ok()
+                        .and_then(|weight| weight.parse().ok())
+                        .unwrap_or(0),
+                    match_headers: HashMap::from([("x-canary".to_string(), "always".to_string())]),
+                    user_ids: Vec::new(),
+                }),
+                mirror: None,
+                grpc: None,
             })
             .await;
 
         registry
             .register(ServiceConfig {
                 name: "inventory-service".to_string(),
+                rewrite: default_rewrite("inventory-service", "INVENTORY_SERVICE"),
                 base_url: std::env::var("INVENTORY_SERVICE_URL")
                     .unwrap_or_else(|_| "http://localhost:3004".to_string()),
                 health_check_path: Some("/health".to_string()),
                 timeout_secs: 30,
                 require_auth: true,
+                openapi_path: Some("/api-docs/openapi.json".to_string()),
+                circuit_breaker: CircuitBreakerConfig::default(),
+                retry: RetryPolicy::default(),
+                cache: Vec::new(),
+                canary: None,
+                mirror: None,
+                grpc: None,
             })
             .await;
 
         registry
             .register(ServiceConfig {
                 name: "notification-service".to_string(),
+                rewrite: default_rewrite("notification-service", "NOTIFICATION_SERVICE"),
                 base_url: std::env::var("NOTIFICATION_SERVICE_URL")
                     .unwrap_or_else(|_| "http://localhost:3005".to_string()),
                 health_check_path: Some("/health".to_string()),
                 timeout_secs: 30,
                 require_auth: false,
+                openapi_path: Some("/api-docs/openapi.json".to_string()),
+                circuit_breaker: CircuitBreakerConfig::default(),
+                retry: RetryPolicy::default(),
+                cache: Vec::new(),
+                canary: None,
+                mirror: None,
+                grpc: None,
             })
             .await;
 
@@ -79,6 +202,7 @@ impl ServiceRegistry {
     pub async fn register(&self, config: ServiceConfig) {
         let mut services = self.services.write().await;
         services.insert(config.name.clone(), config);
+        self.version.send_modify(|v| *v += 1);
     }
 
     pub async fn get(&self, name: &str) -> Option<ServiceConfig> {
@@ -91,9 +215,44 @@ impl ServiceRegistry {
         services.values().cloned().collect()
     }
 
+    /// Service declaring a fully qualified gRPC service for passthrough
+    pub async fn find_by_grpc_service(&self, grpc_service: &str) -> Option<ServiceConfig> {
+        let services = self.services.read().await;
+        services
+            .values()
+            .find(|config| {
+                config
+                    .grpc
+                    .as_ref()
+                    .is_some_and(|grpc| grpc.services.iter().any(|s| s == grpc_service))
+            })
+            .cloned()
+    }
+
     pub async fn remove(&self, name: &str) -> Option<ServiceConfig> {
         let mut services = self.services.write().await;
-        services.remove(name)
+        let removed = services.remove(name);
+        if removed.is_some() {
+            self.version.send_modify(|v| *v += 1);
+        }
+        removed
+    }
+
+    /// Receiver notified whenever a service is registered or removed
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.version.subscribe()
+    }
+}
+
+/// Rewrite rules of a default service: the full gateway path is forwarded unless the
+/// service opts in to prefix stripping with `{env_prefix}_STRIP_GATEWAY_PREFIX=true`
+fn default_rewrite(service: &str, env_prefix: &str) -> RewriteRules {
+    let strip = std::env::var(format!("{}_STRIP_GATEWAY_PREFIX", env_prefix))
+        .is_ok_and(|value| value.eq_ignore_ascii_case("true"));
+    if strip {
+        RewriteRules::strip_gateway_prefix(service)
+    } else {
+        RewriteRules::default()
     }
 }
 
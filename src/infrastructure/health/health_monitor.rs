@@ -0,0 +1,307 @@
+use crate::core::app_state::AppState;
+use crate::infrastructure::constant::{
+    HEALTH_CHECK_INTERVAL_SECS, HEALTH_CHECK_TIMEOUT_SECS, HEALTH_HISTORY_LIMIT,
+};
+use crate::infrastructure::gateway::proxy::check_service_health;
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use rdkafka::producer::{FutureProducer, Producer};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentKind {
+    Database,
+    Cache,
+    Broker,
+    Upstream,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ComponentHealth {
+    pub name: String,
+    pub kind: ComponentKind,
+    pub healthy: bool,
+    /// Readiness fails while a critical component is unhealthy
+    pub critical: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthTransition {
+    pub name: String,
+    pub kind: ComponentKind,
+    /// `None` for the first observation of a component
+    pub previous_healthy: Option<bool>,
+    pub healthy: bool,
+    pub error: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Caches the health of every dependency, refreshed by a background task
+///
+/// Health endpoints read the cache instead of probing dependencies on each call.
+#[derive(Debug, Clone)]
+pub struct HealthMonitor {
+    components: Arc<RwLock<HashMap<String, ComponentHealth>>>,
+    history: Arc<RwLock<VecDeque<HealthTransition>>>,
+    first_round_done: Arc<AtomicBool>,
+}
+
+impl HealthMonitor {
+    pub fn new() -> Self {
+        Self {
+            components: Arc::new(RwLock::new(HashMap::new())),
+            history: Arc::new(RwLock::new(VecDeque::new())),
+            first_round_done: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Run the check loop forever, meant to be spawned once at startup
+    pub async fn run(&self, state: AppState) {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(HEALTH_CHECK_INTERVAL_SECS));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        log::info!("Starting health monitor (interval: {}s)", HEALTH_CHECK_INTERVAL_SECS);
+        loop {
+            interval.tick().await;
+            self.check_all(&state).await;
+        }
+    }
+
+    pub async fn check_all(&self, state: &AppState) {
+        let services = state.gateway_registry.list_all().await;
+        let client = reqwest::Client::new();
+
+        let upstreams = services.iter().map(|service| {
+            let client = &client;
+            timed(&service.name, ComponentKind::Upstream, false, async move {
+                let path = service.health_check_path.as_deref();
+                if check_service_health(client, &service.base_url, path).await {
+                    Ok(())
+                } else {
+                    Err(format!("Health check failed for {}", service.base_url))
+                }
+            })
+        });
+
+        let (postgres, redis, kafka, upstreams) = tokio::join!(
+            timed("postgres", ComponentKind::Database, true, async {
+                state.db.ping().await.map_err(|e| e.to_string())
+            }),
+            timed("redis", ComponentKind::Cache, true, async {
+                state.redis.ping().await.map(|_| ()).map_err(|e| e.to_string())
+            }),
+            // Without Kafka there is no broker to report on
+            async {
+                match &state.kafka_producer {
+                    Some(producer) => Some(timed("kafka", ComponentKind::Broker, false, check_kafka(producer.clone())).await),
+                    None => None,
+                }
+            },
+            join_all(upstreams),
+        );
+
+        self.record_round([postgres, redis].into_iter().chain(kafka).chain(upstreams)).await;
+    }
+
+    /// Record one round of checks, after which readiness is reported
+    async fn record_round(&self, components: impl IntoIterator<Item = ComponentHealth>) {
+        for component in components {
+            self.record(component).await;
+        }
+        self.first_round_done.store(true, Ordering::Release);
+    }
+
+    async fn record(&self, component: ComponentHealth) {
+        let previous = self
+            .components
+            .write()
+            .await
+            .insert(component.name.clone(), component.clone());
+
+        let previous_healthy = previous.map(|p| p.healthy);
+        if previous_healthy == Some(component.healthy) {
+            return;
+        }
+
+        if component.healthy {
+            log::info!("Health of {} changed to healthy", component.name);
+        } else {
+            log::warn!(
+                "Health of {} changed to unhealthy: {}",
+                component.name,
+                component.error.as_deref().unwrap_or("unknown error")
+            );
+        }
+
+        let mut history = self.history.write().await;
+        history.push_back(HealthTransition {
+            name: component.name,
+            kind: component.kind,
+            previous_healthy,
+            healthy: component.healthy,
+            error: component.error,
+            changed_at: component.checked_at,
+        });
+        while history.len() > HEALTH_HISTORY_LIMIT {
+            history.pop_front();
+        }
+    }
+
+    pub async fn get(&self, name: &str) -> Option<ComponentHealth> {
+        self.components.read().await.get(name).cloned()
+    }
+
+    pub async fn components(&self) -> Vec<ComponentHealth> {
+        let mut components: Vec<_> = self.components.read().await.values().cloned().collect();
+        components.sort_by(|a, b| a.name.cmp(&b.name));
+        components
+    }
+
+    /// Most recent transitions first
+    pub async fn history(&self) -> Vec<HealthTransition> {
+        self.history.read().await.iter().rev().cloned().collect()
+    }
+
+    /// Ready once the first round of checks has finished and every critical component is healthy
+    pub async fn is_ready(&self) -> bool {
+        self.first_round_done.load(Ordering::Acquire)
+            && self
+                .components
+                .read()
+                .await
+                .values()
+                .all(|c| c.healthy || !c.critical)
+    }
+}
+
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn timed(
+    name: &str,
+    kind: ComponentKind,
+    critical: bool,
+    check: impl Future<Output = Result<(), String>>,
+) -> ComponentHealth {
+    let started = Instant::now();
+    let timeout = std::time::Duration::from_secs(HEALTH_CHECK_TIMEOUT_SECS);
+    let result = match tokio::time::timeout(timeout, check).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("Timed out after {}s", HEALTH_CHECK_TIMEOUT_SECS)),
+    };
+
+    ComponentHealth {
+        name: name.to_string(),
+        kind,
+        healthy: result.is_ok(),
+        critical,
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: result.err(),
+        checked_at: Utc::now(),
+    }
+}
+
+async fn check_kafka(producer: Arc<FutureProducer>) -> Result<(), String> {
+    // fetch_metadata blocks the calling thread
+    tokio::task::spawn_blocking(move || {
+        producer
+            .client()
+            .fetch_metadata(None, std::time::Duration::from_secs(HEALTH_CHECK_TIMEOUT_SECS))
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component(name: &str, critical: bool, healthy: bool) -> ComponentHealth {
+        ComponentHealth {
+            name: name.to_string(),
+            kind: if critical { ComponentKind::Database } else { ComponentKind::Upstream },
+            healthy,
+            critical,
+            latency_ms: 1,
+            error: (!healthy).then(|| "connection refused".to_string()),
+            checked_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn rounds_track_components_going_down_and_up() {
+        let monitor = HealthMonitor::new();
+
+        monitor.record_round([component("postgres", true, true), component("user-service", false, true)]).await;
+        monitor.record_round([component("postgres", true, true), component("user-service", false, false)]).await;
+        monitor.record_round([component("postgres", true, true), component("user-service", false, true)]).await;
+
+        assert!(monitor.get("user-service").await.unwrap().healthy);
+        let transitions: Vec<_> = monitor
+            .history()
+            .await
+            .into_iter()
+            .filter(|transition| transition.name == "user-service")
+            .map(|transition| (transition.previous_healthy, transition.healthy))
+            .collect();
+        // Newest first; repeated states are not transitions
+        assert_eq!(transitions, vec![(Some(false), true), (Some(true), false), (None, true)]);
+        assert_eq!(monitor.history().await.iter().filter(|transition| transition.name == "postgres").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn not_ready_before_the_first_round() {
+        let monitor = HealthMonitor::new();
+        assert!(!monitor.is_ready().await);
+
+        monitor.record(component("postgres", true, true)).await;
+        assert!(!monitor.is_ready().await, "a single check is not a round");
+
+        monitor.record_round([component("postgres", true, true)]).await;
+        assert!(monitor.is_ready().await);
+    }
+
+    #[tokio::test]
+    async fn only_critical_components_affect_readiness() {
+        let monitor = HealthMonitor::new();
+
+        monitor.record_round([component("postgres", true, true), component("user-service", false, false)]).await;
+        assert!(monitor.is_ready().await);
+
+        monitor.record_round([component("postgres", true, false), component("user-service", false, false)]).await;
+        assert!(!monitor.is_ready().await);
+
+        monitor.record_round([component("postgres", true, true)]).await;
+        assert!(monitor.is_ready().await);
+    }
+
+    #[tokio::test]
+    async fn history_is_capped() {
+        let monitor = HealthMonitor::new();
+
+        for round in 0..HEALTH_HISTORY_LIMIT + 10 {
+            monitor.record(component("redis", true, round % 2 == 0)).await;
+        }
+
+        let history = monitor.history().await;
+        assert_eq!(history.len(), HEALTH_HISTORY_LIMIT);
+        assert_eq!(history[0].healthy, (HEALTH_HISTORY_LIMIT + 9) % 2 == 0, "the newest transition is kept");
+    }
+}
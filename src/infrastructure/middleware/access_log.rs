@@ -0,0 +1,55 @@
+use crate::infrastructure::gateway::proxy::UpstreamTarget;
+use crate::infrastructure::middleware::authenticate::extract_bearer_claims;
+use axum::extract::{ConnectInfo, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::time::Instant;
+use utils::request_id::current_request_id;
+use utils::telemetry::ACCESS_LOG_TARGET;
+
+#[derive(Debug, Serialize)]
+struct AccessLogEntry {
+    timestamp: String,
+    request_id: Option<String>,
+    method: String,
+    path: String,
+    status: u16,
+    latency_ms: u128,
+    user_id: Option<i64>,
+    client_ip: Option<String>,
+    upstream: Option<String>,
+}
+
+/// Middleware writing one structured access log entry per request
+pub async fn access_log(request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let user_id = extract_bearer_claims(request.headers()).map(|claims| claims.user_id);
+    let client_ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string());
+
+    let response = next.run(request).await;
+
+    let entry = AccessLogEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        request_id: current_request_id(),
+        method,
+        path,
+        status: response.status().as_u16(),
+        latency_ms: started.elapsed().as_millis(),
+        user_id,
+        client_ip,
+        upstream: response.extensions().get::<UpstreamTarget>().map(|u| u.0.clone()),
+    };
+    match serde_json::to_string(&entry) {
+        Ok(line) => tracing::info!(target: ACCESS_LOG_TARGET, "{}", line),
+        Err(e) => log::error!("Failed to serialize access log entry: {}", e),
+    }
+
+    response
+}
@@ -2,6 +2,7 @@ use crate::core::app_state::AppState;
 use crate::infrastructure::error::AppError;
 use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
+use axum::http::{header, HeaderMap};
 use axum::RequestPartsExt;
 use axum_extra::{
     headers::{authorization::Bearer, Authorization},
@@ -33,3 +34,13 @@ impl FromRequestParts<AppState> for UserClaims {
         }
     }
 }
+
+/// Decode the bearer token of a request without rejecting it, for optional authentication
+pub fn extract_bearer_claims(headers: &HeaderMap) -> Option<UserClaims> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|auth_str| auth_str.strip_prefix("Bearer "))
+        .and_then(|token| UserClaims::decode(token, &ACCESS_TOKEN_DECODE_KEY).ok())
+        .map(|td| td.claims)
+}
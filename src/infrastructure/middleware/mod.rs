@@ -1 +1,3 @@
+pub mod access_log;
 pub mod authenticate;
+pub mod rate_limit;
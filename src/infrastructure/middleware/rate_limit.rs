@@ -0,0 +1,35 @@
+use crate::core::app_state::AppState;
+use crate::infrastructure::middleware::authenticate::extract_bearer_claims;
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::Request;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use utils::rate_limit::{Identity, RateLimitLayer, RedisRateLimiter};
+
+/// Authenticated callers are limited per user ID, callers with a registered API key per
+/// key, everyone else per client IP
+pub fn resolve_identity(request: &Request<Body>, api_keys: &HashMap<String, String>) -> Identity {
+    if let Some(claims) = extract_bearer_claims(request.headers()) {
+        return Identity::User(claims.user_id.to_string());
+    }
+    if let Some(identity) = Identity::from_api_key(request.headers(), api_keys) {
+        return identity;
+    }
+
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| Identity::Ip(addr.ip().to_string()))
+        .unwrap_or(Identity::Unknown)
+}
+
+pub fn rate_limit_layer(state: &AppState) -> RateLimitLayer {
+    let api_keys = state.config.rate_limit.api_key_names();
+    RateLimitLayer::new(
+        RedisRateLimiter::new(&state.redis),
+        state.config.rate_limit.rules.clone(),
+        Arc::new(move |request: &Request<Body>| resolve_identity(request, &api_keys)),
+    )
+}
@@ -1,4 +1,5 @@
 pub mod gateway;
+pub mod health;
 pub mod middleware;
 pub mod persistence;
 pub mod third_party;
@@ -7,20 +7,14 @@ use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseTransaction, EntityLoaderTr
 
 #[async_trait]
 impl AddressRepositoryInterface for Entity {
-    async fn create_address(conn: &DatabaseTransaction, model: ActiveModelEx) -> AppResult<bool> {
-        let _address = model
-            .insert(conn)
-            .await
-            .map_err(|e| AppError::DatabaseError(e));
-        Ok(true)
+    async fn create_address(conn: &DatabaseTransaction, model: ActiveModelEx) -> AppResult<ModelEx> {
+        let address = model.insert(conn).await?;
+        Ok(address)
     }
 
     async fn update_address(conn: &DatabaseTransaction, model: ActiveModelEx) -> AppResult<bool> {
         // Convert Model to ActiveModel in infrastructure layer
-        let _address = model
-            .update(conn)
-            .await
-            .map_err(|e| AppError::DatabaseError(e));
+        let _address = model.update(conn).await?;
         Ok(true)
     }
 
@@ -1,2 +1,3 @@
 mod user_repository;
-mod address_repository;
\ No newline at end of file
+mod address_repository;mod outbox_repository;
+mod webhook_repository;
@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveEnum, ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseTransaction, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set, Statement,
+};
+use crate::domain::outbox::outbox::{ActiveModel, Column, Entity, Model, Status};
+use crate::domain::outbox::outbox_repository_interface::OutboxRepositoryInterface;
+use crate::infrastructure::constant::SERVICE_NAME;
+use crate::infrastructure::error::AppResult;
+use crate::infrastructure::third_party::kafka::context_headers_json;
+use serde_json::Value;
+use utils::events::{DomainEvent, EventEnvelope};
+use utils::kafka_consumer::EVENT_TYPE_HEADER;
+
+/// Advisory lock key held by the relay publishing the outbox, so only one publishes at a time
+const OUTBOX_RELAY_LOCK_KEY: i64 = 0x6f75_7462_6f78;
+
+#[async_trait]
+impl OutboxRepositoryInterface for Entity {
+    async fn enqueue_event<E: DomainEvent + 'static>(conn: &DatabaseTransaction, event: E) -> AppResult<()> {
+        let envelope = EventEnvelope::new(SERVICE_NAME, event);
+        let mut headers = context_headers_json();
+        if let Value::Object(headers) = &mut headers {
+            headers.insert(EVENT_TYPE_HEADER.to_string(), Value::from(E::EVENT_TYPE));
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        let event = ActiveModel {
+            topic: Set(E::TOPIC.to_string()),
+            aggregate_key: Set(envelope.aggregateid.clone()),
+            payload: Set(serde_json::to_string(&envelope)?),
+            headers: Set(headers),
+            status: Set(Status::PENDING),
+            attempts: Set(0),
+            last_error: Set(None),
+            available_at: Set(now),
+            created_at: Set(now),
+            published_at: Set(None),
+            ..Default::default()
+        };
+        event.insert(conn).await?;
+        Ok(())
+    }
+
+    async fn try_lock_relay(conn: &DatabaseTransaction) -> AppResult<bool> {
+        let row = conn
+            .query_one_raw(Statement::from_sql_and_values(
+                conn.get_database_backend(),
+                "SELECT pg_try_advisory_xact_lock($1) AS locked",
+                [OUTBOX_RELAY_LOCK_KEY.into()],
+            ))
+            .await?;
+        Ok(row.map(|row| row.try_get::<bool>("", "locked")).transpose()?.unwrap_or(false))
+    }
+
+    async fn find_pending_events(conn: &DatabaseTransaction, now: NaiveDateTime, limit: u64) -> AppResult<Vec<Model>> {
+        // Rows still backing off would otherwise fill the batch and starve every other key
+        let no_earlier_backing_off = Expr::cust_with_values(
+            "NOT EXISTS (SELECT 1 FROM outbox_events AS earlier \
+             WHERE earlier.aggregate_key = outbox_events.aggregate_key AND earlier.id < outbox_events.id \
+             AND earlier.status = $1 AND earlier.available_at > $2)",
+            [sea_orm::Value::from(Status::PENDING.to_value()), sea_orm::Value::from(now)],
+        );
+        let events = Entity::find()
+            .filter(Column::Status.eq(Status::PENDING))
+            .filter(Column::AvailableAt.lte(now))
+            .filter(no_earlier_backing_off)
+            .order_by_asc(Column::Id)
+            .limit(limit)
+            .all(conn)
+            .await?;
+        Ok(events)
+    }
+
+    async fn mark_event_published(conn: &DatabaseTransaction, id: i64) -> AppResult<()> {
+        Entity::update_many()
+            .col_expr(Column::Status, Status::PUBLISHED.into())
+            .col_expr(Column::PublishedAt, chrono::Utc::now().naive_utc().into())
+            .filter(Column::Id.eq(id))
+            .exec(conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_event_failed(
+        conn: &DatabaseTransaction,
+        event: Model,
+        error: String,
+        retry_at: Option<NaiveDateTime>,
+    ) -> AppResult<()> {
+        let attempts = event.attempts + 1;
+        let mut event: ActiveModel = event.into();
+        event.attempts = Set(attempts);
+        event.last_error = Set(Some(error));
+        match retry_at {
+            Some(retry_at) => event.available_at = Set(retry_at),
+            None => event.status = Set(Status::FAILED),
+        }
+        event.update(conn).await?;
+        Ok(())
+    }
+
+    async fn delete_published_events_before(conn: &DatabaseTransaction, cutoff: NaiveDateTime) -> AppResult<u64> {
+        let result = Entity::delete_many()
+            .filter(Column::Status.eq(Status::PUBLISHED))
+            .filter(Column::PublishedAt.lt(cutoff))
+            .exec(conn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
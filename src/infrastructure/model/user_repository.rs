@@ -7,15 +7,9 @@ use crate::domain::{address, user};
 
 #[async_trait]
 impl UserRepositoryInterface for user::user::Entity {
-    async fn create_user(conn: &DatabaseTransaction, model: ActiveModelEx) -> AppResult<bool> {
-        // Convert Model to ActiveModel in infrastructure layer
-
-
-        let user = model.insert(conn).await.map_err(
-            |e| e,
-        )?;
-
-        Ok(true)
+    async fn create_user(conn: &DatabaseTransaction, model: ActiveModelEx) -> AppResult<ModelEx> {
+        let user = model.insert(conn).await?;
+        Ok(user)
     }
 
     async fn update_user(conn: &DatabaseTransaction, model: ActiveModelEx) -> AppResult<bool> {
@@ -128,4 +122,14 @@ impl UserRepositoryInterface for user::user::Entity {
             .await?;
         Ok(users)
     }
+
+    async fn find_users_by_ids(conn: &DatabaseTransaction, ids: &[i64]) -> AppResult<Vec<Model>> {
+        use sea_orm::EntityTrait;
+        let users = user::user::Entity::find()
+            .filter(user::user::Column::Id.is_in(ids.iter().copied()))
+            .filter(user::user::Column::IsDeleted.eq(false))
+            .all(conn)
+            .await?;
+        Ok(users)
+    }
 }
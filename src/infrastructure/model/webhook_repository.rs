@@ -0,0 +1,218 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sea_orm::sea_query::{Expr, Query};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseTransaction, EntityTrait, ExprTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set, Statement,
+};
+use crate::domain::webhook::webhook_delivery;
+use crate::domain::webhook::webhook_repository_interface::{
+    WebhookDeliveryRepositoryInterface, WebhookSubscriptionRepositoryInterface,
+};
+use crate::domain::webhook::webhook_subscription;
+use crate::infrastructure::error::AppResult;
+
+/// Advisory lock key held by the dispatcher sending webhooks, so only one sends at a time
+const WEBHOOK_DISPATCHER_LOCK_KEY: i64 = 0x0077_6562_686f_6f6b;
+
+#[async_trait]
+impl WebhookSubscriptionRepositoryInterface for webhook_subscription::Entity {
+    async fn create_subscription(
+        conn: &DatabaseTransaction,
+        model: webhook_subscription::ActiveModel,
+    ) -> AppResult<webhook_subscription::Model> {
+        Ok(model.insert(conn).await?)
+    }
+
+    async fn update_subscription(
+        conn: &DatabaseTransaction,
+        model: webhook_subscription::ActiveModel,
+    ) -> AppResult<webhook_subscription::Model> {
+        Ok(model.update(conn).await?)
+    }
+
+    async fn find_subscription_by_id(
+        conn: &DatabaseTransaction,
+        id: i64,
+    ) -> AppResult<Option<webhook_subscription::Model>> {
+        Ok(webhook_subscription::Entity::find_by_id(id).one(conn).await?)
+    }
+
+    async fn find_subscriptions(
+        conn: &DatabaseTransaction,
+        owner: Option<&str>,
+    ) -> AppResult<Vec<webhook_subscription::Model>> {
+        let mut query = webhook_subscription::Entity::find().order_by_asc(webhook_subscription::Column::Id);
+        if let Some(owner) = owner {
+            query = query.filter(webhook_subscription::Column::Owner.eq(owner));
+        }
+        Ok(query.all(conn).await?)
+    }
+
+    async fn find_active_subscriptions(conn: &DatabaseTransaction) -> AppResult<Vec<webhook_subscription::Model>> {
+        let subscriptions = webhook_subscription::Entity::find()
+            .filter(webhook_subscription::Column::Status.eq(webhook_subscription::Status::ACTIVE))
+            .all(conn)
+            .await?;
+        Ok(subscriptions)
+    }
+
+    async fn delete_subscription(conn: &DatabaseTransaction, id: i64) -> AppResult<bool> {
+        let result = webhook_subscription::Entity::delete_by_id(id).exec(conn).await?;
+        Ok(result.rows_affected == 1)
+    }
+
+    async fn reset_subscription_failures(conn: &DatabaseTransaction, id: i64) -> AppResult<()> {
+        webhook_subscription::Entity::update_many()
+            .col_expr(webhook_subscription::Column::ConsecutiveFailures, 0.into())
+            .filter(webhook_subscription::Column::Id.eq(id))
+            .filter(webhook_subscription::Column::ConsecutiveFailures.ne(0))
+            .exec(conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn record_subscription_failure(
+        conn: &DatabaseTransaction,
+        id: i64,
+        disable_after: i32,
+    ) -> AppResult<bool> {
+        webhook_subscription::Entity::update_many()
+            .col_expr(
+                webhook_subscription::Column::ConsecutiveFailures,
+                Expr::col(webhook_subscription::Column::ConsecutiveFailures).add(1),
+            )
+            .filter(webhook_subscription::Column::Id.eq(id))
+            .exec(conn)
+            .await?;
+
+        let disabled = webhook_subscription::Entity::update_many()
+            .col_expr(webhook_subscription::Column::Status, webhook_subscription::Status::DISABLED.into())
+            .col_expr(
+                webhook_subscription::Column::DisabledReason,
+                Some(format!("Disabled after {} consecutive failed deliveries", disable_after)).into(),
+            )
+            .col_expr(webhook_subscription::Column::UpdatedAt, chrono::Utc::now().naive_utc().into())
+            .filter(webhook_subscription::Column::Id.eq(id))
+            .filter(webhook_subscription::Column::Status.eq(webhook_subscription::Status::ACTIVE))
+            .filter(webhook_subscription::Column::ConsecutiveFailures.gte(disable_after))
+            .exec(conn)
+            .await?;
+        Ok(disabled.rows_affected == 1)
+    }
+}
+
+#[async_trait]
+impl WebhookDeliveryRepositoryInterface for webhook_delivery::Entity {
+    async fn create_delivery(
+        conn: &DatabaseTransaction,
+        model: webhook_delivery::ActiveModel,
+    ) -> AppResult<webhook_delivery::Model> {
+        Ok(model.insert(conn).await?)
+    }
+
+    async fn try_lock_dispatcher(conn: &DatabaseTransaction) -> AppResult<bool> {
+        let row = conn
+            .query_one_raw(Statement::from_sql_and_values(
+                conn.get_database_backend(),
+                "SELECT pg_try_advisory_xact_lock($1) AS locked",
+                [WEBHOOK_DISPATCHER_LOCK_KEY.into()],
+            ))
+            .await?;
+        Ok(row.map(|row| row.try_get::<bool>("", "locked")).transpose()?.unwrap_or(false))
+    }
+
+    async fn find_due_deliveries(
+        conn: &DatabaseTransaction,
+        now: NaiveDateTime,
+        limit: u64,
+    ) -> AppResult<Vec<webhook_delivery::Model>> {
+        let active_subscriptions = Query::select()
+            .column(webhook_subscription::Column::Id)
+            .from(webhook_subscription::Entity)
+            .and_where(webhook_subscription::Column::Status.eq(webhook_subscription::Status::ACTIVE))
+            .to_owned();
+        let deliveries = webhook_delivery::Entity::find()
+            .filter(webhook_delivery::Column::Status.eq(webhook_delivery::Status::PENDING))
+            .filter(webhook_delivery::Column::NextAttemptAt.lte(now))
+            .filter(webhook_delivery::Column::SubscriptionId.in_subquery(active_subscriptions))
+            .order_by_asc(webhook_delivery::Column::Id)
+            .limit(limit)
+            .all(conn)
+            .await?;
+        Ok(deliveries)
+    }
+
+    async fn find_delivery_by_id(conn: &DatabaseTransaction, id: i64) -> AppResult<Option<webhook_delivery::Model>> {
+        Ok(webhook_delivery::Entity::find_by_id(id).one(conn).await?)
+    }
+
+    async fn find_deliveries_by_subscription(
+        conn: &DatabaseTransaction,
+        subscription_id: i64,
+        status: Option<webhook_delivery::Status>,
+        limit: u64,
+    ) -> AppResult<Vec<webhook_delivery::Model>> {
+        let mut query = webhook_delivery::Entity::find()
+            .filter(webhook_delivery::Column::SubscriptionId.eq(subscription_id))
+            .order_by_desc(webhook_delivery::Column::Id)
+            .limit(limit);
+        if let Some(status) = status {
+            query = query.filter(webhook_delivery::Column::Status.eq(status));
+        }
+        Ok(query.all(conn).await?)
+    }
+
+    async fn mark_delivery_delivered(conn: &DatabaseTransaction, id: i64, response_status: i32) -> AppResult<()> {
+        webhook_delivery::Entity::update_many()
+            .col_expr(webhook_delivery::Column::Status, webhook_delivery::Status::DELIVERED.into())
+            .col_expr(webhook_delivery::Column::Attempts, Expr::col(webhook_delivery::Column::Attempts).add(1))
+            .col_expr(webhook_delivery::Column::ResponseStatus, Some(response_status).into())
+            .col_expr(webhook_delivery::Column::DeliveredAt, Some(chrono::Utc::now().naive_utc()).into())
+            .filter(webhook_delivery::Column::Id.eq(id))
+            .exec(conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_delivery_failed(
+        conn: &DatabaseTransaction,
+        delivery: webhook_delivery::Model,
+        response_status: Option<i32>,
+        error: String,
+        retry_at: Option<NaiveDateTime>,
+    ) -> AppResult<()> {
+        let attempts = delivery.attempts + 1;
+        let mut delivery: webhook_delivery::ActiveModel = delivery.into();
+        delivery.attempts = Set(attempts);
+        delivery.response_status = Set(response_status);
+        delivery.last_error = Set(Some(error));
+        match retry_at {
+            Some(retry_at) => delivery.next_attempt_at = Set(retry_at),
+            None => delivery.status = Set(webhook_delivery::Status::FAILED),
+        }
+        delivery.update(conn).await?;
+        Ok(())
+    }
+
+    async fn requeue_delivery(conn: &DatabaseTransaction, id: i64) -> AppResult<()> {
+        webhook_delivery::Entity::update_many()
+            .col_expr(webhook_delivery::Column::Status, webhook_delivery::Status::PENDING.into())
+            .col_expr(webhook_delivery::Column::Attempts, 0.into())
+            .col_expr(webhook_delivery::Column::NextAttemptAt, chrono::Utc::now().naive_utc().into())
+            .col_expr(webhook_delivery::Column::DeliveredAt, Option::<NaiveDateTime>::None.into())
+            .filter(webhook_delivery::Column::Id.eq(id))
+            .exec(conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_deliveries_before(conn: &DatabaseTransaction, cutoff: NaiveDateTime) -> AppResult<u64> {
+        let result = webhook_delivery::Entity::delete_many()
+            .filter(webhook_delivery::Column::Status.ne(webhook_delivery::Status::PENDING))
+            .filter(webhook_delivery::Column::CreatedAt.lt(cutoff))
+            .exec(conn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
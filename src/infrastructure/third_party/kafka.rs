@@ -0,0 +1,63 @@
+use rdkafka::message::{Header, OwnedHeaders};
+use serde_json::{Map, Value};
+use tracing::Span;
+use utils::request_id::{current_request_id, REQUEST_ID_HEADER};
+use utils::telemetry::trace_context_pairs;
+
+/// Headers attached to every produced record so consumers can correlate it with the originating request
+///
+/// Carries the request ID and the W3C trace context of the current span.
+pub fn record_headers() -> OwnedHeaders {
+    headers_from_pairs(context_pairs())
+}
+
+/// Same headers as `record_headers`, as a JSON object stored with an outbox event
+pub fn context_headers_json() -> Value {
+    Value::Object(
+        context_pairs()
+            .into_iter()
+            .map(|(key, value)| (key, Value::String(value)))
+            .collect::<Map<String, Value>>(),
+    )
+}
+
+/// Header pairs from a JSON object written by `context_headers_json`
+pub fn header_pairs_from_json(headers: &Value) -> Vec<(String, String)> {
+    headers
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| value.as_str().map(|value| (key.clone(), value.to_string())))
+        .collect()
+}
+
+fn context_pairs() -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    if let Some(request_id) = current_request_id() {
+        pairs.push((REQUEST_ID_HEADER.to_string(), request_id));
+    }
+    pairs.extend(trace_context_pairs());
+    pairs
+}
+
+fn headers_from_pairs(pairs: Vec<(String, String)>) -> OwnedHeaders {
+    let mut headers = OwnedHeaders::new();
+    for (key, value) in &pairs {
+        headers = headers.insert(Header {
+            key: key.as_str(),
+            value: Some(value.as_str()),
+        });
+    }
+    headers
+}
+
+/// Producer span for a record sent to `topic`, build the record headers inside it
+pub fn produce_span(topic: &str) -> Span {
+    tracing::info_span!(
+        "kafka.produce",
+        otel.name = %format!("{} publish", topic),
+        otel.kind = "producer",
+        messaging.system = "kafka",
+        messaging.destination.name = %topic,
+    )
+}
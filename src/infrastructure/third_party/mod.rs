@@ -1,3 +1,7 @@
+pub mod kafka;
+pub mod outbox_relay;
 pub mod token;
+pub mod webhook;
+pub mod webhook_dispatcher;
 
 // Redis module moved to infrastructure::persistence::redis_client
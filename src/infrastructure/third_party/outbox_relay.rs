@@ -0,0 +1,200 @@
+use crate::domain::outbox::outbox;
+use crate::domain::outbox::outbox_repository_interface::OutboxRepositoryInterface;
+use crate::infrastructure::constant::{
+    OUTBOX_BATCH_SIZE, OUTBOX_CLEANUP_INTERVAL, OUTBOX_MAX_ATTEMPTS, OUTBOX_MAX_BACKOFF, OUTBOX_POLL_INTERVAL,
+    OUTBOX_RETENTION,
+};
+use crate::infrastructure::error::AppResult;
+use crate::infrastructure::persistence::postgres::DatabaseClient;
+use crate::infrastructure::third_party::kafka::{header_pairs_from_json, produce_span};
+use chrono::NaiveDateTime;
+use sea_orm::TransactionTrait;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+use utils::events::publisher::{EventPublisher, OutgoingEvent};
+use utils::request_id::{with_optional_request_id, REQUEST_ID_HEADER};
+
+/// Publishes pending outbox events through the configured `EventPublisher`
+///
+/// Events are published in insertion order per aggregate key: while an event is waiting
+/// to be retried, later events with the same key wait too. Delivery is at least once, an
+/// event is published again if its row could not be marked afterwards. Only one relay
+/// publishes at a time across gateway instances.
+pub struct OutboxRelay {
+    db: Arc<DatabaseClient>,
+    publisher: Arc<dyn EventPublisher>,
+}
+
+impl OutboxRelay {
+    pub fn new(db: Arc<DatabaseClient>, publisher: Arc<dyn EventPublisher>) -> Self {
+        Self { db, publisher }
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(OUTBOX_POLL_INTERVAL);
+        let mut last_cleanup = Instant::now();
+
+        loop {
+            interval.tick().await;
+
+            match self.relay_batch().await {
+                // A full batch means more are probably waiting, don't sleep
+                Ok(published) if published as u64 >= OUTBOX_BATCH_SIZE => interval.reset_immediately(),
+                Ok(_) => {},
+                Err(e) => log::error!("Outbox relay failed: {}", e),
+            }
+
+            if last_cleanup.elapsed() >= OUTBOX_CLEANUP_INTERVAL {
+                last_cleanup = Instant::now();
+                if let Err(e) = self.cleanup().await {
+                    log::error!("Outbox cleanup failed: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Publish one batch of pending events, returning how many were published
+    async fn relay_batch(&self) -> AppResult<usize> {
+        let tx = self.db.begin().await?;
+        if !outbox::Entity::try_lock_relay(&tx).await? {
+            return Ok(0);
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        let events = outbox::Entity::find_pending_events(&tx, now, OUTBOX_BATCH_SIZE).await?;
+        // Keys whose event failed in this batch, their later events wait for the retry
+        let mut blocked_keys = HashSet::new();
+        let mut published = 0;
+
+        for event in events {
+            if blocked_keys.contains(&event.aggregate_key) {
+                continue;
+            }
+
+            let request_id = request_id_of(&event);
+            let span = tracing::info_span!(
+                "outbox.relay",
+                outbox_id = event.id,
+                request_id = %request_id.as_deref().unwrap_or_default(),
+            );
+            let relay_event = async {
+                match self.publish(&event).await {
+                    Ok(()) => {
+                        outbox::Entity::mark_event_published(&tx, event.id).await?;
+                        published += 1;
+                    },
+                    Err(error) => {
+                        let retry_at = retry_at(now, event.attempts + 1);
+                        match retry_at {
+                            Some(_) => {
+                                log::warn!("Failed to publish outbox event {} to {}: {}", event.id, event.topic, error);
+                                blocked_keys.insert(event.aggregate_key.clone());
+                            },
+                            None => log::error!(
+                                "Giving up on outbox event {} to {} after {} attempts: {}",
+                                event.id, event.topic, OUTBOX_MAX_ATTEMPTS, error
+                            ),
+                        }
+                        outbox::Entity::mark_event_failed(&tx, event, error, retry_at).await?;
+                    },
+                }
+                AppResult::Ok(())
+            };
+            // Logs and anything the publisher spawns correlate with the request that wrote the event
+            with_optional_request_id(request_id, relay_event.instrument(span)).await?;
+        }
+
+        tx.commit().await?;
+        Ok(published)
+    }
+
+    async fn publish(&self, event: &outbox::Model) -> Result<(), String> {
+        let span = produce_span(&event.topic);
+        let outgoing = OutgoingEvent {
+            topic: event.topic.clone(),
+            key: event.aggregate_key.clone(),
+            payload: event.payload.clone(),
+            headers: header_pairs_from_json(&event.headers),
+        };
+        self.publisher.publish(&outgoing).instrument(span).await
+    }
+
+    async fn cleanup(&self) -> AppResult<()> {
+        let cutoff = chrono::Utc::now().naive_utc()
+            - chrono::Duration::from_std(OUTBOX_RETENTION).unwrap_or_default();
+        let tx = self.db.begin().await?;
+        let deleted = outbox::Entity::delete_published_events_before(&tx, cutoff).await?;
+        tx.commit().await?;
+        if deleted > 0 {
+            log::info!("Deleted {} published outbox events", deleted);
+        }
+        Ok(())
+    }
+}
+
+/// Request ID captured in the event's headers when it was written
+fn request_id_of(event: &outbox::Model) -> Option<String> {
+    header_pairs_from_json(&event.headers)
+        .into_iter()
+        .find(|(key, _)| key == REQUEST_ID_HEADER)
+        .map(|(_, value)| value)
+}
+
+/// When to retry after `attempts` failures, exponential up to a cap; `None` to give up
+fn retry_at(now: NaiveDateTime, attempts: i32) -> Option<NaiveDateTime> {
+    if attempts >= OUTBOX_MAX_ATTEMPTS {
+        return None;
+    }
+    let backoff = Duration::from_secs(1u64 << attempts.clamp(0, 16)).min(OUTBOX_MAX_BACKOFF);
+    Some(now + chrono::Duration::from_std(backoff).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Value};
+    use std::collections::BTreeMap;
+    use utils::events::publisher::InMemoryEventPublisher;
+
+    fn event(id: i64, key: &str) -> outbox::Model {
+        let now = chrono::Utc::now().naive_utc();
+        outbox::Model {
+            id,
+            topic: "user.events".to_string(),
+            aggregate_key: key.to_string(),
+            payload: "{}".to_string(),
+            headers: serde_json::json!({}),
+            status: outbox::Status::PENDING,
+            attempts: 0,
+            last_error: None,
+            available_at: now,
+            created_at: now,
+            published_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn backed_off_events_do_not_hold_back_other_keys() {
+        let locked = BTreeMap::from([("locked", Value::from(true))]);
+        // What the query returns once rows of key `a` are backing off
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![locked]])
+            .append_query_results([vec![event(7, "b")]])
+            .append_exec_results([MockExecResult { last_insert_id: 0, rows_affected: 1 }])
+            .into_connection();
+        let db = Arc::new(db);
+        let publisher = Arc::new(InMemoryEventPublisher::new());
+
+        let relay = OutboxRelay::new(db.clone(), publisher.clone());
+        assert_eq!(relay.relay_batch().await.unwrap(), 1);
+        drop(relay);
+
+        assert_eq!(publisher.published()[0].key, "b");
+        let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+        let sql = format!("{:?}", log).replace(r#"\""#, r#"""#);
+        assert!(sql.contains(r#""outbox_events"."available_at" <= $"#), "{}", sql);
+        assert!(sql.contains("earlier.available_at > $"), "{}", sql);
+    }
+}
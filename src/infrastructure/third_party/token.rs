@@ -12,11 +12,12 @@ pub fn service_generate_tokens(
     session_id: &Uuid,
     user_info: &UserInfo,
 ) -> AppResult<TokenResponse> {
+    let roles = std::slice::from_ref(&user_info.role);
     let access_token =
-        UserClaims::new(EXPIRE_BEARER_TOKEN_SECS, user_id, session_id)
+        UserClaims::new(EXPIRE_BEARER_TOKEN_SECS, user_id, session_id, roles)
             .encode(&ACCESS_TOKEN_ENCODE_KEY)?;
     let refresh_token =
-        UserClaims::new(EXPIRE_REFRESH_TOKEN_SECS, user_id, session_id)
+        UserClaims::new(EXPIRE_REFRESH_TOKEN_SECS, user_id, session_id, roles)
             .encode(&REFRESH_TOKEN_ENCODE_KEY)?;
     Ok(TokenResponse::new(access_token, refresh_token, EXPIRE_BEARER_TOKEN_SECS.as_secs(), user_info.clone()))
 }
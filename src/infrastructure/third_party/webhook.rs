@@ -0,0 +1,103 @@
+use crate::infrastructure::constant::SERVICE_NAME;
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::redirect::Policy;
+use reqwest::Client;
+use sha2::Sha256;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// ID of the delivered event, the same for every attempt so receivers can drop repeats
+pub const WEBHOOK_ID_HEADER: &str = "X-Webhook-Id";
+pub const WEBHOOK_EVENT_HEADER: &str = "X-Webhook-Event";
+/// Unix time the request was signed at, receivers should reject stale ones
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+const SIGNATURE_PREFIX: &str = "sha256=";
+/// Response bodies quoted in a delivery's error are cut to this many characters
+const MAX_ERROR_BODY_LEN: usize = 500;
+
+/// `sha256=` followed by the hex HMAC-SHA256 of `{timestamp}.{body}`, keyed with the
+/// subscription's secret
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let mac = signed_content_mac(secret, timestamp, body);
+    format!("{}{}", SIGNATURE_PREFIX, hex::encode(mac.finalize().into_bytes()))
+}
+
+/// What a receiver does with `X-Webhook-Signature`, in constant time
+pub fn verify_signature(secret: &str, timestamp: i64, body: &str, signature: &str) -> bool {
+    let Some(signature) = signature.strip_prefix(SIGNATURE_PREFIX).and_then(|hex| hex::decode(hex).ok()) else {
+        return false;
+    };
+    signed_content_mac(secret, timestamp, body).verify_slice(&signature).is_ok()
+}
+
+fn signed_content_mac(secret: &str, timestamp: i64, body: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    mac
+}
+
+/// One signed POST of an event to a subscription's URL
+pub struct WebhookRequest<'a> {
+    pub url: &'a str,
+    pub secret: &'a str,
+    pub event_id: Uuid,
+    pub event_type: &'a str,
+    /// The event's envelope, sent as is
+    pub payload: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeliveryOutcome {
+    /// The receiver answered with a 2xx status
+    Delivered { status: u16 },
+    /// Any other status, or no response at all
+    Failed { status: Option<u16>, error: String },
+}
+
+/// Sends webhook requests, without following redirects
+pub struct WebhookSender {
+    client: Client,
+}
+
+impl WebhookSender {
+    pub fn new(timeout: Duration) -> Self {
+        let client = Client::builder()
+            .timeout(timeout)
+            .redirect(Policy::none())
+            .user_agent(format!("{}-webhooks", SERVICE_NAME))
+            .build()
+            .expect("webhook HTTP client");
+        Self { client }
+    }
+
+    pub async fn send(&self, request: &WebhookRequest<'_>) -> DeliveryOutcome {
+        let timestamp = chrono::Utc::now().timestamp();
+        let response = self
+            .client
+            .post(request.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_ID_HEADER, request.event_id.to_string())
+            .header(WEBHOOK_EVENT_HEADER, request.event_type)
+            .header(WEBHOOK_TIMESTAMP_HEADER, timestamp.to_string())
+            .header(WEBHOOK_SIGNATURE_HEADER, sign_payload(request.secret, timestamp, request.payload))
+            .body(request.payload.to_string())
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => {
+                DeliveryOutcome::Delivered { status: response.status().as_u16() }
+            },
+            Ok(response) => {
+                let status = response.status();
+                let body: String = response.text().await.unwrap_or_default().chars().take(MAX_ERROR_BODY_LEN).collect();
+                DeliveryOutcome::Failed { status: Some(status.as_u16()), error: format!("HTTP {}: {}", status, body) }
+            },
+            Err(e) => DeliveryOutcome::Failed { status: None, error: e.to_string() },
+        }
+    }
+}
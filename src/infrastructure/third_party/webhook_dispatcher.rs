@@ -0,0 +1,204 @@
+use crate::core::configure::webhook::WebhookConfig;
+use crate::domain::webhook::webhook_repository_interface::{
+    WebhookDeliveryRepositoryInterface, WebhookSubscriptionRepositoryInterface,
+};
+use crate::domain::webhook::{webhook_delivery, webhook_subscription};
+use crate::infrastructure::constant::{
+    WEBHOOK_BASE_BACKOFF, WEBHOOK_BATCH_SIZE, WEBHOOK_CLEANUP_INTERVAL, WEBHOOK_DELIVERY_RETENTION,
+    WEBHOOK_MAX_BACKOFF, WEBHOOK_POLL_INTERVAL,
+};
+use crate::infrastructure::error::AppResult;
+use crate::infrastructure::persistence::postgres::DatabaseClient;
+use crate::infrastructure::third_party::webhook::{DeliveryOutcome, WebhookRequest, WebhookSender};
+use chrono::NaiveDateTime;
+use sea_orm::TransactionTrait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+use utils::request_id::with_optional_request_id;
+
+/// Sends pending webhook deliveries to their subscriptions
+///
+/// A batch is sent concurrently. A failed attempt is retried with exponential backoff
+/// until `max_attempts`, and counts against the subscription, which is disabled after
+/// `disable_after_failures` failures in a row; its pending deliveries wait until an admin
+/// enables it again. Only one dispatcher sends at a time across gateway instances.
+pub struct WebhookDispatcher {
+    db: Arc<DatabaseClient>,
+    sender: WebhookSender,
+    config: WebhookConfig,
+}
+
+impl WebhookDispatcher {
+    pub fn new(db: Arc<DatabaseClient>, config: WebhookConfig) -> Self {
+        let sender = WebhookSender::new(Duration::from_millis(config.timeout_ms));
+        Self { db, sender, config }
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(WEBHOOK_POLL_INTERVAL);
+        let mut last_cleanup = Instant::now();
+
+        loop {
+            interval.tick().await;
+
+            match self.dispatch_batch().await {
+                // A full batch means more are probably waiting, don't sleep
+                Ok(sent) if sent as u64 >= WEBHOOK_BATCH_SIZE => interval.reset_immediately(),
+                Ok(_) => {},
+                Err(e) => log::error!("Webhook dispatcher failed: {}", e),
+            }
+
+            if last_cleanup.elapsed() >= WEBHOOK_CLEANUP_INTERVAL {
+                last_cleanup = Instant::now();
+                if let Err(e) = self.cleanup().await {
+                    log::error!("Webhook delivery cleanup failed: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Send one batch of due deliveries, returning how many were attempted
+    async fn dispatch_batch(&self) -> AppResult<usize> {
+        let tx = self.db.begin().await?;
+        if !webhook_delivery::Entity::try_lock_dispatcher(&tx).await? {
+            return Ok(0);
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        let deliveries = webhook_delivery::Entity::find_due_deliveries(&tx, now, WEBHOOK_BATCH_SIZE).await?;
+        let mut subscriptions = HashMap::new();
+        for delivery in &deliveries {
+            if !subscriptions.contains_key(&delivery.subscription_id) {
+                if let Some(subscription) =
+                    webhook_subscription::Entity::find_subscription_by_id(&tx, delivery.subscription_id).await?
+                {
+                    subscriptions.insert(subscription.id, subscription);
+                }
+            }
+        }
+
+        let sends = deliveries.iter().filter_map(|delivery| {
+            let subscription = subscriptions.get(&delivery.subscription_id)?;
+            Some(async move { (delivery.id, self.send(subscription, delivery).await) })
+        });
+        let mut outcomes: HashMap<i64, DeliveryOutcome> = futures::future::join_all(sends).await.into_iter().collect();
+
+        let attempted = outcomes.len();
+        for delivery in deliveries {
+            let Some(outcome) = outcomes.remove(&delivery.id) else {
+                continue;
+            };
+            let span = delivery_span(&delivery);
+            self.record(&tx, delivery, outcome, now).instrument(span).await?;
+        }
+
+        tx.commit().await?;
+        Ok(attempted)
+    }
+
+    async fn send(&self, subscription: &webhook_subscription::Model, delivery: &webhook_delivery::Model) -> DeliveryOutcome {
+        let request = WebhookRequest {
+            url: &subscription.url,
+            secret: &subscription.secret,
+            event_id: delivery.event_id,
+            event_type: &delivery.event_type,
+            payload: &delivery.payload,
+        };
+        // Correlate the attempt with the request that caused the event
+        let send = self.sender.send(&request).instrument(delivery_span(delivery));
+        with_optional_request_id(request_id_of(delivery), send).await
+    }
+
+    async fn record(
+        &self,
+        tx: &sea_orm::DatabaseTransaction,
+        delivery: webhook_delivery::Model,
+        outcome: DeliveryOutcome,
+        now: NaiveDateTime,
+    ) -> AppResult<()> {
+        let subscription_id = delivery.subscription_id;
+        match outcome {
+            DeliveryOutcome::Delivered { status } => {
+                webhook_delivery::Entity::mark_delivery_delivered(tx, delivery.id, status as i32).await?;
+                webhook_subscription::Entity::reset_subscription_failures(tx, subscription_id).await?;
+            },
+            DeliveryOutcome::Failed { status, error } => {
+                let retry_at = retry_at(now, delivery.attempts + 1, self.config.max_attempts);
+                match retry_at {
+                    Some(_) => log::warn!(
+                        "Webhook delivery {} to subscription {} failed: {}",
+                        delivery.id, subscription_id, error
+                    ),
+                    None => log::error!(
+                        "Giving up on webhook delivery {} to subscription {} after {} attempts: {}",
+                        delivery.id, subscription_id, self.config.max_attempts, error
+                    ),
+                }
+                webhook_delivery::Entity::mark_delivery_failed(tx, delivery, status.map(i32::from), error, retry_at)
+                    .await?;
+
+                let disabled = webhook_subscription::Entity::record_subscription_failure(
+                    tx,
+                    subscription_id,
+                    self.config.disable_after_failures,
+                )
+                .await?;
+                if disabled {
+                    log::warn!(
+                        "Disabled webhook subscription {} after {} consecutive failures",
+                        subscription_id, self.config.disable_after_failures
+                    );
+                }
+            },
+        }
+        Ok(())
+    }
+
+    async fn cleanup(&self) -> AppResult<()> {
+        let cutoff = chrono::Utc::now().naive_utc()
+            - chrono::Duration::from_std(WEBHOOK_DELIVERY_RETENTION).unwrap_or_default();
+        let tx = self.db.begin().await?;
+        let deleted = webhook_delivery::Entity::delete_deliveries_before(&tx, cutoff).await?;
+        tx.commit().await?;
+        if deleted > 0 {
+            log::info!("Deleted {} webhook deliveries", deleted);
+        }
+        Ok(())
+    }
+}
+
+/// When to retry after `attempts` failed attempts, doubling from `WEBHOOK_BASE_BACKOFF`
+/// up to a cap; `None` to give up
+pub fn retry_at(now: NaiveDateTime, attempts: i32, max_attempts: i32) -> Option<NaiveDateTime> {
+    if attempts >= max_attempts {
+        return None;
+    }
+    let backoff = WEBHOOK_BASE_BACKOFF
+        .saturating_mul(1u32 << (attempts - 1).clamp(0, 16))
+        .min(WEBHOOK_MAX_BACKOFF);
+    Some(now + chrono::Duration::from_std(backoff).unwrap_or_default())
+}
+
+#[derive(serde::Deserialize)]
+struct EnvelopeCorrelation {
+    correlationid: Option<String>,
+}
+
+/// Request ID of the request that caused the delivered event, from its envelope
+fn request_id_of(delivery: &webhook_delivery::Model) -> Option<String> {
+    serde_json::from_str::<EnvelopeCorrelation>(&delivery.payload)
+        .ok()
+        .and_then(|envelope| envelope.correlationid)
+}
+
+/// Span for one delivery, carrying the request ID of the event it sends
+fn delivery_span(delivery: &webhook_delivery::Model) -> tracing::Span {
+    tracing::info_span!(
+        "webhook.deliver",
+        delivery_id = delivery.id,
+        subscription_id = delivery.subscription_id,
+        request_id = %request_id_of(delivery).unwrap_or_default(),
+    )
+}
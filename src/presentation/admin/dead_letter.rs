@@ -0,0 +1,11 @@
+use serde::Serialize;
+use utils::kafka_consumer::DeadLetter;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeadLetterSerializer {
+    #[serde(flatten)]
+    pub letter: DeadLetter,
+    /// `replayed` or `discarded` once an admin dealt with it
+    pub resolution: Option<String>,
+}
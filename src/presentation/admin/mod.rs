@@ -0,0 +1,2 @@
+pub mod dead_letter;
+pub mod webhook;
@@ -0,0 +1,94 @@
+use crate::domain::webhook::webhook_delivery::{Model as DeliveryModel, Status as DeliveryStatus};
+use crate::domain::webhook::webhook_subscription::{Model as SubscriptionModel, Status as SubscriptionStatus};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct WebhookSubscriptionSerializer {
+    pub id: i64,
+    pub owner: String,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub status: SubscriptionStatus,
+    pub consecutive_failures: i32,
+    pub disabled_reason: Option<String>,
+    /// Only returned when the subscription is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<SubscriptionModel> for WebhookSubscriptionSerializer {
+    fn from(value: SubscriptionModel) -> Self {
+        WebhookSubscriptionSerializer {
+            event_types: value.event_type_patterns(),
+            id: value.id,
+            owner: value.owner,
+            url: value.url,
+            status: value.status,
+            consecutive_failures: value.consecutive_failures,
+            disabled_reason: value.disabled_reason,
+            secret: None,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct WebhookDeliverySerializer {
+    pub id: i64,
+    pub subscription_id: i64,
+    pub event_id: Uuid,
+    pub event_type: String,
+    /// The event's envelope as sent
+    pub payload: String,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    pub delivered_at: Option<NaiveDateTime>,
+}
+
+impl From<DeliveryModel> for WebhookDeliverySerializer {
+    fn from(value: DeliveryModel) -> Self {
+        WebhookDeliverySerializer {
+            id: value.id,
+            subscription_id: value.subscription_id,
+            event_id: value.event_id,
+            event_type: value.event_type,
+            payload: value.payload,
+            status: value.status,
+            attempts: value.attempts,
+            response_status: value.response_status,
+            last_error: value.last_error,
+            next_attempt_at: value.next_attempt_at,
+            created_at: value.created_at,
+            delivered_at: value.delivered_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Clone)]
+pub struct CreateWebhookSubscriptionRequest {
+    /// Tenant or partner the subscription belongs to
+    pub owner: String,
+    pub url: String,
+    /// Event type patterns, e.g. `user.created`, `user.*` or `*`
+    pub event_types: Vec<String>,
+    /// Signing secret, generated when omitted
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Clone)]
+pub struct UpdateWebhookSubscriptionRequest {
+    pub url: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    /// `true` re-enables a disabled subscription and clears its failure count
+    pub enabled: Option<bool>,
+}
@@ -1,4 +1,5 @@
 pub mod authen;
 pub mod user;
 pub mod address;
+pub mod admin;
 mod common;
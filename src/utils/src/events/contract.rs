@@ -0,0 +1,103 @@
+use crate::events::DomainEvent;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// JSON Schema of an event's payload, as committed under `schemas/events`
+pub fn payload_schema<E: DomainEvent>() -> Value {
+    serde_json::to_value(schemars::schema_for!(E)).expect("JSON schemas serialize")
+}
+
+/// File name of the committed schema of one event version, e.g. `user.registered.v1.json`
+pub fn schema_file_name<E: DomainEvent>() -> String {
+    format!("{}.v{}.json", E::EVENT_TYPE, E::SCHEMA_VERSION)
+}
+
+/// Changes from `old` to `new` that consumers of either schema cannot read
+///
+/// Consumers built against `old` must read new events, and new consumers must read
+/// events published with `old`, e.g. when they are replayed. Adding optional fields is
+/// fine; removing fields, changing types or formats, changing enum values or which
+/// fields are required is not.
+pub fn breaking_changes(old: &Value, new: &Value) -> Vec<String> {
+    let mut comparison = Comparison { old_root: old, new_root: new, changes: Vec::new() };
+    comparison.compare("$", old, new);
+    comparison.changes
+}
+
+struct Comparison<'a> {
+    old_root: &'a Value,
+    new_root: &'a Value,
+    changes: Vec<String>,
+}
+
+impl<'a> Comparison<'a> {
+    fn compare(&mut self, path: &str, old: &'a Value, new: &'a Value) {
+        let old = resolve(self.old_root, old);
+        let new = resolve(self.new_root, new);
+
+        for keyword in ["type", "format", "enum", "const"] {
+            if old.get(keyword) != new.get(keyword) {
+                self.changes.push(format!(
+                    "{}: {} changed from {} to {}",
+                    path,
+                    keyword,
+                    old.get(keyword).unwrap_or(&Value::Null),
+                    new.get(keyword).unwrap_or(&Value::Null)
+                ));
+            }
+        }
+
+        let old_properties = old.get("properties").and_then(Value::as_object);
+        let new_properties = new.get("properties").and_then(Value::as_object);
+        for (name, old_property) in old_properties.into_iter().flatten() {
+            let property_path = format!("{}.{}", path, name);
+            match new_properties.and_then(|properties| properties.get(name)) {
+                Some(new_property) => self.compare(&property_path, old_property, new_property),
+                None => self.changes.push(format!("{}: removed", property_path)),
+            }
+        }
+
+        let old_required = required(old);
+        let new_required = required(new);
+        for name in old_required.difference(&new_required) {
+            self.changes.push(format!("{}.{}: no longer required", path, name));
+        }
+        for name in new_required.difference(&old_required) {
+            self.changes.push(format!("{}.{}: newly required, earlier events lack it", path, name));
+        }
+
+        for keyword in ["anyOf", "oneOf", "allOf"] {
+            match (old.get(keyword).and_then(Value::as_array), new.get(keyword).and_then(Value::as_array)) {
+                (None, None) => {},
+                (Some(old_variants), Some(new_variants)) if old_variants.len() == new_variants.len() => {
+                    for (index, (old_variant, new_variant)) in old_variants.iter().zip(new_variants).enumerate() {
+                        self.compare(&format!("{}.{}[{}]", path, keyword, index), old_variant, new_variant);
+                    }
+                },
+                _ => self.changes.push(format!("{}: {} changed", path, keyword)),
+            }
+        }
+
+        if let (Some(old_items), Some(new_items)) = (old.get("items"), new.get("items")) {
+            self.compare(&format!("{}[]", path), old_items, new_items);
+        }
+    }
+}
+
+/// Follow a local `$ref`, e.g. `#/$defs/DeviceInfoEvent`
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| root.pointer(pointer))
+        .unwrap_or(schema)
+}
+
+fn required(schema: &Value) -> BTreeSet<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,70 @@
+use crate::request_id::current_request_id;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub mod contract;
+pub mod publisher;
+
+/// CloudEvents version the envelope follows
+pub const CLOUDEVENTS_SPEC_VERSION: &str = "1.0";
+const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// Payload of a domain event, published inside an `EventEnvelope`
+///
+/// The payload schema is part of the contract with consumers: changes they cannot read
+/// need a new `SCHEMA_VERSION`, which the contract tests in `tests/event_contracts.rs`
+/// enforce against the schemas committed under `schemas/events`.
+pub trait DomainEvent: Serialize + DeserializeOwned + JsonSchema + Send + Sync {
+    /// CloudEvents `type`, e.g. `user.registered`
+    const EVENT_TYPE: &'static str;
+    const SCHEMA_VERSION: u32;
+    const TOPIC: &'static str;
+
+    /// ID of the entity the event is about, also used as the record key
+    fn aggregate_id(&self) -> String;
+}
+
+/// Metadata common to every domain event, in CloudEvents structured JSON mode
+///
+/// `schemaversion`, `correlationid` and `aggregateid` are CloudEvents extension attributes.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EventEnvelope<T> {
+    pub specversion: String,
+    /// Unique per event, the same when an event is redelivered
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Service that produced the event
+    pub source: String,
+    /// When the event occurred, in UTC
+    pub time: DateTime<Utc>,
+    pub datacontenttype: String,
+    /// Version of the `data` schema
+    pub schemaversion: u32,
+    /// Request ID of the request that caused the event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlationid: Option<String>,
+    pub aggregateid: String,
+    pub data: T,
+}
+
+impl<T: DomainEvent> EventEnvelope<T> {
+    /// Envelope for an event occurring now, correlated with the current request
+    pub fn new(source: &str, data: T) -> Self {
+        Self {
+            specversion: CLOUDEVENTS_SPEC_VERSION.to_string(),
+            id: Uuid::new_v4(),
+            event_type: T::EVENT_TYPE.to_string(),
+            source: source.to_string(),
+            time: Utc::now(),
+            datacontenttype: JSON_CONTENT_TYPE.to_string(),
+            schemaversion: T::SCHEMA_VERSION,
+            correlationid: current_request_id(),
+            aggregateid: data.aggregate_id(),
+            data,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::events::{DomainEvent, EventEnvelope};
+use crate::kafka_consumer::EVENT_TYPE_HEADER;
+use crate::metrics::metrics;
+use async_trait::async_trait;
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const PUBLISH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One record to publish, as stored in the outbox
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutgoingEvent {
+    pub topic: String,
+    pub key: String,
+    pub payload: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl OutgoingEvent {
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Where published events go: Kafka, memory or nowhere, chosen per profile
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    async fn publish(&self, event: &OutgoingEvent) -> Result<(), String>;
+
+    /// The capturing publisher, when this is one, so tests can assert on what was published
+    fn in_memory(&self) -> Option<&InMemoryEventPublisher> {
+        None
+    }
+}
+
+/// Sends events to Kafka, waiting for the broker's acknowledgement
+pub struct KafkaEventPublisher {
+    producer: Arc<FutureProducer>,
+}
+
+impl KafkaEventPublisher {
+    pub fn new(producer: Arc<FutureProducer>) -> Self {
+        Self { producer }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for KafkaEventPublisher {
+    async fn publish(&self, event: &OutgoingEvent) -> Result<(), String> {
+        let mut headers = OwnedHeaders::new();
+        for (key, value) in &event.headers {
+            headers = headers.insert(Header { key: key.as_str(), value: Some(value.as_str()) });
+        }
+        let record = FutureRecord::to(&event.topic).payload(&event.payload).key(&event.key).headers(headers);
+
+        let result = self.producer.send(record, PUBLISH_TIMEOUT).await;
+        metrics().record_kafka_produce(&event.topic, result.is_ok());
+        result.map(|_| ()).map_err(|(e, _)| e.to_string())
+    }
+}
+
+/// Keeps published events in memory, for tests and local runs without Kafka
+#[derive(Default)]
+pub struct InMemoryEventPublisher {
+    events: Mutex<Vec<OutgoingEvent>>,
+}
+
+impl InMemoryEventPublisher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every event published so far, oldest first
+    pub fn published(&self) -> Vec<OutgoingEvent> {
+        self.events.lock().expect("publisher lock poisoned").clone()
+    }
+
+    /// Envelopes of the published `E` events, oldest first
+    pub fn published_events<E: DomainEvent>(&self) -> Vec<EventEnvelope<E>> {
+        self.published()
+            .iter()
+            .filter(|event| event.topic == E::TOPIC && event.header(EVENT_TYPE_HEADER) == Some(E::EVENT_TYPE))
+            .filter_map(|event| serde_json::from_str(&event.payload).ok())
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.events.lock().expect("publisher lock poisoned").clear();
+    }
+}
+
+#[async_trait]
+impl EventPublisher for InMemoryEventPublisher {
+    async fn publish(&self, event: &OutgoingEvent) -> Result<(), String> {
+        log::debug!("Captured {} event for {}", event.topic, event.key);
+        self.events.lock().expect("publisher lock poisoned").push(event.clone());
+        Ok(())
+    }
+
+    fn in_memory(&self) -> Option<&InMemoryEventPublisher> {
+        Some(self)
+    }
+}
+
+/// Drops every event
+pub struct NoopEventPublisher;
+
+#[async_trait]
+impl EventPublisher for NoopEventPublisher {
+    async fn publish(&self, _event: &OutgoingEvent) -> Result<(), String> {
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use crate::request_id::current_request_id;
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Header carrying the signed user context from the gateway to downstream services
+pub const INTERNAL_CONTEXT_HEADER: &str = "x-internal-user-context";
+
+/// Identity headers only the gateway may set; any client-supplied copy must be dropped
+pub const IDENTITY_HEADERS: &[&str] = &[
+    INTERNAL_CONTEXT_HEADER,
+    "x-user-id",
+    "x-session-id",
+    "x-user-roles",
+];
+
+const INTERNAL_ISSUER: &str = "api-gateway";
+
+/// Claims of the short-lived internal JWT minted by the gateway for each proxied request
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct InternalClaims {
+    pub iss: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub user_id: i64,
+    pub sid: String,
+    pub roles: Vec<String>,
+}
+
+impl InternalClaims {
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+}
+
+/// Signs internal user context tokens (HS256 with a secret shared by gateway and services)
+pub struct InternalTokenSigner {
+    key: EncodingKey,
+    ttl: Duration,
+}
+
+impl InternalTokenSigner {
+    pub fn new(secret: &str, ttl: Duration) -> Self {
+        Self {
+            key: EncodingKey::from_secret(secret.as_bytes()),
+            ttl,
+        }
+    }
+
+    pub fn sign(
+        &self,
+        user_id: i64,
+        session_id: &str,
+        roles: &[String],
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        let now = chrono::Utc::now().timestamp();
+        let claims = InternalClaims {
+            iss: INTERNAL_ISSUER.to_string(),
+            iat: now,
+            exp: now + self.ttl.as_secs() as i64,
+            user_id,
+            sid: session_id.to_string(),
+            roles: roles.to_vec(),
+        };
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.key)
+    }
+}
+
+/// Verifies internal user context tokens, cheap to clone into each service's state
+#[derive(Clone)]
+pub struct InternalTokenVerifier {
+    key: Arc<DecodingKey>,
+    validation: Arc<Validation>,
+}
+
+impl InternalTokenVerifier {
+    pub fn new(secret: &str) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[INTERNAL_ISSUER]);
+        validation.leeway = 5;
+
+        Self {
+            key: Arc::new(DecodingKey::from_secret(secret.as_bytes())),
+            validation: Arc::new(validation),
+        }
+    }
+
+    pub fn verify(&self, token: &str) -> Result<InternalClaims, jsonwebtoken::errors::Error> {
+        jsonwebtoken::decode::<InternalClaims>(token, &self.key, &self.validation)
+            .map(|data| data.claims)
+    }
+}
+
+/// Extractor for the caller identity forwarded by the gateway
+///
+/// The service state must expose an `InternalTokenVerifier` through `FromRef`.
+#[derive(Debug, Clone)]
+pub struct InternalUserContext(pub InternalClaims);
+
+#[derive(Debug)]
+pub enum InternalAuthRejection {
+    MissingContext,
+    InvalidContext(String),
+}
+
+impl IntoResponse for InternalAuthRejection {
+    fn into_response(self) -> Response {
+        let detail = match self {
+            InternalAuthRejection::MissingContext => "Missing internal user context".to_string(),
+            InternalAuthRejection::InvalidContext(err) => {
+                log::warn!("Rejected internal user context: {}", err);
+                "Invalid internal user context".to_string()
+            },
+        };
+        let body = serde_json::json!({
+            "code_message": "Unauthorized",
+            "message": { "detail": detail },
+            "request_id": current_request_id(),
+        });
+        (StatusCode::UNAUTHORIZED, Json(body)).into_response()
+    }
+}
+
+impl<S> FromRequestParts<S> for InternalUserContext
+where
+    InternalTokenVerifier: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = InternalAuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(INTERNAL_CONTEXT_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(InternalAuthRejection::MissingContext)?;
+
+        let verifier = InternalTokenVerifier::from_ref(state);
+        verifier
+            .verify(token)
+            .map(InternalUserContext)
+            .map_err(|e| InternalAuthRejection::InvalidContext(e.to_string()))
+    }
+}
+
+/// Claims of a service-to-service token, `iss` is the calling service
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ServiceClaims {
+    pub iss: String,
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// Signs tokens a service presents when calling another service's internal API
+///
+/// Each caller has its own secret, named by the token's `kid`, so a service can only
+/// mint tokens for itself.
+pub struct ServiceTokenSigner {
+    service: String,
+    key: EncodingKey,
+    ttl: Duration,
+}
+
+impl ServiceTokenSigner {
+    pub fn new(service: &str, secret: &str, ttl: Duration) -> Self {
+        Self {
+            service: service.to_string(),
+            key: EncodingKey::from_secret(secret.as_bytes()),
+            ttl,
+        }
+    }
+
+    /// Token for calls to `audience`, sent as `authorization: Bearer <token>`
+    pub fn sign(&self, audience: &str) -> Result<String, jsonwebtoken::errors::Error> {
+        let now = chrono::Utc::now().timestamp();
+        let claims = ServiceClaims {
+            iss: self.service.clone(),
+            aud: audience.to_string(),
+            iat: now,
+            exp: now + self.ttl.as_secs() as i64,
+        };
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some(self.service.clone());
+        jsonwebtoken::encode(&header, &claims, &self.key)
+    }
+}
+
+/// Verifies service tokens addressed to one audience, each with the secret of its caller
+#[derive(Clone)]
+pub struct ServiceTokenVerifier {
+    keys: Arc<HashMap<String, DecodingKey>>,
+    audience: String,
+}
+
+impl ServiceTokenVerifier {
+    /// `callers` maps each service allowed to call to the secret it signs with
+    pub fn new(audience: &str, callers: &HashMap<String, String>) -> Self {
+        let keys = callers
+            .iter()
+            .map(|(service, secret)| (service.clone(), DecodingKey::from_secret(secret.as_bytes())))
+            .collect();
+
+        Self {
+            keys: Arc::new(keys),
+            audience: audience.to_string(),
+        }
+    }
+
+    /// Claims of a token signed with its caller's secret; `iss` must name that caller
+    pub fn verify(&self, token: &str) -> Result<ServiceClaims, jsonwebtoken::errors::Error> {
+        let caller = jsonwebtoken::decode_header(token)?
+            .kid
+            .ok_or(ErrorKind::InvalidToken)?;
+        let key = self.keys.get(&caller).ok_or(ErrorKind::InvalidIssuer)?;
+
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_audience(&[&self.audience]);
+        validation.set_issuer(&[&caller]);
+        validation.leeway = 5;
+        jsonwebtoken::decode::<ServiceClaims>(token, key, &validation).map(|data| data.claims)
+    }
+}
@@ -0,0 +1,265 @@
+use config::ConfigError;
+use rdkafka::consumer::StreamConsumer;
+use rdkafka::producer::FutureProducer;
+use rdkafka::ClientConfig;
+use serde::Deserialize;
+use std::sync::Arc;
+use crate::events::publisher::{EventPublisher, InMemoryEventPublisher, KafkaEventPublisher, NoopEventPublisher};
+
+/// Kafka client settings shared by the producer and every consumer of a service
+///
+/// Read from the `[kafka]` section, each field can be overridden with e.g.
+/// `DEV_APP__KAFKA__BOOTSTRAP_SERVERS` or `DEV_APP__KAFKA__SASL__PASSWORD`.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct KafkaConfig {
+    /// Comma-separated `host:port` list
+    #[serde(alias = "server_url")]
+    pub bootstrap_servers: String,
+    /// Name of the service, shown in broker logs and metrics
+    pub client_id: String,
+    pub security_protocol: SecurityProtocol,
+    /// Required with the `sasl_plaintext` and `sasl_ssl` protocols
+    pub sasl: Option<SaslConfig>,
+    /// CA bundle used to verify the brokers with `ssl` and `sasl_ssl`
+    pub ssl_ca_location: Option<String>,
+    pub acks: Acks,
+    /// Exactly-once per partition on producer retries, requires `acks = "all"`
+    pub enable_idempotence: bool,
+    pub compression: Compression,
+    pub linger_ms: u64,
+    /// Maximum bytes per partition batch
+    pub batch_size: u32,
+    /// How long the producer keeps trying to deliver a record
+    #[serde(alias = "timeout_ms")]
+    pub message_timeout_ms: u64,
+    pub session_timeout_ms: u64,
+    pub allow_auto_create_topics: bool,
+    /// Where events are published; anything but `kafka` runs without a broker
+    pub publisher: PublisherKind,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PublisherKind {
+    Kafka,
+    /// Kept in memory, e.g. for tests and `local`
+    InMemory,
+    /// Dropped
+    Noop,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityProtocol {
+    Plaintext,
+    Ssl,
+    SaslPlaintext,
+    SaslSsl,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SaslConfig {
+    pub mechanism: SaslMechanism,
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SaslMechanism {
+    #[serde(rename = "PLAIN")]
+    Plain,
+    #[serde(rename = "SCRAM-SHA-256")]
+    ScramSha256,
+    #[serde(rename = "SCRAM-SHA-512")]
+    ScramSha512,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Acks {
+    /// Wait for every in-sync replica
+    All,
+    Leader,
+    None,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    None,
+    Gzip,
+    Snappy,
+    Lz4,
+    Zstd,
+}
+
+impl SecurityProtocol {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SecurityProtocol::Plaintext => "plaintext",
+            SecurityProtocol::Ssl => "ssl",
+            SecurityProtocol::SaslPlaintext => "sasl_plaintext",
+            SecurityProtocol::SaslSsl => "sasl_ssl",
+        }
+    }
+
+    fn uses_sasl(&self) -> bool {
+        matches!(self, SecurityProtocol::SaslPlaintext | SecurityProtocol::SaslSsl)
+    }
+}
+
+impl SaslMechanism {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SaslMechanism::Plain => "PLAIN",
+            SaslMechanism::ScramSha256 => "SCRAM-SHA-256",
+            SaslMechanism::ScramSha512 => "SCRAM-SHA-512",
+        }
+    }
+}
+
+impl Acks {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Acks::All => "all",
+            Acks::Leader => "1",
+            Acks::None => "0",
+        }
+    }
+}
+
+impl Compression {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Snappy => "snappy",
+            Compression::Lz4 => "lz4",
+            Compression::Zstd => "zstd",
+        }
+    }
+}
+
+impl Default for KafkaConfig {
+    fn default() -> Self {
+        Self {
+            bootstrap_servers: "localhost:9092".to_string(),
+            client_id: String::new(),
+            security_protocol: SecurityProtocol::Plaintext,
+            sasl: None,
+            ssl_ca_location: None,
+            acks: Acks::All,
+            enable_idempotence: true,
+            compression: Compression::Lz4,
+            linger_ms: 5,
+            batch_size: 16384,
+            message_timeout_ms: 5000,
+            session_timeout_ms: 6000,
+            allow_auto_create_topics: true,
+            publisher: PublisherKind::Kafka,
+        }
+    }
+}
+
+impl KafkaConfig {
+    /// Whether a broker is used at all; without one no consumers are started
+    pub fn enabled(&self) -> bool {
+        self.publisher == PublisherKind::Kafka
+    }
+
+    /// Reject combinations librdkafka would only report once a client is created
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |message: &str| Err(ConfigError::Message(format!("Invalid kafka config: {}", message)));
+
+        if self.bootstrap_servers.split(',').all(|server| server.trim().is_empty()) {
+            return invalid("bootstrap_servers is empty");
+        }
+        if self.client_id.trim().is_empty() {
+            return invalid("client_id is empty");
+        }
+        match (&self.sasl, self.security_protocol.uses_sasl()) {
+            (None, true) => return invalid("sasl is required with a sasl_* security_protocol"),
+            (Some(_), false) => return invalid("sasl is set but security_protocol does not use SASL"),
+            (Some(sasl), true) if sasl.username.is_empty() || sasl.password.is_empty() => {
+                return invalid("sasl username and password are required");
+            },
+            _ => {},
+        }
+        if self.enable_idempotence && self.acks != Acks::All {
+            return invalid("enable_idempotence requires acks = \"all\"");
+        }
+        if self.batch_size == 0 {
+            return invalid("batch_size must be positive");
+        }
+        if self.linger_ms >= self.message_timeout_ms {
+            return invalid("linger_ms must be below message_timeout_ms");
+        }
+        Ok(())
+    }
+
+    /// Connection and security settings common to producers and consumers
+    fn client_config(&self) -> ClientConfig {
+        let mut config = ClientConfig::new();
+        config
+            .set("bootstrap.servers", &self.bootstrap_servers)
+            .set("client.id", &self.client_id)
+            .set("security.protocol", self.security_protocol.as_str())
+            .set("allow.auto.create.topics", self.allow_auto_create_topics.to_string());
+        if let Some(sasl) = &self.sasl {
+            config
+                .set("sasl.mechanism", sasl.mechanism.as_str())
+                .set("sasl.username", &sasl.username)
+                .set("sasl.password", &sasl.password);
+        }
+        if let Some(ca_location) = &self.ssl_ca_location {
+            config.set("ssl.ca.location", ca_location);
+        }
+        config
+    }
+
+    /// Without Kafka the producer has no brokers, so it stays idle instead of reconnecting
+    pub fn create_kafka_producer(&self) -> FutureProducer {
+        let mut config = self.client_config();
+        if !self.enabled() {
+            config.remove("bootstrap.servers");
+        }
+        config
+            .set("message.timeout.ms", self.message_timeout_ms.to_string())
+            .set("acks", self.acks.as_str())
+            .set("enable.idempotence", self.enable_idempotence.to_string())
+            .set("compression.type", self.compression.as_str())
+            .set("linger.ms", self.linger_ms.to_string())
+            .set("batch.size", self.batch_size.to_string())
+            .create()
+            .expect("Producer creation error")
+    }
+
+    /// Reuses `producer` for Kafka when one was already built
+    pub fn create_event_publisher(&self, producer: Option<Arc<FutureProducer>>) -> Arc<dyn EventPublisher> {
+        match self.publisher {
+            PublisherKind::Kafka => Arc::new(KafkaEventPublisher::new(
+                producer.unwrap_or_else(|| Arc::new(self.create_kafka_producer())),
+            )),
+            PublisherKind::InMemory => Arc::new(InMemoryEventPublisher::new()),
+            PublisherKind::Noop => Arc::new(NoopEventPublisher),
+        }
+    }
+
+    /// Connection settings for consumers, group and offset handling are set by the caller
+    pub fn consumer_client_config(&self) -> ClientConfig {
+        let mut config = self.client_config();
+        config.set("session.timeout.ms", self.session_timeout_ms.to_string());
+        config
+    }
+
+    /// Consumer committing offsets automatically, for subscriptions that can lose a message
+    pub fn create_group_consumer(&self, group_id: &str) -> StreamConsumer {
+        self.consumer_client_config()
+            .set("group.id", group_id)
+            .set("enable.partition.eof", "false")
+            .set("enable.auto.commit", "true")
+            .create()
+            .expect("Consumer creation failed")
+    }
+}
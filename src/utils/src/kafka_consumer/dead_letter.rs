@@ -0,0 +1,293 @@
+use crate::kafka_consumer::handler::EVENT_TYPE_HEADER;
+use crate::metrics::metrics;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rdkafka::consumer::{BaseConsumer, Consumer};
+use rdkafka::error::{KafkaError, KafkaResult};
+use rdkafka::message::{Header, Headers, OwnedHeaders};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::util::Timeout;
+use rdkafka::{ClientConfig, Message, Offset, TopicPartitionList};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// Retry tier of a record, `0` for its first delivery
+pub const RETRY_ATTEMPT_HEADER: &str = "retry-attempt";
+/// Unix millis before which a record on a retry topic is not handled
+pub const RETRY_NOT_BEFORE_HEADER: &str = "retry-not-before";
+pub const ORIGINAL_TOPIC_HEADER: &str = "original-topic";
+pub const ORIGINAL_PARTITION_HEADER: &str = "original-partition";
+pub const ORIGINAL_OFFSET_HEADER: &str = "original-offset";
+pub const CONSUMER_GROUP_HEADER: &str = "consumer-group";
+pub const ERROR_HEADER: &str = "error";
+/// `transient` when the retries ran out, `permanent` when retrying could not help
+pub const ERROR_KIND_HEADER: &str = "error-kind";
+pub const FAILED_AT_HEADER: &str = "failed-at";
+
+/// Headers set by the runtime, replaced rather than copied when a record is forwarded
+const CONTROL_HEADERS: &[&str] = &[
+    RETRY_ATTEMPT_HEADER,
+    RETRY_NOT_BEFORE_HEADER,
+    ORIGINAL_TOPIC_HEADER,
+    ORIGINAL_PARTITION_HEADER,
+    ORIGINAL_OFFSET_HEADER,
+    CONSUMER_GROUP_HEADER,
+    ERROR_HEADER,
+    ERROR_KIND_HEADER,
+    FAILED_AT_HEADER,
+];
+
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(5);
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const READ_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Retry topic `tier` of a consumer group, shared by every topic the group consumes
+pub fn retry_topic(group_id: &str, tier: usize) -> String {
+    format!("{}.retry.{}", group_id, tier)
+}
+
+/// Dead-letter topic of a consumer group
+pub fn dead_letter_topic(group_id: &str) -> String {
+    format!("{}.dlq", group_id)
+}
+
+/// First value of a header, when it is valid UTF-8
+pub(crate) fn header_value<'a>(message: &'a impl Message, key: &str) -> Option<&'a str> {
+    message
+        .headers()?
+        .iter()
+        .find(|header| header.key == key)
+        .and_then(|header| std::str::from_utf8(header.value?).ok())
+}
+
+/// Copy of `message` on `topic`, its control headers replaced by `control`
+pub(crate) async fn forward(
+    producer: &FutureProducer,
+    topic: &str,
+    message: &impl Message,
+    control: Vec<(&str, String)>,
+) -> KafkaResult<()> {
+    let mut headers = OwnedHeaders::new();
+    if let Some(original) = message.headers() {
+        for header in original.iter().filter(|header| !CONTROL_HEADERS.contains(&header.key)) {
+            headers = headers.insert(header);
+        }
+    }
+    for (key, value) in &control {
+        headers = headers.insert(Header { key, value: Some(value.as_str()) });
+    }
+
+    let mut record = FutureRecord::to(topic)
+        .payload(message.payload().unwrap_or_default())
+        .headers(headers);
+    if let Some(key) = message.key() {
+        record = record.key(key);
+    }
+
+    let result = producer
+        .send(record, Timeout::After(FORWARD_TIMEOUT))
+        .await
+        .map(|_| ())
+        .map_err(|(e, _)| e);
+    metrics().record_kafka_produce(topic, result.is_ok());
+    result
+}
+
+/// A record in a dead-letter topic, with the metadata the runtime attached
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DeadLetter {
+    pub partition: i32,
+    pub offset: i64,
+    pub key: Option<String>,
+    pub original_topic: Option<String>,
+    pub event_type: Option<String>,
+    pub error: Option<String>,
+    pub error_kind: Option<String>,
+    /// Retry tiers the record went through before it was dead-lettered
+    pub attempts: Option<u32>,
+    pub failed_at: Option<String>,
+    pub headers: BTreeMap<String, String>,
+    /// Record value as produced, base64 encoded in JSON since it need not be UTF-8
+    #[serde(serialize_with = "serialize_base64")]
+    #[schema(value_type = String, format = Byte)]
+    pub payload: Vec<u8>,
+}
+
+fn serialize_base64<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&BASE64.encode(bytes))
+}
+
+impl DeadLetter {
+    fn from_message(message: &impl Message) -> Self {
+        let headers: BTreeMap<String, String> = message
+            .headers()
+            .map(|headers| {
+                headers
+                    .iter()
+                    .filter_map(|header| {
+                        let value = std::str::from_utf8(header.value?).ok()?;
+                        Some((header.key.to_string(), value.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let header = |key: &str| headers.get(key).cloned();
+
+        Self {
+            partition: message.partition(),
+            offset: message.offset(),
+            key: message.key().map(|key| String::from_utf8_lossy(key).into_owned()),
+            original_topic: header(ORIGINAL_TOPIC_HEADER),
+            event_type: header(EVENT_TYPE_HEADER),
+            error: header(ERROR_HEADER),
+            error_kind: header(ERROR_KIND_HEADER),
+            attempts: header(RETRY_ATTEMPT_HEADER).and_then(|value| value.parse().ok()),
+            failed_at: header(FAILED_AT_HEADER),
+            payload: message.payload().unwrap_or_default().to_vec(),
+            headers,
+        }
+    }
+}
+
+/// Reads and replays the dead-letter topic of one consumer group
+///
+/// Kafka cannot delete single records, so callers keep track of which dead letters
+/// were replayed or discarded themselves.
+#[derive(Clone)]
+pub struct DeadLetterQueue {
+    client_config: ClientConfig,
+    producer: Arc<FutureProducer>,
+    group_id: String,
+}
+
+impl DeadLetterQueue {
+    pub fn new(client_config: ClientConfig, producer: Arc<FutureProducer>, group_id: &str) -> Self {
+        Self { client_config, producer, group_id: group_id.to_string() }
+    }
+
+    pub fn topic(&self) -> String {
+        dead_letter_topic(&self.group_id)
+    }
+
+    /// Up to `limit` dead letters from `offset` on, in every partition unless `partition` is set
+    ///
+    /// Records at the `(partition, offset)` positions in `skip` are read past without counting
+    /// towards `limit`.
+    pub async fn list(
+        &self,
+        partition: Option<i32>,
+        offset: Option<i64>,
+        limit: usize,
+        skip: HashSet<(i32, i64)>,
+    ) -> KafkaResult<Vec<DeadLetter>> {
+        let client_config = self.reader_config();
+        let topic = self.topic();
+        tokio::task::spawn_blocking(move || read(client_config, &topic, partition, offset, limit, &skip))
+            .await
+            .map_err(|_| KafkaError::Canceled)?
+    }
+
+    pub async fn get(&self, partition: i32, offset: i64) -> KafkaResult<Option<DeadLetter>> {
+        let letters = self.list(Some(partition), Some(offset), 1, HashSet::new()).await?;
+        Ok(letters.into_iter().find(|letter| letter.offset == offset))
+    }
+
+    /// Send a dead letter through the group's first retry tier again, without delay
+    pub async fn replay(&self, letter: &DeadLetter) -> KafkaResult<()> {
+        let topic = retry_topic(&self.group_id, 1);
+        let mut headers = OwnedHeaders::new();
+        let kept = letter.headers.iter().filter(|(key, _)| {
+            !CONTROL_HEADERS.contains(&key.as_str()) || key.starts_with("original-")
+        });
+        for (key, value) in kept {
+            headers = headers.insert(Header { key: key.as_str(), value: Some(value.as_str()) });
+        }
+        headers = headers
+            .insert(Header { key: RETRY_ATTEMPT_HEADER, value: Some("1") })
+            .insert(Header {
+                key: RETRY_NOT_BEFORE_HEADER,
+                value: Some(&chrono::Utc::now().timestamp_millis().to_string()),
+            });
+
+        let mut record = FutureRecord::to(&topic).payload(&letter.payload).headers(headers);
+        if let Some(key) = &letter.key {
+            record = record.key(key.as_bytes());
+        }
+        let result = self
+            .producer
+            .send(record, Timeout::After(FORWARD_TIMEOUT))
+            .await
+            .map(|_| ())
+            .map_err(|(e, _)| e);
+        metrics().record_kafka_produce(&topic, result.is_ok());
+        result
+    }
+
+    /// Reads never commit, so any group ID works; a separate one keeps them out of the runtime's group
+    fn reader_config(&self) -> ClientConfig {
+        let mut config = self.client_config.clone();
+        config
+            .set("group.id", format!("{}.dlq-reader", self.group_id))
+            .set("enable.auto.commit", "false")
+            .set("enable.partition.eof", "false");
+        config
+    }
+}
+
+fn read(
+    client_config: ClientConfig,
+    topic: &str,
+    partition: Option<i32>,
+    offset: Option<i64>,
+    limit: usize,
+    skip: &HashSet<(i32, i64)>,
+) -> KafkaResult<Vec<DeadLetter>> {
+    let consumer: BaseConsumer = client_config.create()?;
+    let metadata = consumer.fetch_metadata(Some(topic), READ_TIMEOUT)?;
+    let partitions: Vec<i32> = metadata
+        .topics()
+        .iter()
+        .flat_map(|topic| topic.partitions().iter().map(|partition| partition.id()))
+        .filter(|id| partition.is_none_or(|partition| partition == *id))
+        .collect();
+
+    // Read each partition up to the end it had when the read started
+    let mut assignment = TopicPartitionList::new();
+    let mut ends = HashMap::new();
+    for id in partitions {
+        let (low, high) = consumer.fetch_watermarks(topic, id, READ_TIMEOUT)?;
+        let start = offset.unwrap_or(low).max(low);
+        if start < high {
+            assignment.add_partition_offset(topic, id, Offset::Offset(start))?;
+            ends.insert(id, high);
+        }
+    }
+    if ends.is_empty() {
+        return Ok(Vec::new());
+    }
+    consumer.assign(&assignment)?;
+
+    let deadline = Instant::now() + READ_TIMEOUT;
+    let mut letters = Vec::new();
+    while letters.len() < limit && !ends.is_empty() && Instant::now() < deadline {
+        let Some(message) = consumer.poll(READ_POLL_INTERVAL) else {
+            continue;
+        };
+        let message = message?;
+        let Some(end) = ends.get(&message.partition()).copied() else {
+            continue;
+        };
+        if !skip.contains(&(message.partition(), message.offset())) {
+            letters.push(DeadLetter::from_message(&message));
+        }
+        if message.offset() + 1 >= end {
+            ends.remove(&message.partition());
+        }
+    }
+
+    letters.sort_by_key(|letter| (letter.partition, letter.offset));
+    Ok(letters)
+}
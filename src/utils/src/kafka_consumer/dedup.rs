@@ -0,0 +1,82 @@
+use sea_orm::{ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, DbErr, Statement};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+/// Table recording the events each consumer group has handled, created by each service's
+/// migrations
+pub const PROCESSED_EVENTS_TABLE: &str = "processed_events";
+
+/// How often rows past the retention are deleted
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Just the ID of an `EventEnvelope`, read before the handler decodes the rest
+#[derive(Deserialize)]
+pub(crate) struct EnvelopeId {
+    pub id: Uuid,
+}
+
+/// Record `event_id` as handled by `group_id` in the handler's transaction
+///
+/// Returns false when the group already handled the event, in which case the handler must
+/// not run. The row is rolled back with the handler's changes if the handler fails, so a
+/// retried event is not mistaken for a duplicate.
+pub(crate) async fn claim_event(
+    tx: &DatabaseTransaction,
+    group_id: &str,
+    event_id: Uuid,
+    event_type: &str,
+) -> Result<bool, DbErr> {
+    let result = tx
+        .execute_raw(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                "INSERT INTO {} (consumer_group, event_id, event_type, processed_at) \
+                 VALUES ($1, $2, $3, NOW()) ON CONFLICT (consumer_group, event_id) DO NOTHING",
+                PROCESSED_EVENTS_TABLE
+            ),
+            [group_id.into(), event_id.into(), event_type.into()],
+        ))
+        .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Delete the group's rows older than `retention` every hour until `stop` is set
+///
+/// Duplicates come from redelivery after a crash or rebalance and from the outbox
+/// publishing an event twice, both well within the retention.
+pub(crate) async fn cleanup_processed_events(
+    db: Arc<DatabaseConnection>,
+    group_id: String,
+    retention: Duration,
+    mut stop: watch::Receiver<bool>,
+) {
+    let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = stop.wait_for(|stopped| *stopped) => return,
+        }
+
+        let cutoff = chrono::Utc::now() - chrono::Duration::from_std(retention).unwrap_or_default();
+        let deleted = db
+            .execute_raw(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                format!(
+                    "DELETE FROM {} WHERE consumer_group = $1 AND processed_at < $2",
+                    PROCESSED_EVENTS_TABLE
+                ),
+                [group_id.as_str().into(), cutoff.into()],
+            ))
+            .await;
+        match deleted {
+            Ok(result) if result.rows_affected() > 0 => {
+                log::info!("Deleted {} processed events of {}", result.rows_affected(), group_id)
+            },
+            Ok(_) => {},
+            Err(e) => log::error!("Processed events cleanup of {} failed: {}", group_id, e),
+        }
+    }
+}
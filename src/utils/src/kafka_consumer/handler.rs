@@ -0,0 +1,116 @@
+use crate::kafka_consumer::dead_letter::{
+    header_value, ORIGINAL_OFFSET_HEADER, ORIGINAL_PARTITION_HEADER, ORIGINAL_TOPIC_HEADER,
+    RETRY_ATTEMPT_HEADER,
+};
+use crate::events::EventEnvelope;
+use async_trait::async_trait;
+use rdkafka::Message;
+use sea_orm::{DatabaseTransaction, DbErr};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Header naming the event type of a record, when a topic carries more than one
+///
+/// Records without it are dispatched with the topic name as their event type.
+pub const EVENT_TYPE_HEADER: &str = "event-type";
+
+/// Where a record came from, passed to handlers next to the decoded event
+///
+/// For a record redelivered from a retry topic these describe the original record.
+#[derive(Debug, Clone)]
+pub struct RecordMeta {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    pub key: Option<String>,
+    pub event_type: String,
+    /// Retry tier the record is handled in, `0` for its first delivery
+    pub attempt: usize,
+}
+
+impl RecordMeta {
+    pub fn from_message(message: &impl Message) -> Self {
+        let topic = header_value(message, ORIGINAL_TOPIC_HEADER).unwrap_or(message.topic()).to_string();
+        let partition = header_value(message, ORIGINAL_PARTITION_HEADER).and_then(|value| value.parse().ok());
+        let offset = header_value(message, ORIGINAL_OFFSET_HEADER).and_then(|value| value.parse().ok());
+        let attempt = header_value(message, RETRY_ATTEMPT_HEADER).and_then(|value| value.parse().ok());
+
+        Self {
+            partition: partition.unwrap_or(message.partition()),
+            offset: offset.unwrap_or(message.offset()),
+            key: message.key().map(|key| String::from_utf8_lossy(key).into_owned()),
+            event_type: header_value(message, EVENT_TYPE_HEADER).unwrap_or(&topic).to_string(),
+            attempt: attempt.unwrap_or(0),
+            topic,
+        }
+    }
+}
+
+/// Outcome of a failed handler call, deciding what happens to the record
+#[derive(Debug, thiserror::Error)]
+pub enum HandlerError {
+    /// Retried through the retry topics, then dead-lettered
+    #[error("Transient handler error: {0}")]
+    Transient(String),
+    /// Retrying cannot help, the record is dead-lettered right away
+    #[error("Permanent handler error: {0}")]
+    Permanent(String),
+}
+
+impl From<DbErr> for HandlerError {
+    fn from(err: DbErr) -> Self {
+        HandlerError::Transient(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for HandlerError {
+    fn from(err: serde_json::Error) -> Self {
+        HandlerError::Permanent(err.to_string())
+    }
+}
+
+/// Typed handler for one event type, registered with `ConsumerRuntime::register`
+///
+/// Records are decoded as an `EventEnvelope` around `Event`. Each call runs in its own
+/// transaction, committed by the runtime when the handler returns `Ok`. The runtime skips
+/// events the group already handled, so changes made through `tx` happen once; side effects
+/// outside it, e.g. Redis or email, repeat if the commit fails after them.
+#[async_trait]
+pub trait EventHandler: Send + Sync + 'static {
+    type Event: DeserializeOwned + Send;
+
+    async fn handle(
+        &self,
+        tx: &DatabaseTransaction,
+        event: EventEnvelope<Self::Event>,
+        meta: &RecordMeta,
+    ) -> Result<(), HandlerError>;
+}
+
+/// Handlers by topic and event type
+pub(crate) type HandlerMap = HashMap<(String, String), Arc<dyn RecordHandler>>;
+
+/// `EventHandler` with the event type erased, so handlers can share one registry
+#[async_trait]
+pub(crate) trait RecordHandler: Send + Sync {
+    async fn handle_record(
+        &self,
+        tx: &DatabaseTransaction,
+        payload: &[u8],
+        meta: &RecordMeta,
+    ) -> Result<(), HandlerError>;
+}
+
+#[async_trait]
+impl<H: EventHandler> RecordHandler for H {
+    async fn handle_record(
+        &self,
+        tx: &DatabaseTransaction,
+        payload: &[u8],
+        meta: &RecordMeta,
+    ) -> Result<(), HandlerError> {
+        let event = serde_json::from_slice(payload)?;
+        self.handle(tx, event, meta).await
+    }
+}
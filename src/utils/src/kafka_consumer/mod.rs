@@ -0,0 +1,12 @@
+pub mod dead_letter;
+pub mod dedup;
+pub mod handler;
+pub mod replay;
+pub mod runtime;
+
+// Re-export commonly used types
+pub use dead_letter::{DeadLetter, DeadLetterQueue};
+pub use dedup::PROCESSED_EVENTS_TABLE;
+pub use handler::{EventHandler, HandlerError, RecordMeta, EVENT_TYPE_HEADER};
+pub use replay::{EventReplay, Projection, ReplayArgs, ReplayError, ReplayReport, ReplayStart};
+pub use runtime::ConsumerRuntime;
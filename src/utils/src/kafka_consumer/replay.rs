@@ -0,0 +1,317 @@
+use crate::kafka_consumer::handler::{EventHandler, HandlerError, HandlerMap, RecordHandler, RecordMeta};
+use chrono::{DateTime, Utc};
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::error::{KafkaError, KafkaResult};
+use rdkafka::message::BorrowedMessage;
+use rdkafka::{ClientConfig, Message, Offset, TopicPartitionList};
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+const REPLAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where a replay starts reading each partition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayStart {
+    /// The oldest record still retained
+    Beginning,
+    /// This offset, or the oldest retained one if it was already deleted
+    Offset(i64),
+    /// The first record produced at or after this time
+    Time(DateTime<Utc>),
+}
+
+/// `replay` subcommand of a service binary
+#[derive(Debug, Clone, clap::Args)]
+pub struct ReplayArgs {
+    /// Projection to feed, as registered by the service
+    #[arg(long)]
+    pub projection: String,
+    /// Topic to re-read
+    #[arg(long)]
+    pub topic: String,
+    /// First offset to read in each partition
+    #[arg(long, conflicts_with = "from_time")]
+    pub from_offset: Option<i64>,
+    /// Read records produced at or after this time, e.g. 2025-01-15T00:00:00Z
+    #[arg(long)]
+    pub from_time: Option<DateTime<Utc>>,
+    /// Only read this partition
+    #[arg(long)]
+    pub partition: Option<i32>,
+    /// Run the handlers but roll back their transactions, refused for projections with
+    /// side effects outside the database
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl ReplayArgs {
+    /// From the beginning unless an offset or time is given
+    pub fn start(&self) -> ReplayStart {
+        match (self.from_offset, self.from_time) {
+            (Some(offset), _) => ReplayStart::Offset(offset),
+            (None, Some(time)) => ReplayStart::Time(time),
+            (None, None) => ReplayStart::Beginning,
+        }
+    }
+}
+
+/// Named set of handlers building one read model, fed by `EventReplay`
+///
+/// The same handlers are usually registered with the service's `ConsumerRuntime` too, so
+/// a replay rebuilds the read model exactly the way live consumption builds it.
+pub struct Projection {
+    name: String,
+    pub(crate) handlers: HandlerMap,
+    side_effects: bool,
+}
+
+impl Projection {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), handlers: HashMap::new(), side_effects: false }
+    }
+
+    /// Mark the handlers as changing state outside their transaction, e.g. Redis, which a
+    /// rollback cannot undo, so `EventReplay` refuses to dry-run them
+    pub fn with_side_effects(mut self) -> Self {
+        self.side_effects = true;
+        self
+    }
+
+    pub fn has_side_effects(&self) -> bool {
+        self.side_effects
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Handle `event_type` records of `topic`
+    pub fn register<H: EventHandler>(mut self, topic: &str, event_type: &str, handler: H) -> Self {
+        self.handlers.insert((topic.to_string(), event_type.to_string()), Arc::new(handler));
+        self
+    }
+
+    /// Topics the projection has handlers for
+    pub fn topics(&self) -> BTreeSet<&str> {
+        self.handlers.keys().map(|(topic, _)| topic.as_str()).collect()
+    }
+}
+
+/// Outcome of a replay
+#[derive(Debug, Default, Serialize)]
+pub struct ReplayReport {
+    /// Records read, up to the end each partition had when the replay started
+    pub read: u64,
+    pub handled: u64,
+    /// Records of event types the projection has no handler for
+    pub skipped: u64,
+    pub failed: Vec<ReplayFailure>,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayError {
+    #[error("Projection {0} has side effects outside the database, it cannot be dry-run")]
+    DryRunWithSideEffects(String),
+    #[error(transparent)]
+    Kafka(#[from] KafkaError),
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayFailure {
+    pub partition: i32,
+    pub offset: i64,
+    pub event_type: String,
+    pub error: String,
+}
+
+/// Re-reads a topic into a `Projection`, e.g. to rebuild a read model after a bug fix
+///
+/// Partitions are assigned directly, so the replay neither joins nor commits for a
+/// consumer group and the service's consumers are unaffected. Each record is handled in
+/// its own transaction, in partition order, without the `processed_events` check since
+/// every record was handled before. A failed record is reported and the replay goes on;
+/// it is not retried or dead-lettered.
+pub struct EventReplay {
+    client_config: ClientConfig,
+    db: Arc<DatabaseConnection>,
+    partition: Option<i32>,
+    dry_run: bool,
+}
+
+impl EventReplay {
+    pub fn new(client_config: ClientConfig, db: Arc<DatabaseConnection>) -> Self {
+        Self { client_config, db, partition: None, dry_run: false }
+    }
+
+    /// Only read this partition
+    pub fn with_partition(mut self, partition: Option<i32>) -> Self {
+        self.partition = partition;
+        self
+    }
+
+    /// Roll back every handler's transaction instead of committing it
+    ///
+    /// Only for projections without side effects outside the transaction, `run` refuses
+    /// the others.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Feed `topic` from `start` up to its current end into `projection`
+    pub async fn run(
+        &self,
+        projection: &Projection,
+        topic: &str,
+        start: ReplayStart,
+    ) -> Result<ReplayReport, ReplayError> {
+        if self.dry_run && projection.side_effects {
+            return Err(ReplayError::DryRunWithSideEffects(projection.name.clone()));
+        }
+        let mut report = ReplayReport { dry_run: self.dry_run, ..Default::default() };
+        let client_config = self.reader_config(projection);
+        let (topic_name, partition) = (topic.to_string(), self.partition);
+        let (consumer, mut ends) =
+            tokio::task::spawn_blocking(move || assign(client_config, &topic_name, partition, start))
+                .await
+                .map_err(|_| KafkaError::Canceled)??;
+        log::info!(
+            "Replaying {} into {} from {:?}, {} partitions{}",
+            topic,
+            projection.name,
+            start,
+            ends.len(),
+            if self.dry_run { " (dry run)" } else { "" }
+        );
+
+        while !ends.is_empty() {
+            let message = match tokio::time::timeout(REPLAY_TIMEOUT, consumer.recv()).await {
+                Ok(message) => message?,
+                Err(_) => {
+                    log::warn!("No records from {} for {:?}, stopping with {:?} unread", topic, REPLAY_TIMEOUT, ends);
+                    break;
+                },
+            };
+            let Some(end) = ends.get(&message.partition()).copied() else {
+                continue;
+            };
+            if message.offset() + 1 >= end {
+                ends.remove(&message.partition());
+            }
+            report.read += 1;
+            self.replay_record(projection, &message, &mut report).await;
+        }
+
+        log::info!(
+            "Replayed {} into {}: {} read, {} handled, {} skipped, {} failed",
+            topic,
+            projection.name,
+            report.read,
+            report.handled,
+            report.skipped,
+            report.failed.len()
+        );
+        Ok(report)
+    }
+
+    async fn replay_record(&self, projection: &Projection, message: &BorrowedMessage<'_>, report: &mut ReplayReport) {
+        let meta = RecordMeta::from_message(message);
+        let Some(handler) = projection.handlers.get(&(meta.topic.clone(), meta.event_type.clone())) else {
+            report.skipped += 1;
+            return;
+        };
+
+        let payload = message.payload().unwrap_or_default();
+        match self.handle(handler.as_ref(), payload, &meta).await {
+            Ok(()) => report.handled += 1,
+            Err(e) => {
+                log::warn!("Replay of {} at {}/{}@{} failed: {}", meta.event_type, meta.topic, meta.partition, meta.offset, e);
+                report.failed.push(ReplayFailure {
+                    partition: meta.partition,
+                    offset: meta.offset,
+                    event_type: meta.event_type,
+                    error: e.to_string(),
+                });
+            },
+        }
+    }
+
+    async fn handle(&self, handler: &dyn RecordHandler, payload: &[u8], meta: &RecordMeta) -> Result<(), HandlerError> {
+        let tx = self.db.begin().await?;
+        handler.handle_record(&tx, payload, meta).await?;
+        if self.dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+        Ok(())
+    }
+
+    /// Nothing is committed, the group ID only has to be distinct from the live consumers'
+    fn reader_config(&self, projection: &Projection) -> ClientConfig {
+        let mut config = self.client_config.clone();
+        config
+            .set("group.id", format!("{}.replay", projection.name))
+            .set("enable.auto.commit", "false")
+            .set("enable.auto.offset.store", "false")
+            .set("enable.partition.eof", "false");
+        config
+    }
+}
+
+/// Consumer assigned to every partition with records from `start` on, and the end of each
+fn assign(
+    client_config: ClientConfig,
+    topic: &str,
+    partition: Option<i32>,
+    start: ReplayStart,
+) -> KafkaResult<(StreamConsumer, HashMap<i32, i64>)> {
+    let consumer: StreamConsumer = client_config.create()?;
+    let metadata = consumer.fetch_metadata(Some(topic), REPLAY_TIMEOUT)?;
+    let partitions: Vec<i32> = metadata
+        .topics()
+        .iter()
+        .flat_map(|topic| topic.partitions().iter().map(|partition| partition.id()))
+        .filter(|id| partition.is_none_or(|partition| partition == *id))
+        .collect();
+
+    let starts: HashMap<i32, i64> = match start {
+        ReplayStart::Time(time) => {
+            let mut times = TopicPartitionList::new();
+            for id in &partitions {
+                times.add_partition_offset(topic, *id, Offset::Offset(time.timestamp_millis()))?;
+            }
+            consumer
+                .offsets_for_times(times, REPLAY_TIMEOUT)?
+                .elements()
+                .iter()
+                .filter_map(|element| match element.offset() {
+                    Offset::Offset(offset) => Some((element.partition(), offset)),
+                    // No record at or after the time
+                    _ => None,
+                })
+                .collect()
+        },
+        ReplayStart::Offset(offset) => partitions.iter().map(|id| (*id, offset)).collect(),
+        ReplayStart::Beginning => partitions.iter().map(|id| (*id, 0)).collect(),
+    };
+
+    let mut assignment = TopicPartitionList::new();
+    let mut ends = HashMap::new();
+    for (id, start) in starts {
+        let (low, high) = consumer.fetch_watermarks(topic, id, REPLAY_TIMEOUT)?;
+        let start = start.max(low);
+        if start < high {
+            assignment.add_partition_offset(topic, id, Offset::Offset(start))?;
+            ends.insert(id, high);
+        }
+    }
+    if !ends.is_empty() {
+        consumer.assign(&assignment)?;
+    }
+    Ok((consumer, ends))
+}
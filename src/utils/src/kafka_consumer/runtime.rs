@@ -0,0 +1,438 @@
+use crate::kafka_consumer::dead_letter::{
+    dead_letter_topic, forward, header_value, retry_topic, CONSUMER_GROUP_HEADER, ERROR_HEADER,
+    ERROR_KIND_HEADER, FAILED_AT_HEADER, ORIGINAL_OFFSET_HEADER, ORIGINAL_PARTITION_HEADER,
+    ORIGINAL_TOPIC_HEADER, RETRY_ATTEMPT_HEADER, RETRY_NOT_BEFORE_HEADER,
+};
+use crate::kafka_consumer::dedup::{claim_event, cleanup_processed_events, EnvelopeId};
+use crate::kafka_consumer::handler::{EventHandler, HandlerError, HandlerMap, RecordHandler, RecordMeta};
+use crate::kafka_consumer::replay::Projection;
+use crate::metrics::metrics;
+use crate::request_id::{with_optional_request_id, REQUEST_ID_HEADER};
+use crate::telemetry::{context_from_pairs, set_span_parent};
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::error::KafkaResult;
+use rdkafka::message::{Headers, OwnedMessage};
+use rdkafka::producer::FutureProducer;
+use rdkafka::{ClientConfig, Message, TopicPartitionList};
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use std::collections::{BTreeSet, HashMap};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch, Semaphore};
+use tokio::task::JoinSet;
+use tracing::Instrument;
+
+/// Records buffered per partition; past half of it the partition is paused
+const PARTITION_QUEUE_SIZE: usize = 64;
+const DEFAULT_MAX_CONCURRENCY: usize = 8;
+const DEFAULT_RETRY_DELAYS: &[Duration] =
+    &[Duration::from_secs(10), Duration::from_secs(60), Duration::from_secs(600)];
+const INITIAL_FORWARD_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_FORWARD_BACKOFF: Duration = Duration::from_secs(30);
+const DEFAULT_DEDUP_RETENTION: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// Consumer group dispatching records to typed handlers by topic and event type
+///
+/// Records of one partition are handled in order by a dedicated worker, partitions are
+/// handled concurrently up to `max_concurrency`. A record's offset is stored once its
+/// handler's transaction commits, or once the record was forwarded after a failure, so a
+/// crash redelivers records instead of losing them.
+///
+/// Failed records never hold back their partition: a transient failure moves the record
+/// to the group's next retry topic, `{group}.retry.{n}`, to be handled again after that
+/// tier's delay. Permanent failures and records out of retries go to `{group}.dlq` with
+/// the error in their headers.
+///
+/// Each event is handled at most once per group: its envelope ID is recorded in the
+/// `processed_events` table in the handler's transaction, and redelivered events whose ID
+/// is already there are skipped without calling the handler.
+pub struct ConsumerRuntime {
+    client_config: ClientConfig,
+    group_id: String,
+    db: Arc<DatabaseConnection>,
+    producer: Arc<FutureProducer>,
+    handlers: HandlerMap,
+    max_concurrency: usize,
+    retry_delays: Vec<Duration>,
+    dedup_retention: Duration,
+}
+
+impl ConsumerRuntime {
+    /// `client_config` holds the connection settings, offset handling is set by the runtime
+    pub fn new(
+        client_config: ClientConfig,
+        group_id: &str,
+        db: Arc<DatabaseConnection>,
+        producer: Arc<FutureProducer>,
+    ) -> Self {
+        Self {
+            client_config,
+            group_id: group_id.to_string(),
+            db,
+            producer,
+            handlers: HashMap::new(),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            retry_delays: DEFAULT_RETRY_DELAYS.to_vec(),
+            dedup_retention: DEFAULT_DEDUP_RETENTION,
+        }
+    }
+
+    /// Partitions handled at the same time, across all topics
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Delay of each retry tier, at least one; defaults to 10s, 1m and 10m
+    pub fn with_retry_delays(mut self, retry_delays: Vec<Duration>) -> Self {
+        if !retry_delays.is_empty() {
+            self.retry_delays = retry_delays;
+        }
+        self
+    }
+
+    /// How long handled event IDs are remembered; defaults to 7 days
+    pub fn with_dedup_retention(mut self, dedup_retention: Duration) -> Self {
+        self.dedup_retention = dedup_retention;
+        self
+    }
+
+    /// Handle `event_type` records of `topic`; the runtime subscribes to every registered topic
+    pub fn register<H: EventHandler>(mut self, topic: &str, event_type: &str, handler: H) -> Self {
+        self.handlers.insert((topic.to_string(), event_type.to_string()), Arc::new(handler));
+        self
+    }
+
+    /// Handle records with every handler of `projection`, the same ones a replay runs
+    pub fn register_projection(mut self, projection: Projection) -> Self {
+        self.handlers.extend(projection.handlers);
+        self
+    }
+
+    /// Consume until `shutdown` completes, then finish in-flight records and commit
+    pub async fn run(mut self, shutdown: impl Future<Output = ()>) -> KafkaResult<()> {
+        if self.handlers.is_empty() {
+            log::info!("No event handlers registered for {}, not consuming", self.group_id);
+            return Ok(());
+        }
+
+        let consumer: Arc<StreamConsumer> = Arc::new(
+            self.client_config
+                .set("group.id", &self.group_id)
+                .set("enable.partition.eof", "false")
+                .set("enable.auto.commit", "true")
+                .set("enable.auto.offset.store", "false")
+                .create()?,
+        );
+        let mut topics: BTreeSet<String> =
+            self.handlers.keys().map(|(topic, _)| topic.clone()).collect();
+        topics.extend((1..=self.retry_delays.len()).map(|tier| retry_topic(&self.group_id, tier)));
+        consumer.subscribe(&topics.iter().map(String::as_str).collect::<Vec<_>>())?;
+        log::info!("Consumer group {} subscribed to {:?}", self.group_id, topics);
+
+        let routing = Arc::new(FailureRouting {
+            group_id: self.group_id.clone(),
+            producer: self.producer.clone(),
+            retry_delays: std::mem::take(&mut self.retry_delays),
+        });
+        let handlers = Arc::new(std::mem::take(&mut self.handlers));
+        let permits = Arc::new(Semaphore::new(self.max_concurrency));
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let mut partitions: HashMap<(String, i32), PartitionQueue> = HashMap::new();
+        let mut workers = JoinSet::new();
+        let cleanup = tokio::spawn(cleanup_processed_events(
+            self.db.clone(),
+            self.group_id.clone(),
+            self.dedup_retention,
+            stop_rx.clone(),
+        ));
+
+        tokio::pin!(shutdown);
+        loop {
+            let message = tokio::select! {
+                _ = &mut shutdown => break,
+                message = consumer.recv() => match message {
+                    Ok(message) => message.detach(),
+                    Err(e) => {
+                        log::warn!("Kafka consumer error in {}: {}", self.group_id, e);
+                        continue;
+                    },
+                },
+            };
+
+            let key = (message.topic().to_string(), message.partition());
+            let partition = partitions.entry(key).or_insert_with(|| {
+                let (records, queue) = mpsc::channel(PARTITION_QUEUE_SIZE);
+                let paused = Arc::new(AtomicBool::new(false));
+                let worker = PartitionWorker {
+                    consumer: consumer.clone(),
+                    db: self.db.clone(),
+                    group_id: self.group_id.clone(),
+                    handlers: handlers.clone(),
+                    routing: routing.clone(),
+                    permits: permits.clone(),
+                    paused: paused.clone(),
+                    stop: stop_rx.clone(),
+                };
+                workers.spawn(worker.run(queue));
+                PartitionQueue { records, paused }
+            });
+            let (topic, partition_id) = (message.topic().to_string(), message.partition());
+
+            tokio::select! {
+                _ = &mut shutdown => break,
+                sent = partition.records.send(message) => if sent.is_err() {
+                    log::error!("Partition worker of {} stopped, shutting down", self.group_id);
+                    break;
+                },
+            }
+
+            // A worker falling behind, e.g. waiting out a retry delay, must not hold back
+            // the other partitions, so its partition stops fetching until it catches up
+            if partition.records.capacity() < PARTITION_QUEUE_SIZE / 2
+                && !partition.paused.swap(true, Ordering::SeqCst)
+            {
+                if let Err(e) = consumer.pause(&partition_list(&topic, partition_id)) {
+                    log::warn!("Failed to pause {}/{}: {}", topic, partition_id, e);
+                    partition.paused.store(false, Ordering::SeqCst);
+                }
+            }
+        }
+
+        log::info!("Stopping consumer group {}", self.group_id);
+        drop(partitions);
+        let _ = stop_tx.send(true);
+        while workers.join_next().await.is_some() {}
+        let _ = cleanup.await;
+
+        if let Err(e) = consumer.commit_consumer_state(CommitMode::Sync) {
+            log::debug!("Final offset commit of {} skipped: {}", self.group_id, e);
+        }
+        consumer.unsubscribe();
+        Ok(())
+    }
+}
+
+struct PartitionQueue {
+    records: mpsc::Sender<OwnedMessage>,
+    paused: Arc<AtomicBool>,
+}
+
+fn partition_list(topic: &str, partition: i32) -> TopicPartitionList {
+    let mut list = TopicPartitionList::new();
+    list.add_partition(topic, partition);
+    list
+}
+
+/// Where failed records go next
+struct FailureRouting {
+    group_id: String,
+    producer: Arc<FutureProducer>,
+    retry_delays: Vec<Duration>,
+}
+
+impl FailureRouting {
+    /// Target topic, control headers and metric result for a failed record
+    fn route(&self, meta: &RecordMeta, error: &HandlerError) -> (String, Vec<(&'static str, String)>, &'static str) {
+        let mut headers = vec![
+            (ORIGINAL_TOPIC_HEADER, meta.topic.clone()),
+            (ORIGINAL_PARTITION_HEADER, meta.partition.to_string()),
+            (ORIGINAL_OFFSET_HEADER, meta.offset.to_string()),
+        ];
+
+        match error {
+            HandlerError::Transient(e) if meta.attempt < self.retry_delays.len() => {
+                let tier = meta.attempt + 1;
+                let delay = self.retry_delays[meta.attempt];
+                log::warn!(
+                    "Retrying {} at {}/{}@{} in {:?}: {}",
+                    meta.event_type, meta.topic, meta.partition, meta.offset, delay, e
+                );
+                let not_before = chrono::Utc::now().timestamp_millis() + delay.as_millis() as i64;
+                headers.push((RETRY_ATTEMPT_HEADER, tier.to_string()));
+                headers.push((RETRY_NOT_BEFORE_HEADER, not_before.to_string()));
+                (retry_topic(&self.group_id, tier), headers, "retry")
+            },
+            error => {
+                let (kind, detail) = match error {
+                    HandlerError::Transient(detail) => ("transient", detail),
+                    HandlerError::Permanent(detail) => ("permanent", detail),
+                };
+                log::error!(
+                    "Dead-lettering {} at {}/{}@{} after {} retries: {}",
+                    meta.event_type, meta.topic, meta.partition, meta.offset, meta.attempt, detail
+                );
+                headers.push((RETRY_ATTEMPT_HEADER, meta.attempt.to_string()));
+                headers.push((CONSUMER_GROUP_HEADER, self.group_id.clone()));
+                headers.push((ERROR_HEADER, detail.clone()));
+                headers.push((ERROR_KIND_HEADER, kind.to_string()));
+                headers.push((FAILED_AT_HEADER, chrono::Utc::now().to_rfc3339()));
+                (dead_letter_topic(&self.group_id), headers, "dead_letter")
+            },
+        }
+    }
+}
+
+/// Handles the records of one partition in order
+struct PartitionWorker {
+    consumer: Arc<StreamConsumer>,
+    db: Arc<DatabaseConnection>,
+    group_id: String,
+    handlers: Arc<HandlerMap>,
+    routing: Arc<FailureRouting>,
+    permits: Arc<Semaphore>,
+    paused: Arc<AtomicBool>,
+    stop: watch::Receiver<bool>,
+}
+
+impl PartitionWorker {
+    async fn run(self, mut queue: mpsc::Receiver<OwnedMessage>) {
+        while let Some(message) = queue.recv().await {
+            if queue.len() < PARTITION_QUEUE_SIZE / 4 && self.paused.swap(false, Ordering::SeqCst) {
+                let partitions = partition_list(message.topic(), message.partition());
+                if let Err(e) = self.consumer.resume(&partitions) {
+                    log::warn!("Failed to resume {}/{}: {}", message.topic(), message.partition(), e);
+                }
+            }
+
+            // Queued records are left uncommitted and redelivered after a restart
+            if !self.wait_until_due(&message).await {
+                return;
+            }
+            let Ok(_permit) = self.permits.acquire().await else {
+                return;
+            };
+            if *self.stop.borrow() {
+                return;
+            }
+
+            let meta = RecordMeta::from_message(&message);
+            let span = consume_span(&message, &meta);
+            // Events the handler emits stay correlated with the request that produced the record
+            let request_id = header_value(&message, REQUEST_ID_HEADER).map(str::to_string);
+            let process = self.process(&message, &meta).instrument(span);
+            if with_optional_request_id(request_id, process).await {
+                if let Err(e) =
+                    self.consumer.store_offset(message.topic(), message.partition(), message.offset())
+                {
+                    log::warn!("Error while storing offset of {}: {}", message.topic(), e);
+                }
+            }
+        }
+    }
+
+    /// Hold a record from a retry topic until its tier's delay has passed, false on shutdown
+    async fn wait_until_due(&self, message: &OwnedMessage) -> bool {
+        let not_before = header_value(message, RETRY_NOT_BEFORE_HEADER)
+            .and_then(|value| value.parse::<i64>().ok())
+            .unwrap_or_default();
+        let wait = not_before - chrono::Utc::now().timestamp_millis();
+        if wait <= 0 {
+            return true;
+        }
+
+        let mut stop = self.stop.clone();
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_millis(wait as u64)) => true,
+            _ = stop.wait_for(|stopped| *stopped) => false,
+        }
+    }
+
+    /// Whether the record is done with and its offset can be stored
+    async fn process(&self, message: &OwnedMessage, meta: &RecordMeta) -> bool {
+        let key = (meta.topic.clone(), meta.event_type.clone());
+        let Some(handler) = self.handlers.get(&key) else {
+            log::debug!("No handler for {} on {}, skipping", meta.event_type, meta.topic);
+            metrics().record_kafka_consume(&meta.topic, "skipped");
+            return true;
+        };
+
+        let payload = message.payload().unwrap_or_default();
+        let error = match self.handle(handler.as_ref(), payload, meta).await {
+            Ok(result) => {
+                metrics().record_kafka_consume(&meta.topic, result);
+                return true;
+            },
+            Err(error) => error,
+        };
+        let (topic, headers, result) = self.routing.route(meta, &error);
+
+        // The record is only acknowledged once forwarded, retrying while Kafka is unavailable
+        let mut stop = self.stop.clone();
+        let mut backoff = INITIAL_FORWARD_BACKOFF;
+        loop {
+            match forward(&self.routing.producer, &topic, message, headers.clone()).await {
+                Ok(()) => {
+                    metrics().record_kafka_consume(&meta.topic, result);
+                    return true;
+                },
+                Err(e) => {
+                    log::warn!("Failed to forward {} to {}, retrying in {:?}: {}", meta.event_type, topic, backoff, e);
+                    tokio::select! {
+                        _ = tokio::time::sleep(backoff) => {},
+                        _ = stop.wait_for(|stopped| *stopped) => return false,
+                    }
+                    backoff = (backoff * 2).min(MAX_FORWARD_BACKOFF);
+                },
+            }
+        }
+    }
+
+    /// One attempt in its own transaction, rolled back when the handler fails
+    ///
+    /// Returns the metric result, `duplicate` when the group already handled the event.
+    async fn handle(
+        &self,
+        handler: &dyn RecordHandler,
+        payload: &[u8],
+        meta: &RecordMeta,
+    ) -> Result<&'static str, HandlerError> {
+        let EnvelopeId { id } = serde_json::from_slice(payload)?;
+        let tx = self.db.begin().await?;
+        if !claim_event(&tx, &self.group_id, id, &meta.event_type).await? {
+            log::info!(
+                "Skipping duplicate {} {} at {}/{}@{}",
+                meta.event_type, id, meta.topic, meta.partition, meta.offset
+            );
+            return Ok("duplicate");
+        }
+        handler.handle_record(&tx, payload, meta).await?;
+        tx.commit().await?;
+        Ok("ok")
+    }
+}
+
+/// Consumer span continuing the trace of the request that produced the record
+fn consume_span(message: &OwnedMessage, meta: &RecordMeta) -> tracing::Span {
+    let headers: Vec<(&str, &str)> = message
+        .headers()
+        .map(|headers| {
+            headers
+                .iter()
+                .filter_map(|header| Some((header.key, std::str::from_utf8(header.value?).ok()?)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let request_id = headers
+        .iter()
+        .find(|(key, _)| *key == REQUEST_ID_HEADER)
+        .map(|(_, value)| *value)
+        .unwrap_or_default();
+
+    let span = tracing::info_span!(
+        "kafka.consume",
+        otel.name = %format!("{} process", message.topic()),
+        otel.kind = "consumer",
+        messaging.system = "kafka",
+        messaging.destination.name = %message.topic(),
+        messaging.kafka.partition = message.partition(),
+        messaging.kafka.offset = message.offset(),
+        event_type = %meta.event_type,
+        retry_attempt = meta.attempt,
+        request_id = %request_id,
+    );
+    set_span_parent(&span, context_from_pairs(headers.iter().copied()));
+    span
+}
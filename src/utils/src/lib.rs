@@ -1,4 +1,12 @@
 pub mod date_time;
 pub mod dir;
+pub mod events;
+pub mod internal_auth;
+pub mod kafka_config;
+pub mod kafka_consumer;
+pub mod metrics;
+pub mod rate_limit;
 pub mod redis_client;
+pub mod request_id;
+pub mod telemetry;
 
@@ -0,0 +1,237 @@
+use axum::extract::{MatchedPath, Request};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Route label for requests that matched no route, keeps label cardinality bounded
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+/// Prometheus collectors shared by the gateway and every service
+///
+/// All binaries register the same names and labels, distinguished by the constant
+/// `service` label, so one dashboard covers all of them.
+pub struct Metrics {
+    registry: Registry,
+    /// labels: method, route, status
+    pub http_requests_total: IntCounterVec,
+    /// labels: method, route, status
+    pub http_request_duration_seconds: HistogramVec,
+    /// labels: upstream, status
+    pub upstream_request_duration_seconds: HistogramVec,
+    /// labels: upstream, kind (timeout, connect, circuit_open, status)
+    pub upstream_errors_total: IntCounterVec,
+    /// labels: state (size, idle, max)
+    pub db_pool_connections: IntGaugeVec,
+    /// labels: operation, status (ok, error)
+    pub redis_command_duration_seconds: HistogramVec,
+    /// labels: topic, result (ok, error)
+    pub kafka_produce_total: IntCounterVec,
+    /// labels: topic, result (ok, retry, dead_letter, skipped)
+    pub kafka_consume_total: IntCounterVec,
+    /// labels: upstream, result (HIT, STALE, MISS, BYPASS)
+    pub response_cache_total: IntCounterVec,
+    /// labels: upstream, version (stable or the canary's version)
+    pub upstream_version_requests_total: IntCounterVec,
+    /// labels: upstream, result (match, status_mismatch, body_mismatch, shadow_error)
+    pub mirror_comparisons_total: IntCounterVec,
+    /// labels: endpoint, part, result (ok, error, timeout)
+    pub composite_parts_total: IntCounterVec,
+    /// labels: result (success, failure), reason
+    pub login_attempts_total: IntCounterVec,
+    pub account_lockouts_total: IntCounter,
+}
+
+impl Metrics {
+    fn new(service: &str) -> Result<Self, prometheus::Error> {
+        let labels = HashMap::from([("service".to_string(), service.to_string())]);
+        let registry = Registry::new_custom(None, Some(labels))?;
+
+        let metrics = Self {
+            http_requests_total: IntCounterVec::new(
+                Opts::new("http_requests_total", "HTTP requests handled"),
+                &["method", "route", "status"],
+            )?,
+            http_request_duration_seconds: HistogramVec::new(
+                HistogramOpts::new("http_request_duration_seconds", "HTTP request latency")
+                    .buckets(LATENCY_BUCKETS.to_vec()),
+                &["method", "route", "status"],
+            )?,
+            upstream_request_duration_seconds: HistogramVec::new(
+                HistogramOpts::new(
+                    "upstream_request_duration_seconds",
+                    "Latency of requests proxied to upstream services",
+                )
+                .buckets(LATENCY_BUCKETS.to_vec()),
+                &["upstream", "status"],
+            )?,
+            upstream_errors_total: IntCounterVec::new(
+                Opts::new("upstream_errors_total", "Failed requests to upstream services"),
+                &["upstream", "kind"],
+            )?,
+            db_pool_connections: IntGaugeVec::new(
+                Opts::new("db_pool_connections", "Database pool connections"),
+                &["state"],
+            )?,
+            redis_command_duration_seconds: HistogramVec::new(
+                HistogramOpts::new("redis_command_duration_seconds", "Redis command latency")
+                    .buckets(LATENCY_BUCKETS.to_vec()),
+                &["operation", "status"],
+            )?,
+            kafka_produce_total: IntCounterVec::new(
+                Opts::new("kafka_produce_total", "Kafka records produced"),
+                &["topic", "result"],
+            )?,
+            kafka_consume_total: IntCounterVec::new(
+                Opts::new("kafka_consume_total", "Kafka records handled by consumers"),
+                &["topic", "result"],
+            )?,
+            response_cache_total: IntCounterVec::new(
+                Opts::new("response_cache_total", "Gateway response cache lookups"),
+                &["upstream", "result"],
+            )?,
+            upstream_version_requests_total: IntCounterVec::new(
+                Opts::new(
+                    "upstream_version_requests_total",
+                    "Proxied requests by upstream version, for services with a canary",
+                ),
+                &["upstream", "version"],
+            )?,
+            mirror_comparisons_total: IntCounterVec::new(
+                Opts::new("mirror_comparisons_total", "Shadow responses compared with the primary"),
+                &["upstream", "result"],
+            )?,
+            composite_parts_total: IntCounterVec::new(
+                Opts::new("composite_parts_total", "Parts fetched for composite gateway endpoints"),
+                &["endpoint", "part", "result"],
+            )?,
+            login_attempts_total: IntCounterVec::new(
+                Opts::new("login_attempts_total", "Login attempts"),
+                &["result", "reason"],
+            )?,
+            account_lockouts_total: IntCounter::new(
+                "account_lockouts_total",
+                "Accounts locked after too many failed logins",
+            )?,
+            registry,
+        };
+
+        metrics.registry.register(Box::new(metrics.http_requests_total.clone()))?;
+        metrics.registry.register(Box::new(metrics.http_request_duration_seconds.clone()))?;
+        metrics.registry.register(Box::new(metrics.upstream_request_duration_seconds.clone()))?;
+        metrics.registry.register(Box::new(metrics.upstream_errors_total.clone()))?;
+        metrics.registry.register(Box::new(metrics.db_pool_connections.clone()))?;
+        metrics.registry.register(Box::new(metrics.redis_command_duration_seconds.clone()))?;
+        metrics.registry.register(Box::new(metrics.kafka_produce_total.clone()))?;
+        metrics.registry.register(Box::new(metrics.kafka_consume_total.clone()))?;
+        metrics.registry.register(Box::new(metrics.response_cache_total.clone()))?;
+        metrics.registry.register(Box::new(metrics.upstream_version_requests_total.clone()))?;
+        metrics.registry.register(Box::new(metrics.mirror_comparisons_total.clone()))?;
+        metrics.registry.register(Box::new(metrics.composite_parts_total.clone()))?;
+        metrics.registry.register(Box::new(metrics.login_attempts_total.clone()))?;
+        metrics.registry.register(Box::new(metrics.account_lockouts_total.clone()))?;
+        Ok(metrics)
+    }
+
+    pub fn observe_redis(&self, operation: &str, elapsed: Duration, ok: bool) {
+        self.redis_command_duration_seconds
+            .with_label_values(&[operation, if ok { "ok" } else { "error" }])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn record_kafka_produce(&self, topic: &str, ok: bool) {
+        self.kafka_produce_total
+            .with_label_values(&[topic, if ok { "ok" } else { "error" }])
+            .inc();
+    }
+
+    pub fn record_kafka_consume(&self, topic: &str, result: &str) {
+        self.kafka_consume_total.with_label_values(&[topic, result]).inc();
+    }
+
+    pub fn record_login(&self, success: bool, reason: &str) {
+        self.login_attempts_total
+            .with_label_values(&[if success { "success" } else { "failure" }, reason])
+            .inc();
+    }
+
+    pub fn set_db_pool(&self, size: u32, idle: usize, max: u32) {
+        self.db_pool_connections.with_label_values(&["size"]).set(size as i64);
+        self.db_pool_connections.with_label_values(&["idle"]).set(idle as i64);
+        self.db_pool_connections.with_label_values(&["max"]).set(max as i64);
+    }
+
+    /// Text exposition of every collector
+    pub fn render(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+/// Register the collectors with this binary's `service` label, call once at startup
+pub fn init_metrics(service: &str) {
+    let mut created = false;
+    METRICS.get_or_init(|| {
+        created = true;
+        Metrics::new(service).expect("metric definitions are valid")
+    });
+    if !created {
+        log::warn!("Metrics already initialized, ignoring service name {}", service);
+    }
+}
+
+/// Shared collectors, labelled `service="unknown"` if `init_metrics` was not called
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(|| Metrics::new("unknown").expect("metric definitions are valid"))
+}
+
+/// Middleware counting requests and their latency by matched route and status
+pub async fn track_http_metrics(request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    let labels = [method.as_str(), route.as_str(), status.as_str()];
+    let metrics = metrics();
+    metrics.http_requests_total.with_label_values(&labels).inc();
+    metrics
+        .http_request_duration_seconds
+        .with_label_values(&labels)
+        .observe(started.elapsed().as_secs_f64());
+    response
+}
+
+/// Prometheus text exposition response for a `/metrics` route
+pub fn metrics_response() -> Response {
+    match metrics().render() {
+        Ok(body) => (
+            [(header::CONTENT_TYPE, HeaderValue::from_static("text/plain; version=0.0.4"))],
+            body,
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to encode metrics: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        },
+    }
+}
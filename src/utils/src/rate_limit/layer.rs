@@ -0,0 +1,174 @@
+use crate::rate_limit::limiter::{RateLimitDecision, RedisRateLimiter};
+use crate::rate_limit::rules::RateLimitRules;
+use crate::request_id::current_request_id;
+use axum::body::Body;
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, Request, Response, StatusCode};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+const RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+/// Header a partner sends its API key in
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Who a request is counted against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identity {
+    User(String),
+    ApiKey(String),
+    Ip(String),
+    Unknown,
+}
+
+impl Identity {
+    /// The registered API key sent in `X-API-Key`, by its name in `known`
+    ///
+    /// `known` maps the hex SHA-256 of each key to its name, so raw keys are neither
+    /// configured nor written to Redis. Unregistered keys are ignored, otherwise a caller
+    /// could escape its IP limit by sending a new key with every request.
+    pub fn from_api_key(headers: &HeaderMap, known: &HashMap<String, String>) -> Option<Identity> {
+        let key = headers.get(API_KEY_HEADER)?.to_str().ok()?;
+        known.get(&api_key_fingerprint(key)).map(|name| Identity::ApiKey(name.clone()))
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        matches!(self, Identity::Ip(_) | Identity::Unknown)
+    }
+
+    fn key(&self) -> String {
+        match self {
+            Identity::User(id) => format!("user:{}", id),
+            Identity::ApiKey(key) => format!("api_key:{}", key),
+            Identity::Ip(ip) => format!("ip:{}", ip),
+            Identity::Unknown => "unknown".to_string(),
+        }
+    }
+}
+
+/// Hex SHA-256 of an API key, as registered in the rate limit config
+pub fn api_key_fingerprint(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Resolves the caller identity of a request, supplied by the service using the layer
+pub type IdentityResolver = Arc<dyn Fn(&Request<Body>) -> Identity + Send + Sync>;
+
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: RedisRateLimiter,
+    rules: Arc<RateLimitRules>,
+    resolver: IdentityResolver,
+}
+
+impl RateLimitLayer {
+    pub fn new(limiter: RedisRateLimiter, rules: RateLimitRules, resolver: IdentityResolver) -> Self {
+        Self {
+            limiter,
+            rules: Arc::new(rules),
+            resolver,
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            limiter: self.limiter.clone(),
+            rules: self.rules.clone(),
+            resolver: self.resolver.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    limiter: RedisRateLimiter,
+    rules: Arc<RateLimitRules>,
+    resolver: IdentityResolver,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // Take the service that was driven to readiness and leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let limiter = self.limiter.clone();
+        let rules = self.rules.clone();
+        let identity = (self.resolver)(&request);
+
+        Box::pin(async move {
+            let (bucket, policy) = rules.policy_for(
+                request.method().as_str(),
+                request.uri().path(),
+                identity.is_anonymous(),
+            );
+            let key = format!("{}:{}", bucket, identity.key());
+
+            let decision = match limiter.hit(&key, policy).await {
+                Ok(decision) => decision,
+                Err(e) => {
+                    // Fail open: an unavailable Redis must not take the gateway down with it
+                    log::warn!("Rate limiter unavailable, allowing request: {}", e);
+                    return inner.call(request).await;
+                },
+            };
+
+            if !decision.allowed {
+                log::info!("Rate limit exceeded for {} on bucket {}", identity.key(), bucket);
+                return Ok(too_many_requests(&decision));
+            }
+
+            let mut response = inner.call(request).await?;
+            insert_rate_limit_headers(response.headers_mut(), &decision);
+            Ok(response)
+        })
+    }
+}
+
+fn insert_rate_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(RATE_LIMIT_LIMIT, HeaderValue::from(decision.limit));
+    headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from(decision.remaining));
+    headers.insert(RATE_LIMIT_RESET, HeaderValue::from(reset_secs(decision)));
+}
+
+fn reset_secs(decision: &RateLimitDecision) -> u64 {
+    decision.reset_after.as_millis().div_ceil(1000) as u64
+}
+
+fn too_many_requests(decision: &RateLimitDecision) -> Response<Body> {
+    let body = serde_json::json!({
+        "code_message": "TooManyRequests",
+        "message": { "detail": "Rate limit exceeded, please retry later" },
+        "request_id": current_request_id(),
+    });
+
+    let mut response = Response::new(Body::from(body.to_string()));
+    *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    headers.insert(header::RETRY_AFTER, HeaderValue::from(reset_secs(decision).max(1)));
+    insert_rate_limit_headers(headers, decision);
+    response
+}
@@ -0,0 +1,87 @@
+use crate::rate_limit::rules::RateLimitPolicy;
+use crate::redis_client::{RedisConnectionPool, RedisResult};
+use once_cell::sync::Lazy;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// Sliding-window log kept in a sorted set scored by request time in milliseconds.
+/// Returns `{allowed, remaining, reset_after_ms}`.
+static SLIDING_WINDOW_SCRIPT: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+local now = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+local limit = tonumber(ARGV[3])
+
+redis.call('ZREMRANGEBYSCORE', KEYS[1], 0, now - window)
+local count = redis.call('ZCARD', KEYS[1])
+local allowed = 0
+if count < limit then
+    redis.call('ZADD', KEYS[1], now, ARGV[4])
+    count = count + 1
+    allowed = 1
+end
+redis.call('PEXPIRE', KEYS[1], window)
+
+local reset = window
+local oldest = redis.call('ZRANGE', KEYS[1], 0, 0, 'WITHSCORES')
+if oldest[2] then
+    reset = tonumber(oldest[2]) + window - now
+end
+return {allowed, limit - count, reset}
+"#,
+    )
+});
+
+#[derive(Debug, Clone)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u64,
+    pub remaining: u64,
+    /// Time until the oldest request in the window expires
+    pub reset_after: Duration,
+}
+
+/// Distributed sliding-window rate limiter shared by every gateway instance through Redis
+#[derive(Clone)]
+pub struct RedisRateLimiter {
+    redis: RedisConnectionPool,
+}
+
+impl RedisRateLimiter {
+    pub fn new(redis: &RedisConnectionPool) -> Self {
+        Self {
+            redis: redis.with_prefix("rate_limit"),
+        }
+    }
+
+    /// Record a hit for `key` and report whether it fits in the policy
+    pub async fn hit(&self, key: &str, policy: &RateLimitPolicy) -> RedisResult<RateLimitDecision> {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let window_ms = policy.window().as_millis();
+
+        let (allowed, remaining, reset_ms): (i64, i64, i64) = self
+            .redis
+            .eval_script(
+                &SLIDING_WINDOW_SCRIPT,
+                key,
+                &[
+                    now_ms.to_string(),
+                    window_ms.to_string(),
+                    policy.limit.to_string(),
+                    format!("{}-{}", now_ms, Uuid::new_v4()),
+                ],
+            )
+            .await?;
+
+        Ok(RateLimitDecision {
+            allowed: allowed == 1,
+            limit: policy.limit,
+            remaining: remaining.max(0) as u64,
+            reset_after: Duration::from_millis(reset_ms.max(0) as u64),
+        })
+    }
+}
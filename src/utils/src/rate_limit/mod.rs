@@ -0,0 +1,8 @@
+pub mod layer;
+pub mod limiter;
+pub mod rules;
+
+// Re-export commonly used types
+pub use layer::{api_key_fingerprint, Identity, IdentityResolver, RateLimitLayer, RateLimitService, API_KEY_HEADER};
+pub use limiter::{RateLimitDecision, RedisRateLimiter};
+pub use rules::{RateLimitPolicy, RateLimitRule, RateLimitRules};
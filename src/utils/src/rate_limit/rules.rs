@@ -0,0 +1,63 @@
+use serde::Deserialize;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitPolicy {
+    /// Requests allowed per window
+    pub limit: u64,
+    pub window_secs: u64,
+}
+
+impl RateLimitPolicy {
+    pub fn new(limit: u64, window_secs: u64) -> Self {
+        Self { limit, window_secs }
+    }
+
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs.max(1))
+    }
+}
+
+/// Limits applied to requests whose path starts with `path_prefix`
+///
+/// Identified callers (user ID or API key) get `authenticated`, everyone else is
+/// keyed by client IP and gets `anonymous`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitRule {
+    pub name: String,
+    pub path_prefix: String,
+    /// Restrict the rule to these methods, empty means every method
+    #[serde(default)]
+    pub methods: Vec<String>,
+    pub authenticated: RateLimitPolicy,
+    pub anonymous: RateLimitPolicy,
+}
+
+impl RateLimitRule {
+    fn matches(&self, method: &str, path: &str) -> bool {
+        path.starts_with(&self.path_prefix)
+            && (self.methods.is_empty()
+                || self.methods.iter().any(|m| m.eq_ignore_ascii_case(method)))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitRules {
+    /// Checked in order, the first matching rule wins
+    #[serde(default)]
+    pub rules: Vec<RateLimitRule>,
+    pub default_authenticated: RateLimitPolicy,
+    pub default_anonymous: RateLimitPolicy,
+}
+
+impl RateLimitRules {
+    /// Resolve the bucket name and policy for a request
+    pub fn policy_for(&self, method: &str, path: &str, anonymous: bool) -> (&str, &RateLimitPolicy) {
+        match self.rules.iter().find(|rule| rule.matches(method, path)) {
+            Some(rule) if anonymous => (&rule.name, &rule.anonymous),
+            Some(rule) => (&rule.name, &rule.authenticated),
+            None if anonymous => ("default", &self.default_anonymous),
+            None => ("default", &self.default_authenticated),
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use super::error::{RedisError, RedisResult};
 use redis::{aio::ConnectionManager, Client};
-use std::time::Duration;
+use crate::metrics::metrics;
+use std::future::Future;
+use std::time::{Duration, Instant};
 
 /// Redis connection pool wrapper using redis crate
 /// Uses ConnectionManager which automatically handles reconnection and is cheap to clone
@@ -40,28 +42,48 @@ impl RedisConnectionPool {
         }
     }
 
+    #[tracing::instrument(
+        name = "redis",
+        skip_all,
+        fields(db.system = "redis", db.operation = "PING", key_prefix = %self.key_prefix)
+    )]
     pub async fn ping(&self) -> RedisResult<String> {
         let mut conn = self.connection.clone();
-        let result: String = redis::cmd("PING").query_async(&mut conn).await?;
+        let result: String = observed("PING", redis::cmd("PING").query_async(&mut conn)).await?;
         Ok(result)
     }
 
+    #[tracing::instrument(
+        name = "redis",
+        skip_all,
+        fields(db.system = "redis", db.operation = "SETEX", key_prefix = %self.key_prefix)
+    )]
     pub async fn set(&self, key: &str, value: &str, expire: Duration) -> RedisResult<()> {
         use redis::AsyncCommands;
         let mut conn = self.connection.clone();
         let prefixed_key = self.prefixed_key(key);
-        conn.set_ex(&prefixed_key, value, expire.as_secs()).await?;
+        observed("SETEX", conn.set_ex::<_, _, ()>(&prefixed_key, value, expire.as_secs())).await?;
         Ok(())
     }
 
+    #[tracing::instrument(
+        name = "redis",
+        skip_all,
+        fields(db.system = "redis", db.operation = "GET", key_prefix = %self.key_prefix)
+    )]
     pub async fn get(&self, key: &str) -> RedisResult<Option<String>> {
         use redis::AsyncCommands;
         let mut conn = self.connection.clone();
         let prefixed_key = self.prefixed_key(key);
-        let value: Option<String> = conn.get(&prefixed_key).await?;
+        let value: Option<String> = observed("GET", conn.get(&prefixed_key)).await?;
         Ok(value)
     }
 
+    #[tracing::instrument(
+        name = "redis",
+        skip_all,
+        fields(db.system = "redis", db.operation = "GET", key_prefix = %self.key_prefix)
+    )]
     pub async fn get_key<T>(&self, key: &str) -> RedisResult<Option<T>>
     where
         T: redis::FromRedisValue,
@@ -69,15 +91,20 @@ impl RedisConnectionPool {
         use redis::AsyncCommands;
         let mut conn = self.connection.clone();
         let prefixed_key = self.prefixed_key(key);
-        let value: Option<T> = conn.get(&prefixed_key).await?;
+        let value: Option<T> = observed("GET", conn.get(&prefixed_key)).await?;
         Ok(value)
     }
 
+    #[tracing::instrument(
+        name = "redis",
+        skip_all,
+        fields(db.system = "redis", db.operation = "DEL", key_prefix = %self.key_prefix)
+    )]
     pub async fn delete_key(&self, key: &str) -> RedisResult<bool> {
         use redis::AsyncCommands;
         let mut conn = self.connection.clone();
         let prefixed_key = self.prefixed_key(key);
-        let deleted: bool = conn.del(&prefixed_key).await?;
+        let deleted: bool = observed("DEL", conn.del(&prefixed_key)).await?;
         Ok(deleted)
     }
 
@@ -103,6 +130,11 @@ impl RedisConnectionPool {
         self.set(key, &value_str, Duration::from_secs(ttl_seconds as u64)).await
     }
 
+    #[tracing::instrument(
+        name = "redis",
+        skip_all,
+        fields(db.system = "redis", db.operation = "SCAN", key_prefix = %self.key_prefix)
+    )]
     pub async fn scan(
         &self,
         pattern: &str,
@@ -116,14 +148,17 @@ impl RedisConnectionPool {
         let scan_count = count.unwrap_or(100);
 
         loop {
-            let (new_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
-                .arg(cursor)
-                .arg("MATCH")
-                .arg(&prefixed_pattern)
-                .arg("COUNT")
-                .arg(scan_count)
-                .query_async(&mut conn)
-                .await?;
+            let (new_cursor, keys): (u64, Vec<String>) = observed(
+                "SCAN",
+                redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(&prefixed_pattern)
+                    .arg("COUNT")
+                    .arg(scan_count)
+                    .query_async(&mut conn),
+            )
+            .await?;
 
             all_keys.extend(keys);
             cursor = new_cursor;
@@ -155,4 +190,37 @@ impl RedisConnectionPool {
         let value_json = serde_json::to_value(value)?;
         self.serialize_and_set_key_with_expiry(key, &value_json, ttl_seconds).await
     }
+
+    /// Run a Lua script atomically against a single (prefixed) key
+    #[tracing::instrument(
+        name = "redis",
+        skip_all,
+        fields(db.system = "redis", db.operation = "EVALSHA", key_prefix = %self.key_prefix)
+    )]
+    pub async fn eval_script<T>(
+        &self,
+        script: &redis::Script,
+        key: &str,
+        args: &[String],
+    ) -> RedisResult<T>
+    where
+        T: redis::FromRedisValue,
+    {
+        let mut conn = self.connection.clone();
+        let prefixed_key = self.prefixed_key(key);
+        let mut invocation = script.key(prefixed_key);
+        for arg in args {
+            invocation.arg(arg);
+        }
+        let value: T = observed("EVALSHA", invocation.invoke_async(&mut conn)).await?;
+        Ok(value)
+    }
+}
+
+/// Await a Redis command, recording its latency and outcome
+async fn observed<T, E>(operation: &str, command: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+    let started = Instant::now();
+    let result = command.await;
+    metrics().observe_redis(operation, started.elapsed(), result.is_ok());
+    result
 }
@@ -0,0 +1,81 @@
+use axum::extract::Request;
+use axum::http::{HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::future::Future;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+use uuid::Uuid;
+
+/// Header carrying the correlation ID across the gateway, services and Kafka records
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied ID that is accepted, anything else is replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Request ID of the request being handled by the current task
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Run `future` with `request_id` as the current request ID, for work spawned outside the request task
+pub async fn with_request_id<F: Future>(request_id: String, future: F) -> F::Output {
+    REQUEST_ID.scope(request_id, future).await
+}
+
+/// Like `with_request_id`, running `future` as is when there is no ID to carry over
+pub async fn with_optional_request_id<F: Future>(request_id: Option<String>, future: F) -> F::Output {
+    match request_id {
+        Some(request_id) => with_request_id(request_id, future).await,
+        None => future.await,
+    }
+}
+
+/// `tokio::spawn` keeping the current request ID and span, which a spawned task does not inherit
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(with_optional_request_id(current_request_id(), future).in_current_span())
+}
+
+/// Request ID stored in the request extensions by `propagate_request_id`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// Middleware accepting the caller's `x-request-id` or generating one
+///
+/// The ID is written back to the request and response headers and is available
+/// through `current_request_id` while the request is handled.
+pub async fn propagate_request_id(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let header_value = HeaderValue::from_str(&request_id).expect("request ID is a valid header value");
+    request
+        .headers_mut()
+        .insert(HeaderName::from_static(REQUEST_ID_HEADER), header_value.clone());
+    request.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let mut response = REQUEST_ID.scope(request_id, next.run(request)).await;
+    response
+        .headers_mut()
+        .insert(HeaderName::from_static(REQUEST_ID_HEADER), header_value);
+    response
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+}
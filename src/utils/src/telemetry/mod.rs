@@ -0,0 +1,252 @@
+use crate::request_id::REQUEST_ID_HEADER;
+use axum::http::{HeaderMap, Request};
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::TracerProvider;
+use opentelemetry::{global, Context};
+use opentelemetry_http::{HeaderExtractor, HeaderInjector};
+use opentelemetry_otlp::{ExporterBuildError, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Span, Subscriber};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::filter::{filter_fn, EnvFilter, ParseError};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::{SubscriberInitExt, TryInitError};
+use tracing_subscriber::Layer;
+
+/// Events with this target are written as their bare message, one JSON object per line
+pub const ACCESS_LOG_TARGET: &str = "access_log";
+
+/// Logging and trace export settings, read from the `[telemetry]` section of each profile
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    /// Console log filter, in `RUST_LOG` syntax
+    pub log_filter: String,
+    /// Write console logs as JSON instead of plain text
+    pub json_logs: bool,
+    /// Export spans to an OpenTelemetry collector over OTLP/HTTP
+    pub otlp_enabled: bool,
+    pub otlp_endpoint: String,
+    /// Filter for exported spans; sea-orm instruments its queries at trace level
+    pub trace_filter: String,
+    /// Fraction of new traces sampled, requests with a sampled parent are always kept
+    pub sample_ratio: f64,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            log_filter: "info".to_string(),
+            json_logs: false,
+            otlp_enabled: false,
+            otlp_endpoint: "http://localhost:4318/v1/traces".to_string(),
+            trace_filter: "info,sea_orm=trace".to_string(),
+            sample_ratio: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TelemetryError {
+    #[error("Invalid filter: {0}")]
+    Filter(#[from] ParseError),
+    #[error("Failed to build OTLP exporter: {0}")]
+    Exporter(#[from] ExporterBuildError),
+    #[error("Failed to install subscriber: {0}")]
+    Init(#[from] TryInitError),
+}
+
+/// Flushes pending spans when dropped, keep it alive for the lifetime of `main`
+pub struct TelemetryGuard {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to shut down tracer provider: {e}");
+            }
+        }
+    }
+}
+
+/// Install the global `tracing` subscriber, also capturing `log` records
+pub fn init_telemetry(
+    service_name: &str,
+    config: &TelemetryConfig,
+) -> Result<TelemetryGuard, TelemetryError> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let not_access_log = filter_fn(|metadata| metadata.target() != ACCESS_LOG_TARGET);
+    let console = if config.json_logs {
+        tracing_subscriber::fmt::layer().json().boxed()
+    } else {
+        tracing_subscriber::fmt::layer().with_target(true).boxed()
+    }
+    .with_filter(EnvFilter::try_new(&config.log_filter)?)
+    .with_filter(not_access_log);
+
+    let access_log = tracing_subscriber::fmt::layer()
+        .event_format(MessageOnly)
+        .with_filter(filter_fn(|metadata| metadata.target() == ACCESS_LOG_TARGET));
+
+    let provider = if config.otlp_enabled {
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .with_endpoint(&config.otlp_endpoint)
+            .build()?;
+        Some(
+            SdkTracerProvider::builder()
+                .with_batch_exporter(exporter)
+                .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+                    config.sample_ratio,
+                ))))
+                .with_resource(Resource::builder().with_service_name(service_name.to_string()).build())
+                .build(),
+        )
+    } else {
+        None
+    };
+
+    let otel = match &provider {
+        Some(provider) => Some(
+            tracing_opentelemetry::layer()
+                .with_tracer(provider.tracer(service_name.to_string()))
+                .with_filter(EnvFilter::try_new(&config.trace_filter)?),
+        ),
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(console)
+        .with(access_log)
+        .with(otel)
+        .try_init()?;
+
+    if provider.is_some() {
+        tracing::info!("Exporting traces to {}", config.otlp_endpoint);
+    }
+    Ok(TelemetryGuard { provider })
+}
+
+/// Server span for an incoming HTTP request, continuing the caller's W3C trace if any
+pub fn http_request_span<B>(request: &Request<B>) -> Span {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    let span = tracing::info_span!(
+        "http.request",
+        otel.name = %format!("{} {}", request.method(), request.uri().path()),
+        otel.kind = "server",
+        http.request.method = %request.method(),
+        url.path = %request.uri().path(),
+        request_id = %request_id,
+    );
+    set_span_parent(&span, extract_context(request.headers()));
+    span
+}
+
+/// Trace context sent by the caller in `traceparent`/`tracestate`
+pub fn extract_context(headers: &HeaderMap) -> Context {
+    global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)))
+}
+
+/// Write the current span's trace context into outgoing HTTP headers
+pub fn inject_context(headers: &mut HeaderMap) {
+    let context = Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers))
+    });
+}
+
+/// Current span's trace context as key/value pairs, for message headers
+pub fn trace_context_pairs() -> Vec<(String, String)> {
+    let context = Span::current().context();
+    let mut carrier = PairCarrier::default();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut carrier));
+    carrier.0.into_iter().collect()
+}
+
+/// Trace context carried in message headers, the counterpart of `trace_context_pairs`
+pub fn context_from_pairs<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Context {
+    let carrier = PairCarrier(
+        pairs
+            .into_iter()
+            .map(|(key, value)| (key.to_lowercase(), value.to_string()))
+            .collect(),
+    );
+    global::get_text_map_propagator(|propagator| propagator.extract(&carrier))
+}
+
+/// Make `span` a child of a remote trace context, e.g. from `context_from_pairs`
+pub fn set_span_parent(span: &Span, parent: Context) {
+    if let Err(e) = span.set_parent(parent) {
+        tracing::debug!("Failed to set span parent: {}", e);
+    }
+}
+
+#[derive(Default)]
+struct PairCarrier(HashMap<String, String>);
+
+impl Injector for PairCarrier {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_string(), value);
+    }
+}
+
+impl Extractor for PairCarrier {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(String::as_str).collect()
+    }
+}
+
+/// Writes only the event message, for records that are already formatted
+struct MessageOnly;
+
+impl<S, N> FormatEvent<S, N> for MessageOnly
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut visitor = MessageVisitor { writer: &mut writer, result: Ok(()) };
+        event.record(&mut visitor);
+        visitor.result?;
+        writeln!(writer)
+    }
+}
+
+struct MessageVisitor<'a, 'w> {
+    writer: &'a mut Writer<'w>,
+    result: fmt::Result,
+}
+
+impl Visit for MessageVisitor<'_, '_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.result = write!(self.writer, "{:?}", value);
+        }
+    }
+}
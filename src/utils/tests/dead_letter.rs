@@ -0,0 +1,29 @@
+//! Dead letters as the admin API shows them
+
+use serde_json::json;
+use std::collections::BTreeMap;
+use utils::kafka_consumer::DeadLetter;
+
+#[test]
+fn payload_is_kept_byte_for_byte_and_serialized_as_base64() {
+    // Not valid UTF-8, a lossy conversion would replace it with U+FFFD
+    let payload = vec![0x0a, 0xff, 0xfe, 0x00, b'{'];
+    let letter = DeadLetter {
+        partition: 0,
+        offset: 42,
+        key: Some("order-7".to_string()),
+        original_topic: Some("order-events".to_string()),
+        event_type: None,
+        error: None,
+        error_kind: None,
+        attempts: None,
+        failed_at: None,
+        headers: BTreeMap::new(),
+        payload: payload.clone(),
+    };
+
+    let json = serde_json::to_value(&letter).unwrap();
+
+    assert_eq!(json["payload"], json!("Cv/+AHs="));
+    assert_eq!(letter.payload, payload);
+}
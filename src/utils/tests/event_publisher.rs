@@ -0,0 +1,61 @@
+//! The publishers tests and local runs use instead of Kafka
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use utils::events::publisher::{EventPublisher, InMemoryEventPublisher, NoopEventPublisher, OutgoingEvent};
+use utils::events::{DomainEvent, EventEnvelope};
+use utils::kafka_consumer::EVENT_TYPE_HEADER;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+struct UserActivated {
+    user_id: i64,
+}
+
+impl DomainEvent for UserActivated {
+    const EVENT_TYPE: &'static str = "user.activated";
+    const SCHEMA_VERSION: u32 = 1;
+    const TOPIC: &'static str = "user_activated";
+
+    fn aggregate_id(&self) -> String {
+        self.user_id.to_string()
+    }
+}
+
+fn outgoing<E: DomainEvent>(event: E) -> OutgoingEvent {
+    let envelope = EventEnvelope::new("test", event);
+    OutgoingEvent {
+        topic: E::TOPIC.to_string(),
+        key: envelope.aggregateid.clone(),
+        payload: serde_json::to_string(&envelope).unwrap(),
+        headers: vec![(EVENT_TYPE_HEADER.to_string(), E::EVENT_TYPE.to_string())],
+    }
+}
+
+#[tokio::test]
+async fn in_memory_publisher_captures_events_in_order() {
+    let publisher = InMemoryEventPublisher::new();
+    publisher.publish(&outgoing(UserActivated { user_id: 1 })).await.unwrap();
+    publisher
+        .publish(&OutgoingEvent { topic: "other".to_string(), ..outgoing(UserActivated { user_id: 2 }) })
+        .await
+        .unwrap();
+    publisher.publish(&outgoing(UserActivated { user_id: 3 })).await.unwrap();
+
+    assert_eq!(publisher.published().len(), 3);
+    let activated: Vec<i64> =
+        publisher.published_events::<UserActivated>().iter().map(|envelope| envelope.data.user_id).collect();
+    assert_eq!(activated, vec![1, 3]);
+
+    publisher.clear();
+    assert!(publisher.published().is_empty());
+}
+
+#[tokio::test]
+async fn only_the_in_memory_publisher_exposes_captures() {
+    let in_memory: Box<dyn EventPublisher> = Box::new(InMemoryEventPublisher::new());
+    let noop: Box<dyn EventPublisher> = Box::new(NoopEventPublisher);
+
+    noop.publish(&outgoing(UserActivated { user_id: 1 })).await.unwrap();
+    assert!(noop.in_memory().is_none());
+    assert!(in_memory.in_memory().is_some());
+}
@@ -0,0 +1,209 @@
+//! `EventReplay` against librdkafka's in-process mock cluster and a mock database
+//!
+//! `ReplayStart::Time` is not covered: the mock broker does not look up offsets by timestamp.
+
+use async_trait::async_trait;
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::mocking::MockCluster;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use sea_orm::{DatabaseBackend, DatabaseConnection, DatabaseTransaction, MockDatabase};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use utils::events::EventEnvelope;
+use utils::kafka_consumer::{
+    EventHandler, EventReplay, HandlerError, Projection, RecordMeta, ReplayError, ReplayStart,
+    EVENT_TYPE_HEADER,
+};
+
+const TOPIC: &str = "user_activated";
+const EVENT_TYPE: &str = "user.activated";
+
+#[derive(Deserialize)]
+struct UserActivated {
+    user_id: i64,
+}
+
+/// Records the users it saw, failing for negative IDs
+struct Recorder {
+    seen: Arc<Mutex<Vec<i64>>>,
+}
+
+#[async_trait]
+impl EventHandler for Recorder {
+    type Event = UserActivated;
+
+    async fn handle(
+        &self,
+        _tx: &DatabaseTransaction,
+        event: EventEnvelope<UserActivated>,
+        _meta: &RecordMeta,
+    ) -> Result<(), HandlerError> {
+        if event.data.user_id < 0 {
+            return Err(HandlerError::Permanent(format!("invalid user {}", event.data.user_id)));
+        }
+        self.seen.lock().unwrap().push(event.data.user_id);
+        Ok(())
+    }
+}
+
+fn envelope(event_type: &str, user_id: i64) -> String {
+    serde_json::json!({
+        "specversion": "1.0",
+        "id": uuid::Uuid::new_v4(),
+        "type": event_type,
+        "source": "api-gateway",
+        "time": chrono::Utc::now(),
+        "datacontenttype": "application/json",
+        "schemaversion": 1,
+        "aggregateid": user_id.to_string(),
+        "data": { "user_id": user_id }
+    })
+    .to_string()
+}
+
+struct Harness {
+    _cluster: MockCluster<'static, rdkafka::producer::DefaultProducerContext>,
+    client_config: ClientConfig,
+    producer: FutureProducer,
+    seen: Arc<Mutex<Vec<i64>>>,
+}
+
+impl Harness {
+    fn new(partitions: i32) -> Self {
+        let cluster = MockCluster::new(1).unwrap();
+        cluster.create_topic(TOPIC, partitions, 1).unwrap();
+        let mut client_config = ClientConfig::new();
+        client_config.set("bootstrap.servers", cluster.bootstrap_servers());
+        let producer = client_config.create().unwrap();
+        Self { _cluster: cluster, client_config, producer, seen: Arc::default() }
+    }
+
+    async fn produce(&self, partition: i32, event_type: &str, payload: &str) {
+        let headers = OwnedHeaders::new().insert(Header { key: EVENT_TYPE_HEADER, value: Some(event_type) });
+        let record = FutureRecord::<str, str>::to(TOPIC).partition(partition).payload(payload).headers(headers);
+        self.producer.send(record, Duration::from_secs(5)).await.unwrap();
+    }
+
+    fn projection(&self) -> Projection {
+        Projection::new("test").register(TOPIC, EVENT_TYPE, Recorder { seen: self.seen.clone() })
+    }
+
+    fn seen(&self) -> Vec<i64> {
+        let mut seen = self.seen.lock().unwrap().clone();
+        seen.sort();
+        seen
+    }
+}
+
+fn mock_db() -> Arc<DatabaseConnection> {
+    Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection())
+}
+
+/// How many replayed transactions ended with `statement`, e.g. `COMMIT`
+fn ended_with(db: Arc<DatabaseConnection>, statement: &str) -> usize {
+    let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+    log.iter().filter(|transaction| format!("{:?}", transaction).contains(statement)).count()
+}
+
+#[tokio::test]
+async fn replays_every_partition_from_the_beginning() {
+    let harness = Harness::new(2);
+    harness.produce(0, EVENT_TYPE, &envelope(EVENT_TYPE, 1)).await;
+    harness.produce(1, EVENT_TYPE, &envelope(EVENT_TYPE, 2)).await;
+    harness.produce(1, "user.other", &envelope("user.other", 3)).await;
+    harness.produce(0, EVENT_TYPE, &envelope(EVENT_TYPE, 4)).await;
+
+    let db = mock_db();
+    let report = EventReplay::new(harness.client_config.clone(), db.clone())
+        .run(&harness.projection(), TOPIC, ReplayStart::Beginning)
+        .await
+        .unwrap();
+
+    assert_eq!((report.read, report.handled, report.skipped), (4, 3, 1));
+    assert!(report.failed.is_empty());
+    assert_eq!(harness.seen(), vec![1, 2, 4]);
+    assert_eq!(ended_with(db, "COMMIT"), 3);
+}
+
+#[tokio::test]
+async fn starts_at_the_given_offset_of_one_partition() {
+    let harness = Harness::new(2);
+    for user_id in 1..=4 {
+        harness.produce(0, EVENT_TYPE, &envelope(EVENT_TYPE, user_id)).await;
+    }
+    harness.produce(1, EVENT_TYPE, &envelope(EVENT_TYPE, 5)).await;
+
+    let report = EventReplay::new(harness.client_config.clone(), mock_db())
+        .with_partition(Some(0))
+        .run(&harness.projection(), TOPIC, ReplayStart::Offset(2))
+        .await
+        .unwrap();
+
+    assert_eq!((report.read, report.handled), (2, 2));
+    assert_eq!(harness.seen(), vec![3, 4]);
+}
+
+#[tokio::test]
+async fn dry_run_rolls_back_every_record() {
+    let harness = Harness::new(1);
+    harness.produce(0, EVENT_TYPE, &envelope(EVENT_TYPE, 1)).await;
+    harness.produce(0, EVENT_TYPE, &envelope(EVENT_TYPE, 2)).await;
+
+    let db = mock_db();
+    let report = EventReplay::new(harness.client_config.clone(), db.clone())
+        .with_dry_run(true)
+        .run(&harness.projection(), TOPIC, ReplayStart::Beginning)
+        .await
+        .unwrap();
+
+    assert!(report.dry_run);
+    assert_eq!(report.handled, 2);
+    assert_eq!(ended_with(db, "ROLLBACK"), 2);
+}
+
+#[tokio::test]
+async fn dry_run_is_refused_for_projections_with_side_effects() {
+    let harness = Harness::new(1);
+    harness.produce(0, EVENT_TYPE, &envelope(EVENT_TYPE, 1)).await;
+
+    let result = EventReplay::new(harness.client_config.clone(), mock_db())
+        .with_dry_run(true)
+        .run(&harness.projection().with_side_effects(), TOPIC, ReplayStart::Beginning)
+        .await;
+
+    assert!(matches!(result, Err(ReplayError::DryRunWithSideEffects(name)) if name == "test"));
+    assert!(harness.seen().is_empty(), "no handler runs");
+}
+
+#[tokio::test]
+async fn failed_records_are_reported_and_skipped() {
+    let harness = Harness::new(1);
+    harness.produce(0, EVENT_TYPE, &envelope(EVENT_TYPE, 1)).await;
+    harness.produce(0, EVENT_TYPE, "not json").await;
+    harness.produce(0, EVENT_TYPE, &envelope(EVENT_TYPE, -1)).await;
+    harness.produce(0, EVENT_TYPE, &envelope(EVENT_TYPE, 2)).await;
+
+    let report = EventReplay::new(harness.client_config.clone(), mock_db())
+        .run(&harness.projection(), TOPIC, ReplayStart::Beginning)
+        .await
+        .unwrap();
+
+    assert_eq!((report.read, report.handled), (4, 2));
+    let failed: Vec<i64> = report.failed.iter().map(|failure| failure.offset).collect();
+    assert_eq!(failed, vec![1, 2]);
+    assert_eq!(harness.seen(), vec![1, 2]);
+}
+
+#[tokio::test]
+async fn empty_topic_reads_nothing() {
+    let harness = Harness::new(1);
+
+    let report = EventReplay::new(harness.client_config.clone(), mock_db())
+        .run(&harness.projection(), TOPIC, ReplayStart::Beginning)
+        .await
+        .unwrap();
+
+    assert_eq!(report.read, 0);
+}
@@ -0,0 +1,36 @@
+//! Kafka settings shared by the gateway and the services
+
+use std::path::PathBuf;
+use utils::kafka_config::KafkaConfig;
+
+fn read(settings: PathBuf) -> KafkaConfig {
+    config::Config::builder()
+        .add_source(config::File::from(settings.clone()))
+        .build()
+        .and_then(|config| config.get::<KafkaConfig>("kafka"))
+        .unwrap_or_else(|e| panic!("{}: {}", settings.display(), e))
+}
+
+#[test]
+fn every_service_profile_points_at_a_broker() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../..");
+
+    for (dir, client_id) in [(".", "api-gateway"), ("order-service", "order-service"), ("product-service", "product-service")] {
+        // prod reads its SASL credentials from the environment
+        for profile in ["dev", "local", "stag", "test"] {
+            let config = read(root.join(dir).join("settings").join(format!("{}.toml", profile)));
+
+            assert_eq!(config.client_id, client_id, "{} {}", dir, profile);
+            config.validate().unwrap_or_else(|e| panic!("{} {}: {}", dir, profile, e));
+        }
+    }
+}
+
+#[test]
+fn rejects_missing_broker_and_client_id() {
+    let config = KafkaConfig { bootstrap_servers: " , ".to_string(), client_id: "order-service".to_string(), ..KafkaConfig::default() };
+    assert!(config.validate().is_err());
+
+    let config = KafkaConfig { client_id: String::new(), ..KafkaConfig::default() };
+    assert!(config.validate().is_err());
+}
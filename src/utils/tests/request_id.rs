@@ -0,0 +1,81 @@
+//! The `x-request-id` middleware and carrying the ID into spawned work
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::middleware::from_fn;
+use axum::routing::get;
+use axum::Router;
+use tower::ServiceExt;
+use utils::request_id::{self, current_request_id, propagate_request_id, RequestId, REQUEST_ID_HEADER};
+
+/// Answers with the ID the handler sees through the task-local, the header and the extension
+async fn seen(request: Request<Body>) -> String {
+    let header = request.headers().get(REQUEST_ID_HEADER).and_then(|value| value.to_str().ok());
+    let extension = request.extensions().get::<RequestId>().map(|id| id.0.as_str());
+    assert_eq!(header, extension);
+    format!("{}|{}", current_request_id().unwrap_or_default(), header.unwrap_or_default())
+}
+
+async fn call(request_id: Option<&str>) -> (String, String) {
+    let app = Router::new().route("/", get(seen)).layer(from_fn(propagate_request_id));
+    let mut request = Request::builder().uri("/");
+    if let Some(request_id) = request_id {
+        request = request.header(REQUEST_ID_HEADER, request_id);
+    }
+
+    let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let echoed = response.headers()[REQUEST_ID_HEADER].to_str().unwrap().to_string();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (echoed, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn generates_an_id_when_the_caller_sends_none() {
+    let (echoed, seen) = call(None).await;
+
+    assert!(uuid::Uuid::parse_str(&echoed).is_ok(), "{}", echoed);
+    assert_eq!(seen, format!("{}|{}", echoed, echoed));
+}
+
+#[tokio::test]
+async fn keeps_and_echoes_the_callers_id() {
+    let (echoed, seen) = call(Some("client-42.a:b_c")).await;
+
+    assert_eq!(echoed, "client-42.a:b_c");
+    assert_eq!(seen, "client-42.a:b_c|client-42.a:b_c");
+}
+
+#[tokio::test]
+async fn replaces_an_invalid_id() {
+    let too_long = "a".repeat(200);
+    for invalid in ["has space", "semi;colon", too_long.as_str()] {
+        let (echoed, seen) = call(Some(invalid)).await;
+
+        assert_ne!(echoed, invalid);
+        assert!(uuid::Uuid::parse_str(&echoed).is_ok());
+        assert_eq!(seen, format!("{}|{}", echoed, echoed));
+    }
+}
+
+#[tokio::test]
+async fn spawned_work_keeps_the_request_id() {
+    let (spawned, plain) = request_id::with_request_id("req-1".to_string(), async {
+        let spawned = request_id::spawn(async { current_request_id() });
+        let plain = tokio::spawn(async { current_request_id() });
+        (spawned.await.unwrap(), plain.await.unwrap())
+    })
+    .await;
+
+    assert_eq!(spawned.as_deref(), Some("req-1"));
+    assert_eq!(plain, None, "a plain spawn does not inherit the task-local");
+}
+
+#[tokio::test]
+async fn optional_id_runs_the_future_as_is_without_one() {
+    let id = request_id::with_optional_request_id(None, async { current_request_id() }).await;
+    assert_eq!(id, None);
+
+    let id = request_id::with_optional_request_id(Some("req-2".to_string()), async { current_request_id() }).await;
+    assert_eq!(id.as_deref(), Some("req-2"));
+}
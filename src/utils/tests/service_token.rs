@@ -0,0 +1,82 @@
+//! Service-to-service tokens, each caller signing with its own secret
+
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use std::collections::HashMap;
+use std::time::Duration;
+use utils::internal_auth::{ServiceClaims, ServiceTokenSigner, ServiceTokenVerifier};
+
+const AUDIENCE: &str = "api-gateway";
+const ORDER_SECRET: &str = "order-service-secret";
+const PRODUCT_SECRET: &str = "product-service-secret";
+
+fn verifier() -> ServiceTokenVerifier {
+    let callers = HashMap::from([
+        ("order-service".to_string(), ORDER_SECRET.to_string()),
+        ("product-service".to_string(), PRODUCT_SECRET.to_string()),
+    ]);
+    ServiceTokenVerifier::new(AUDIENCE, &callers)
+}
+
+fn signer(service: &str, secret: &str) -> ServiceTokenSigner {
+    ServiceTokenSigner::new(service, secret, Duration::from_secs(60))
+}
+
+/// Token with hand-picked claims, signed with `secret` under the key ID `kid`
+fn forge(kid: &str, secret: &str, claims: ServiceClaims) -> String {
+    let mut header = Header::new(Algorithm::HS256);
+    header.kid = Some(kid.to_string());
+    jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret.as_bytes())).unwrap()
+}
+
+fn claims(iss: &str, exp_offset_secs: i64) -> ServiceClaims {
+    let now = chrono::Utc::now().timestamp();
+    ServiceClaims {
+        iss: iss.to_string(),
+        aud: AUDIENCE.to_string(),
+        iat: now,
+        exp: now + exp_offset_secs,
+    }
+}
+
+#[test]
+fn accepts_tokens_signed_with_the_caller_secret() {
+    let verifier = verifier();
+
+    let token = signer("order-service", ORDER_SECRET).sign(AUDIENCE).unwrap();
+    assert_eq!(verifier.verify(&token).unwrap().iss, "order-service");
+
+    let token = signer("product-service", PRODUCT_SECRET).sign(AUDIENCE).unwrap();
+    assert_eq!(verifier.verify(&token).unwrap().iss, "product-service");
+}
+
+#[test]
+fn rejects_a_service_minting_tokens_for_another() {
+    let verifier = verifier();
+
+    // Claiming to be product-service with order-service's secret
+    let token = signer("product-service", ORDER_SECRET).sign(AUDIENCE).unwrap();
+    assert_eq!(verifier.verify(&token).unwrap_err().kind(), &ErrorKind::InvalidSignature);
+
+    // Signed with its own secret, but with another issuer in the claims
+    let token = forge("order-service", ORDER_SECRET, claims("product-service", 60));
+    assert_eq!(verifier.verify(&token).unwrap_err().kind(), &ErrorKind::InvalidIssuer);
+}
+
+#[test]
+fn rejects_unknown_callers_and_other_audiences() {
+    let verifier = verifier();
+
+    let token = signer("billing-service", "billing-secret").sign(AUDIENCE).unwrap();
+    assert_eq!(verifier.verify(&token).unwrap_err().kind(), &ErrorKind::InvalidIssuer);
+
+    let token = signer("order-service", ORDER_SECRET).sign("product-service").unwrap();
+    assert_eq!(verifier.verify(&token).unwrap_err().kind(), &ErrorKind::InvalidAudience);
+}
+
+#[test]
+fn rejects_expired_tokens() {
+    let token = forge("order-service", ORDER_SECRET, claims("order-service", -60));
+
+    assert_eq!(verifier().verify(&token).unwrap_err().kind(), &ErrorKind::ExpiredSignature);
+}
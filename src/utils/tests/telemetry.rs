@@ -0,0 +1,90 @@
+//! W3C trace context propagation through HTTP and message headers
+
+use axum::body::Body;
+use axum::http::{HeaderMap, Request};
+use opentelemetry::global;
+use opentelemetry::trace::{TraceContextExt, TracerProvider};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing::subscriber::DefaultGuard;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+use utils::telemetry::{context_from_pairs, extract_context, http_request_span, inject_context, trace_context_pairs};
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+/// Subscriber exporting nowhere, so spans get real OpenTelemetry contexts
+fn install_tracing() -> DefaultGuard {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let provider = SdkTracerProvider::builder().build();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+    tracing::subscriber::set_default(subscriber)
+}
+
+fn trace_id_of(traceparent: &str) -> &str {
+    traceparent.split('-').nth(1).expect("traceparent has a trace ID")
+}
+
+#[test]
+fn injected_headers_carry_the_current_trace() {
+    let _guard = install_tracing();
+    let span = tracing::info_span!("outgoing");
+    let _entered = span.enter();
+
+    let mut headers = HeaderMap::new();
+    inject_context(&mut headers);
+
+    let traceparent = headers["traceparent"].to_str().unwrap();
+    let trace_id = span.context().span().span_context().trace_id().to_string();
+    assert_eq!(trace_id_of(traceparent), trace_id);
+    assert_eq!(extract_context(&headers).span().span_context().trace_id().to_string(), trace_id);
+}
+
+#[test]
+fn request_span_continues_the_callers_trace() {
+    let _guard = install_tracing();
+    let request = Request::builder()
+        .uri("/api/v1/users")
+        .header("traceparent", TRACEPARENT)
+        .header("x-request-id", "req-1")
+        .body(Body::empty())
+        .unwrap();
+
+    let span = http_request_span(&request);
+    let pairs = span.in_scope(trace_context_pairs);
+
+    let (_, traceparent) = pairs.iter().find(|(key, _)| key == "traceparent").expect("trace context pair");
+    assert_eq!(trace_id_of(traceparent), TRACE_ID);
+    assert_ne!(traceparent, TRACEPARENT, "the request span is a new child span");
+}
+
+#[test]
+fn message_header_pairs_round_trip() {
+    let _guard = install_tracing();
+    let span = tracing::info_span!("produce");
+    let pairs = span.in_scope(trace_context_pairs);
+
+    // Header names are matched case-insensitively, as some clients capitalise them
+    let context = context_from_pairs(pairs.iter().map(|(key, value)| (key.as_str(), value.as_str())));
+    let upper = context_from_pairs(
+        pairs.iter().filter(|(key, _)| key == "traceparent").map(|(_, value)| ("Traceparent", value.as_str())),
+    );
+
+    let trace_id = span.context().span().span_context().trace_id();
+    assert_eq!(context.span().span_context().trace_id(), trace_id);
+    assert_eq!(upper.span().span_context().trace_id(), trace_id);
+    assert!(context.span().span_context().is_remote());
+}
+
+#[test]
+fn nothing_is_injected_without_a_trace() {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let mut headers = HeaderMap::new();
+    inject_context(&mut headers);
+
+    assert!(headers.is_empty());
+    assert!(trace_context_pairs().is_empty());
+}
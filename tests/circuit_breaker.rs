@@ -0,0 +1,142 @@
+//! State machine of the per-service circuit breaker
+
+use api_gateway::infrastructure::gateway::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+use std::time::Duration;
+
+const OPEN_DURATION: Duration = Duration::from_secs(1);
+
+fn breaker() -> CircuitBreaker {
+    CircuitBreaker::new(CircuitBreakerConfig {
+        failure_threshold: 3,
+        open_duration_secs: OPEN_DURATION.as_secs(),
+        half_open_success_threshold: 2,
+        half_open_max_calls: 1,
+    })
+}
+
+fn fail(breaker: &CircuitBreaker, times: u32) {
+    for _ in 0..times {
+        breaker.try_acquire().expect("circuit lets the call through").failure();
+    }
+}
+
+fn wait_until_half_open(breaker: &CircuitBreaker) {
+    std::thread::sleep(OPEN_DURATION + Duration::from_millis(50));
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+}
+
+#[test]
+fn opens_after_the_failure_threshold() {
+    let breaker = breaker();
+
+    fail(&breaker, 2);
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    fail(&breaker, 1);
+    assert_eq!(breaker.state(), CircuitState::Open);
+    let retry_after = breaker.try_acquire().expect_err("open circuit rejects calls");
+    assert!(retry_after <= OPEN_DURATION);
+}
+
+#[test]
+fn success_resets_the_failure_count() {
+    let breaker = breaker();
+
+    fail(&breaker, 2);
+    breaker.try_acquire().unwrap().success();
+    fail(&breaker, 2);
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[test]
+fn closes_again_after_successful_probes() {
+    let breaker = breaker();
+    fail(&breaker, 3);
+    wait_until_half_open(&breaker);
+
+    let probe = breaker.try_acquire().expect("half-open circuit lets a probe through");
+    assert!(breaker.try_acquire().is_err(), "only one probe at a time");
+    probe.success();
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+    breaker.try_acquire().unwrap().success();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[test]
+fn failed_probe_opens_the_circuit_again() {
+    let breaker = breaker();
+    fail(&breaker, 3);
+    wait_until_half_open(&breaker);
+
+    breaker.try_acquire().unwrap().failure();
+    assert_eq!(breaker.state(), CircuitState::Open);
+}
+
+#[test]
+fn dropped_probe_gives_its_slot_back() {
+    let breaker = breaker();
+    fail(&breaker, 3);
+    wait_until_half_open(&breaker);
+
+    let probe = breaker.try_acquire().unwrap();
+    assert!(breaker.try_acquire().is_err());
+    // What happens to a permit when its request future is cancelled
+    drop(probe);
+
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    breaker.try_acquire().expect("slot is free again").success();
+}
+
+#[test]
+fn stale_probe_does_not_free_a_later_slot() {
+    let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+        failure_threshold: 1,
+        open_duration_secs: OPEN_DURATION.as_secs(),
+        half_open_success_threshold: 2,
+        half_open_max_calls: 2,
+    });
+    fail(&breaker, 1);
+    wait_until_half_open(&breaker);
+
+    // The second probe fails and the circuit goes through open to half-open again
+    let stale = breaker.try_acquire().unwrap();
+    breaker.try_acquire().unwrap().failure();
+    wait_until_half_open(&breaker);
+    let _current = breaker.try_acquire().unwrap();
+
+    drop(stale);
+    let _second = breaker.try_acquire().expect("one slot left");
+    assert!(breaker.try_acquire().is_err(), "the stale probe must not have freed a slot");
+}
+
+#[test]
+fn slow_probe_failing_after_close_does_not_reopen() {
+    let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+        failure_threshold: 1,
+        open_duration_secs: OPEN_DURATION.as_secs(),
+        half_open_success_threshold: 1,
+        half_open_max_calls: 2,
+    });
+    fail(&breaker, 1);
+    wait_until_half_open(&breaker);
+
+    let slow = breaker.try_acquire().unwrap();
+    breaker.try_acquire().unwrap().success();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    slow.failure();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[test]
+fn call_from_before_the_circuit_opened_does_not_decide_the_probe() {
+    let breaker = breaker();
+    let slow = breaker.try_acquire().unwrap();
+    fail(&breaker, 3);
+    wait_until_half_open(&breaker);
+
+    slow.failure();
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    breaker.try_acquire().expect("probe slot is untouched").success();
+}
@@ -36,6 +36,7 @@ pub fn create_test_user_claims(
         exp: 10000000000,
         iat: chrono::Utc::now().timestamp(),
         sid: uuid::Uuid::new_v4(),
+        roles: vec![],
     }
 }
 
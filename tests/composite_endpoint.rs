@@ -0,0 +1,95 @@
+//! Composite endpoints: part paths, merging and the shipped defaults
+
+use api_gateway::api::build_routes;
+use api_gateway::infrastructure::error::AppError;
+use api_gateway::infrastructure::gateway::aggregation::{
+    CompositeEndpoint, CompositePart, CompositeRegistry, CompositeResponse, PartError, SELF_SERVICE,
+};
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+fn part(key: &str, required: bool) -> CompositePart {
+    CompositePart {
+        key: key.to_string(),
+        service: SELF_SERVICE.to_string(),
+        path: format!("/v1/{}", key),
+        select: None,
+        timeout_ms: 100,
+        required,
+    }
+}
+
+fn endpoint(parts: Vec<CompositePart>) -> CompositeEndpoint {
+    CompositeEndpoint { name: "screen".to_string(), require_auth: true, parts }
+}
+
+#[test]
+fn merges_every_part_under_its_key() {
+    let endpoint = endpoint(vec![part("profile", true), part("addresses", false)]);
+
+    let merged = CompositeResponse::merge(&endpoint, vec![Ok(json!({ "id": 1 })), Ok(json!([]))]).unwrap();
+
+    assert_eq!(merged.data["profile"], json!({ "id": 1 }));
+    assert_eq!(merged.data["addresses"], json!([]));
+    assert!(merged.errors.is_empty());
+    assert!(!merged.partial);
+}
+
+#[test]
+fn failed_optional_part_is_null_and_reported() {
+    let endpoint = endpoint(vec![part("profile", true), part("addresses", false)]);
+    let failure = PartError::new("addresses", StatusCode::GATEWAY_TIMEOUT, "Timed out after 100ms");
+
+    let merged = CompositeResponse::merge(&endpoint, vec![Ok(json!({ "id": 1 })), Err(failure)]).unwrap();
+
+    assert_eq!(merged.data["profile"], json!({ "id": 1 }));
+    assert_eq!(merged.data["addresses"], Value::Null);
+    assert_eq!(merged.errors.len(), 1);
+    assert_eq!(merged.errors[0].key, "addresses");
+    assert_eq!(merged.errors[0].status, 504);
+    assert!(merged.partial);
+}
+
+#[test]
+fn failed_required_part_fails_the_response() {
+    let endpoint = endpoint(vec![part("profile", true), part("addresses", false)]);
+    let failure = PartError::new("profile", StatusCode::BAD_GATEWAY, "upstream down");
+
+    let merged = CompositeResponse::merge(&endpoint, vec![Err(failure), Ok(json!([]))]);
+
+    assert!(matches!(merged, Err(AppError::BadGatewayError(detail)) if detail.contains("'profile'")));
+}
+
+#[test]
+fn renders_placeholders_from_the_caller_and_the_query() {
+    let part = CompositePart { path: "/v1/search?user_id={user_id}&q={q}".to_string(), ..part("search", false) };
+    let query = HashMap::from([("q".to_string(), "red shoes".to_string())]);
+
+    assert_eq!(part.render_path(Some(7), &query).unwrap(), "/v1/search?user_id=7&q=red%20shoes");
+    assert!(matches!(part.render_path(Some(7), &HashMap::new()), Err(AppError::BadRequestError(_))));
+    assert!(matches!(part.render_path(None, &query), Err(AppError::BadRequestError(_))));
+}
+
+#[test]
+fn selects_into_the_part_response() {
+    let part = CompositePart { select: Some("/data".to_string()), ..part("profile", true) };
+
+    assert_eq!(part.extract(br#"{"data": {"id": 1}}"#).unwrap(), json!({ "id": 1 }));
+    assert!(part.extract(br#"{"message": "ok"}"#).is_err());
+    assert!(part.extract(b"<html>").is_err());
+}
+
+#[tokio::test]
+async fn default_parts_call_existing_gateway_routes() {
+    let (_, openapi) = build_routes().split_for_parts();
+
+    for endpoint in CompositeRegistry::with_defaults().await.list_all().await {
+        for part in endpoint.parts {
+            assert_eq!(part.service, SELF_SERVICE, "part '{}' calls an upstream with no such route", part.key);
+            let path = part.path.split('?').next().unwrap();
+            let item = openapi.paths.paths.get(path).unwrap_or_else(|| panic!("no route {}", path));
+            assert!(item.get.is_some(), "{} has no GET route", path);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Contract tests for the domain events other services consume
+//!
+//! Each event's payload schema is compared with the one committed under `schemas/events`,
+//! and the committed example envelopes must still decode. After a compatible change, e.g. a
+//! new optional field, refresh the committed schemas with
+//!
+//! ```bash
+//! UPDATE_EVENT_SCHEMAS=1 cargo test --test event_contracts
+//! ```
+//!
+//! A breaking change needs a new `SCHEMA_VERSION` instead, published next to the old one.
+
+use api_gateway::domain::address::events::address_added::AddressAddedEvent;
+use api_gateway::domain::address::events::address_removed::AddressRemovedEvent;
+use api_gateway::domain::address::events::address_updated::AddressUpdatedEvent;
+use api_gateway::domain::user::events::user_activated::UserActivatedEvent;
+use api_gateway::domain::user::events::user_created::UserCreatedEvent;
+use api_gateway::domain::user::events::user_deleted::UserDeletedEvent;
+use api_gateway::domain::user::events::user_logged_in::UserLoggedInEvent;
+use api_gateway::domain::user::events::user_registered::UserRegisteredEvent;
+use api_gateway::domain::user::events::user_updated::UserUpdatedEvent;
+use serde_json::Value;
+use std::path::PathBuf;
+use utils::events::contract::{breaking_changes, payload_schema, schema_file_name};
+use utils::events::{DomainEvent, EventEnvelope};
+
+fn schemas_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("schemas").join("events")
+}
+
+fn check_schema<E: DomainEvent>() {
+    let path = schemas_dir().join(schema_file_name::<E>());
+    let generated = payload_schema::<E>();
+
+    if std::env::var("UPDATE_EVENT_SCHEMAS").is_ok_and(|value| value == "1") {
+        if let Ok(committed) = std::fs::read_to_string(&path) {
+            let committed: Value = serde_json::from_str(&committed).unwrap();
+            let changes = breaking_changes(&committed, &generated);
+            assert!(
+                changes.is_empty(),
+                "refusing to overwrite {} with a breaking change, bump SCHEMA_VERSION instead:\n{}",
+                path.display(),
+                changes.join("\n")
+            );
+        }
+        std::fs::create_dir_all(schemas_dir()).unwrap();
+        std::fs::write(&path, serde_json::to_string_pretty(&generated).unwrap() + "\n").unwrap();
+        return;
+    }
+
+    let committed = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "{} has no committed schema at {}, run with UPDATE_EVENT_SCHEMAS=1 to add it",
+            E::EVENT_TYPE,
+            path.display()
+        )
+    });
+    let committed: Value = serde_json::from_str(&committed).unwrap();
+
+    let changes = breaking_changes(&committed, &generated);
+    assert!(
+        changes.is_empty(),
+        "{} v{} changed in a way its consumers cannot read, bump SCHEMA_VERSION instead:\n{}",
+        E::EVENT_TYPE,
+        E::SCHEMA_VERSION,
+        changes.join("\n")
+    );
+    assert_eq!(
+        committed,
+        generated,
+        "{} changed compatibly, run with UPDATE_EVENT_SCHEMAS=1 to update {}",
+        E::EVENT_TYPE,
+        path.display()
+    );
+}
+
+fn check_example<E: DomainEvent>() {
+    let path = schemas_dir().join("examples").join(schema_file_name::<E>());
+    let example = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("{} has no example envelope at {}", E::EVENT_TYPE, path.display()));
+
+    let envelope: EventEnvelope<E> = serde_json::from_str(&example)
+        .unwrap_or_else(|e| panic!("{} no longer decodes: {}", path.display(), e));
+    assert_eq!(envelope.event_type, E::EVENT_TYPE);
+    assert_eq!(envelope.schemaversion, E::SCHEMA_VERSION);
+    assert_eq!(envelope.aggregateid, envelope.data.aggregate_id());
+}
+
+#[test]
+fn user_registered_contract() {
+    check_schema::<UserRegisteredEvent>();
+    check_example::<UserRegisteredEvent>();
+}
+
+#[test]
+fn user_activated_contract() {
+    check_schema::<UserActivatedEvent>();
+    check_example::<UserActivatedEvent>();
+}
+
+#[test]
+fn user_logged_in_contract() {
+    check_schema::<UserLoggedInEvent>();
+    check_example::<UserLoggedInEvent>();
+}
+
+#[test]
+fn user_created_contract() {
+    check_schema::<UserCreatedEvent>();
+    check_example::<UserCreatedEvent>();
+}
+
+#[test]
+fn user_updated_contract() {
+    check_schema::<UserUpdatedEvent>();
+    check_example::<UserUpdatedEvent>();
+}
+
+#[test]
+fn user_deleted_contract() {
+    check_schema::<UserDeletedEvent>();
+    check_example::<UserDeletedEvent>();
+}
+
+#[test]
+fn address_added_contract() {
+    check_schema::<AddressAddedEvent>();
+    check_example::<AddressAddedEvent>();
+}
+
+#[test]
+fn address_updated_contract() {
+    check_schema::<AddressUpdatedEvent>();
+    check_example::<AddressUpdatedEvent>();
+}
+
+#[test]
+fn address_removed_contract() {
+    check_schema::<AddressRemovedEvent>();
+    check_example::<AddressRemovedEvent>();
+}
+
+#[test]
+fn breaking_changes_are_detected() {
+    let old = serde_json::json!({
+        "type": "object",
+        "properties": { "user_id": { "type": "integer" }, "email": { "type": "string" } },
+        "required": ["user_id", "email"]
+    });
+    let added_optional = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "user_id": { "type": "integer" },
+            "email": { "type": "string" },
+            "name": { "type": ["string", "null"] }
+        },
+        "required": ["user_id", "email"]
+    });
+    let retyped = serde_json::json!({
+        "type": "object",
+        "properties": { "user_id": { "type": "string" }, "email": { "type": "string" } },
+        "required": ["user_id", "email"]
+    });
+    let removed = serde_json::json!({
+        "type": "object",
+        "properties": { "user_id": { "type": "integer" } },
+        "required": ["user_id"]
+    });
+
+    assert!(breaking_changes(&old, &added_optional).is_empty());
+    assert_eq!(breaking_changes(&old, &retyped).len(), 1);
+    assert!(!breaking_changes(&old, &removed).is_empty());
+}
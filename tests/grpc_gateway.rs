@@ -0,0 +1,251 @@
+//! gRPC passthrough and JSON transcoding against an in-process tonic server
+
+use api_gateway::api::grpc::pb;
+use api_gateway::api::grpc::pb::administration_service_server::{
+    AdministrationService, AdministrationServiceServer,
+};
+use api_gateway::infrastructure::error::AppError;
+use api_gateway::infrastructure::gateway::circuit_breaker::{CircuitBreakerConfig, CircuitState};
+use api_gateway::infrastructure::gateway::grpc::{GrpcConfig, GrpcGateway, TranscodeRoute};
+use api_gateway::infrastructure::gateway::resilience::UpstreamGuard;
+use api_gateway::infrastructure::gateway::retry::RetryPolicy;
+use api_gateway::infrastructure::gateway::rewrite::RewriteRules;
+use api_gateway::infrastructure::gateway::service_registry::ServiceConfig;
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{header, Method};
+use http_body_util::BodyExt;
+use prost::Message;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tonic::transport::server::TcpIncoming;
+use tonic::{Code, Response, Status};
+
+const GET_USER: &str = "administration.AdministrationService/GetUser";
+const EXISTING_USER: i64 = 1;
+const MISSING_USER: i64 = 2;
+const UNAVAILABLE_USER: i64 = 3;
+
+#[derive(Default)]
+struct Administration {
+    calls: Arc<AtomicUsize>,
+}
+
+#[tonic::async_trait]
+impl AdministrationService for Administration {
+    async fn get_user(
+        &self,
+        request: tonic::Request<pb::GetUserRequest>,
+    ) -> Result<Response<pb::User>, Status> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        match request.into_inner().user_id {
+            EXISTING_USER => Ok(Response::new(pb::User {
+                user_id: EXISTING_USER,
+                username: "alice".to_string(),
+                ..pb::User::default()
+            })),
+            MISSING_USER => Err(Status::not_found("user not found")),
+            _ => Err(Status::unavailable("database is down")),
+        }
+    }
+
+    async fn batch_get_users(
+        &self,
+        _request: tonic::Request<pb::BatchGetUsersRequest>,
+    ) -> Result<Response<pb::BatchGetUsersResponse>, Status> {
+        Err(Status::unimplemented("not needed"))
+    }
+
+    async fn get_user_addresses(
+        &self,
+        _request: tonic::Request<pb::GetUserAddressesRequest>,
+    ) -> Result<Response<pb::GetUserAddressesResponse>, Status> {
+        Err(Status::unimplemented("not needed"))
+    }
+
+    async fn validate_session(
+        &self,
+        _request: tonic::Request<pb::ValidateSessionRequest>,
+    ) -> Result<Response<pb::ValidateSessionResponse>, Status> {
+        Err(Status::unimplemented("not needed"))
+    }
+}
+
+/// Serve the administration API on a random port, returning its URL and call counter
+async fn spawn_upstream() -> (String, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let service = Administration::default();
+    let calls = service.calls.clone();
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(AdministrationServiceServer::new(service))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    (format!("http://{}", addr), calls)
+}
+
+fn grpc_config(base_url: String) -> GrpcConfig {
+    let descriptor_set = std::env::temp_dir().join(format!("administration-{}.bin", std::process::id()));
+    std::fs::write(&descriptor_set, pb::FILE_DESCRIPTOR_SET).unwrap();
+    GrpcConfig {
+        base_url,
+        services: vec!["administration.AdministrationService".to_string()],
+        descriptor_set_path: Some(descriptor_set.to_string_lossy().into_owned()),
+        transcode: vec![TranscodeRoute {
+            method: "GET".to_string(),
+            path: "/users/{user_id}".to_string(),
+            grpc_method: GET_USER.to_string(),
+        }],
+    }
+}
+
+fn service(grpc: &GrpcConfig) -> ServiceConfig {
+    ServiceConfig {
+        name: "user-service".to_string(),
+        base_url: grpc.base_url.clone(),
+        health_check_path: None,
+        timeout_secs: 5,
+        require_auth: false,
+        openapi_path: None,
+        circuit_breaker: CircuitBreakerConfig { failure_threshold: 2, ..CircuitBreakerConfig::default() },
+        retry: RetryPolicy::default(),
+        cache: Vec::new(),
+        canary: None,
+        mirror: None,
+        rewrite: RewriteRules::default(),
+        grpc: Some(grpc.clone()),
+    }
+}
+
+fn grpc_request(user_id: i64) -> Request {
+    let message = pb::GetUserRequest { user_id }.encode_to_vec();
+    let mut frame = vec![0];
+    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&message);
+    Request::builder()
+        .method(Method::POST)
+        .uri(format!("/{}", GET_USER))
+        .header(header::CONTENT_TYPE, "application/grpc")
+        .body(Body::from(frame))
+        .unwrap()
+}
+
+/// Pass a GetUser call through, returning its status and decoded response
+async fn passthrough(
+    gateway: &GrpcGateway,
+    config: &ServiceConfig,
+    guard: &UpstreamGuard,
+    user_id: i64,
+) -> (Code, Option<pb::User>) {
+    let grpc = config.grpc.as_ref().unwrap();
+    let response = gateway.forward(config, grpc, guard, grpc_request(user_id), None).await;
+    let (parts, body) = response.into_parts();
+    let collected = body.collect().await.expect("body streams to the end");
+    let status = collected
+        .trailers()
+        .and_then(Status::from_header_map)
+        .or_else(|| Status::from_header_map(&parts.headers))
+        .expect("response carries a grpc-status");
+    let bytes = collected.to_bytes();
+    let user = (bytes.len() > 5).then(|| pb::User::decode(&bytes[5..]).unwrap());
+    (status.code(), user)
+}
+
+async fn transcode(
+    gateway: &GrpcGateway,
+    config: &ServiceConfig,
+    guard: &UpstreamGuard,
+    user_id: i64,
+) -> Result<serde_json::Value, AppError> {
+    let grpc = config.grpc.as_ref().unwrap();
+    let request = Request::builder().uri(format!("/users/{}", user_id)).body(Body::empty()).unwrap();
+    let response = gateway
+        .transcode(config, grpc, guard, &grpc.transcode[0], vec![("user_id".to_string(), user_id.to_string())], request, None)
+        .await?;
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    Ok(serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn passes_calls_through_with_trailers() {
+    let (base_url, _) = spawn_upstream().await;
+    let config = service(&grpc_config(base_url));
+    let guard = UpstreamGuard::new(&config);
+
+    let (code, user) = passthrough(&GrpcGateway::new(), &config, &guard, EXISTING_USER).await;
+
+    assert_eq!(code, Code::Ok);
+    assert_eq!(user.unwrap().username, "alice");
+    assert_eq!(guard.circuit_breaker.state(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn passthrough_trips_the_breaker_on_unavailable_status() {
+    let (base_url, calls) = spawn_upstream().await;
+    let config = service(&grpc_config(base_url));
+    let guard = UpstreamGuard::new(&config);
+    let gateway = GrpcGateway::new();
+
+    // A rejected call is the upstream working as intended
+    for _ in 0..3 {
+        assert_eq!(passthrough(&gateway, &config, &guard, MISSING_USER).await.0, Code::NotFound);
+    }
+    assert_eq!(guard.circuit_breaker.state(), CircuitState::Closed);
+
+    for _ in 0..2 {
+        assert_eq!(passthrough(&gateway, &config, &guard, UNAVAILABLE_USER).await.0, Code::Unavailable);
+    }
+    assert_eq!(guard.circuit_breaker.state(), CircuitState::Open);
+
+    let (code, _) = passthrough(&gateway, &config, &guard, EXISTING_USER).await;
+    assert_eq!(code, Code::Unavailable);
+    assert_eq!(calls.load(Ordering::SeqCst), 5, "open circuit keeps calls off the upstream");
+}
+
+#[tokio::test]
+async fn transcodes_json_to_unary_calls() {
+    let (base_url, _) = spawn_upstream().await;
+    let config = service(&grpc_config(base_url));
+    let guard = UpstreamGuard::new(&config);
+
+    let user = transcode(&GrpcGateway::new(), &config, &guard, EXISTING_USER).await.unwrap();
+
+    assert_eq!(user["username"], "alice");
+}
+
+#[tokio::test]
+async fn transcode_maps_grpc_statuses_to_http_errors() {
+    let (base_url, _) = spawn_upstream().await;
+    let config = service(&grpc_config(base_url));
+    let guard = UpstreamGuard::new(&config);
+    let gateway = GrpcGateway::new();
+
+    let missing = transcode(&gateway, &config, &guard, MISSING_USER).await;
+    assert!(matches!(missing, Err(AppError::EntityNotFoundError { .. })), "{:?}", missing.err());
+
+    let unavailable = transcode(&gateway, &config, &guard, UNAVAILABLE_USER).await;
+    assert!(matches!(unavailable, Err(AppError::ServiceUnavailableError { .. })), "{:?}", unavailable.err());
+}
+
+#[tokio::test]
+async fn transcode_is_guarded_by_the_circuit_breaker() {
+    let (base_url, calls) = spawn_upstream().await;
+    let config = service(&grpc_config(base_url));
+    let guard = UpstreamGuard::new(&config);
+    let gateway = GrpcGateway::new();
+
+    transcode(&gateway, &config, &guard, MISSING_USER).await.unwrap_err();
+    assert_eq!(guard.circuit_breaker.state(), CircuitState::Closed);
+
+    for _ in 0..2 {
+        transcode(&gateway, &config, &guard, UNAVAILABLE_USER).await.unwrap_err();
+    }
+    assert_eq!(guard.circuit_breaker.state(), CircuitState::Open);
+
+    let rejected = transcode(&gateway, &config, &guard, EXISTING_USER).await;
+    assert!(matches!(rejected, Err(AppError::ServiceUnavailableError { .. })), "{:?}", rejected.err());
+    assert_eq!(calls.load(Ordering::SeqCst), 3, "open circuit keeps calls off the upstream");
+}
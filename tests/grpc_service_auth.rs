@@ -0,0 +1,94 @@
+//! Service tokens on the internal gRPC API, from the client interceptor to the server check
+
+use api_gateway::api::grpc::client;
+use api_gateway::api::grpc::pb;
+use api_gateway::api::grpc::pb::administration_service_server::{
+    AdministrationService, AdministrationServiceServer,
+};
+use api_gateway::api::grpc::authenticate_service;
+use api_gateway::infrastructure::constant::GRPC_SERVICE_AUDIENCE;
+use std::collections::HashMap;
+use std::time::Duration;
+use tonic::transport::server::TcpIncoming;
+use tonic::{Code, Request, Response, Status};
+use utils::internal_auth::{ServiceClaims, ServiceTokenSigner, ServiceTokenVerifier};
+
+const ORDER_SECRET: &str = "order-service-secret";
+
+/// Answers GetUser with the authenticated caller as the username
+struct CallerEcho;
+
+#[tonic::async_trait]
+impl AdministrationService for CallerEcho {
+    async fn get_user(&self, request: Request<pb::GetUserRequest>) -> Result<Response<pb::User>, Status> {
+        let caller = request.extensions().get::<ServiceClaims>().map(|claims| claims.iss.clone());
+        Ok(Response::new(pb::User { username: caller.unwrap_or_default(), ..pb::User::default() }))
+    }
+
+    async fn batch_get_users(
+        &self,
+        _request: Request<pb::BatchGetUsersRequest>,
+    ) -> Result<Response<pb::BatchGetUsersResponse>, Status> {
+        Err(Status::unimplemented("not needed"))
+    }
+
+    async fn get_user_addresses(
+        &self,
+        _request: Request<pb::GetUserAddressesRequest>,
+    ) -> Result<Response<pb::GetUserAddressesResponse>, Status> {
+        Err(Status::unimplemented("not needed"))
+    }
+
+    async fn validate_session(
+        &self,
+        _request: Request<pb::ValidateSessionRequest>,
+    ) -> Result<Response<pb::ValidateSessionResponse>, Status> {
+        Err(Status::unimplemented("not needed"))
+    }
+}
+
+/// Serve the API behind the service token check, with order-service as the only caller
+async fn spawn_server() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let callers = HashMap::from([("order-service".to_string(), ORDER_SECRET.to_string())]);
+    let verifier = ServiceTokenVerifier::new(GRPC_SERVICE_AUDIENCE, &callers);
+    let service = AdministrationServiceServer::with_interceptor(CallerEcho, move |request| {
+        authenticate_service(&verifier, request)
+    });
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+async fn get_user(endpoint: String, service: &str, secret: &str, ttl: Duration) -> Result<String, Status> {
+    let signer = ServiceTokenSigner::new(service, secret, ttl);
+    let mut client = client::connect(endpoint, signer).await.unwrap();
+    let user = client.get_user(pb::GetUserRequest { user_id: 1 }).await?;
+    Ok(user.into_inner().username)
+}
+
+#[tokio::test]
+async fn client_calls_are_authenticated_as_the_calling_service() {
+    let endpoint = spawn_server().await;
+
+    let caller = get_user(endpoint, "order-service", ORDER_SECRET, Duration::from_secs(60)).await;
+
+    assert_eq!(caller.unwrap(), "order-service");
+}
+
+#[tokio::test]
+async fn rejects_tokens_for_another_service_or_without_the_caller_secret() {
+    let endpoint = spawn_server().await;
+
+    let impersonating = get_user(endpoint.clone(), "product-service", ORDER_SECRET, Duration::from_secs(60)).await;
+    assert_eq!(impersonating.unwrap_err().code(), Code::Unauthenticated);
+
+    let wrong_secret = get_user(endpoint, "order-service", "guessed-secret", Duration::from_secs(60)).await;
+    assert_eq!(wrong_secret.unwrap_err().code(), Code::Unauthenticated);
+}
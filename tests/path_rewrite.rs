@@ -0,0 +1,71 @@
+//! Per-service path rewriting before a request is forwarded
+
+use api_gateway::infrastructure::gateway::rewrite::{PathReplace, RewriteRules};
+
+#[test]
+fn forwards_the_full_path_without_rules() {
+    let rules = RewriteRules::default();
+
+    assert_eq!(rules.rewrite_path("/gateway/product-service/api/v1/products"), "/gateway/product-service/api/v1/products");
+}
+
+#[test]
+fn strips_the_gateway_prefix_when_opted_in() {
+    let rules = RewriteRules::strip_gateway_prefix("product-service");
+
+    assert_eq!(rules.rewrite_path("/gateway/product-service/api/v1/products"), "/api/v1/products");
+    assert_eq!(rules.rewrite_path("/gateway/product-service"), "/");
+    // Only whole segments are stripped
+    assert_eq!(rules.rewrite_path("/gateway/product-service-v2/x"), "/gateway/product-service-v2/x");
+}
+
+#[test]
+fn applies_strip_replace_and_add_in_order() {
+    let rules = RewriteRules {
+        replace: vec![PathReplace::new("^/api/v1/(\\w+)", "/$1").unwrap()],
+        add_prefix: Some("/v1/".to_string()),
+        ..RewriteRules::strip_gateway_prefix("product-service")
+    };
+
+    assert_eq!(rules.rewrite_path("/gateway/product-service/api/v1/products/7"), "/v1/products/7");
+}
+
+#[test]
+fn invalid_pattern_is_rejected_with_the_config() {
+    assert!(PathReplace::new("(unclosed", "/").is_err());
+
+    let error = serde_json::from_str::<RewriteRules>(r#"{"replace": [{"pattern": "(unclosed", "replacement": "/"}]}"#)
+        .expect_err("invalid pattern");
+    assert!(error.to_string().contains("invalid rewrite pattern"), "{}", error);
+
+    let rules: RewriteRules =
+        serde_json::from_str(r#"{"replace": [{"pattern": "^/old/", "replacement": "/new/"}]}"#).unwrap();
+    assert_eq!(rules.rewrite_path("/old/items"), "/new/items");
+    assert_eq!(serde_json::to_value(&rules).unwrap()["replace"][0]["pattern"], "^/old/");
+}
+
+#[test]
+fn original_path_inverts_strip_and_add_prefix() {
+    let rules = RewriteRules { add_prefix: Some("/v1".to_string()), ..RewriteRules::strip_gateway_prefix("product-service") };
+
+    assert_eq!(rules.original_path("/v1/products/{id}").as_deref(), Some("/gateway/product-service/products/{id}"));
+    assert_eq!(rules.original_path("/v2/products"), None, "no incoming path gets the add prefix wrong");
+    assert_eq!(rules.original_path("/v1products"), None);
+}
+
+#[test]
+fn original_path_checks_the_replace_rules() {
+    let rules = RewriteRules {
+        replace: vec![PathReplace::new("^/internal/", "/public/").unwrap()],
+        ..RewriteRules::strip_gateway_prefix("product-service")
+    };
+
+    assert_eq!(rules.original_path("/public/items").as_deref(), Some("/gateway/product-service/public/items"));
+    // Every `/internal/` path is rewritten, so none reaches the service unchanged
+    assert_eq!(rules.original_path("/internal/items"), None);
+}
+
+#[test]
+fn original_path_without_rules_is_the_path_itself() {
+    assert_eq!(RewriteRules::default().original_path("/api/v1/products").as_deref(), Some("/api/v1/products"));
+}
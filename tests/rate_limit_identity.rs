@@ -0,0 +1,51 @@
+//! Who a request is counted against by the rate limiter
+
+use api_gateway::infrastructure::middleware::rate_limit::resolve_identity;
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::Request;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use utils::rate_limit::{api_key_fingerprint, Identity, API_KEY_HEADER};
+
+fn api_keys() -> HashMap<String, String> {
+    HashMap::from([(api_key_fingerprint("acme-secret-key"), "acme".to_string())])
+}
+
+fn request(api_key: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().uri("/gateway/order-service/v1/orders");
+    if let Some(api_key) = api_key {
+        builder = builder.header(API_KEY_HEADER, api_key);
+    }
+    let mut request = builder.body(Body::empty()).unwrap();
+    request.extensions_mut().insert(ConnectInfo("203.0.113.7:52100".parse::<SocketAddr>().unwrap()));
+    request
+}
+
+#[test]
+fn registered_api_key_is_counted_by_its_name() {
+    let identity = resolve_identity(&request(Some("acme-secret-key")), &api_keys());
+    assert_eq!(identity, Identity::ApiKey("acme".to_string()));
+    assert!(!identity.is_anonymous(), "API keys get the authenticated policies");
+}
+
+#[test]
+fn unregistered_api_key_falls_back_to_the_client_ip() {
+    let identity = resolve_identity(&request(Some("made-up-key")), &api_keys());
+    assert_eq!(identity, Identity::Ip("203.0.113.7".to_string()));
+    assert!(identity.is_anonymous());
+}
+
+#[test]
+fn request_without_credentials_is_counted_by_ip_or_unknown() {
+    assert_eq!(resolve_identity(&request(None), &api_keys()), Identity::Ip("203.0.113.7".to_string()));
+
+    let without_peer = Request::builder().uri("/").body(Body::empty()).unwrap();
+    assert_eq!(resolve_identity(&without_peer, &api_keys()), Identity::Unknown);
+}
+
+#[test]
+fn fingerprint_is_hex_sha256() {
+    // echo -n abc | sha256sum
+    assert_eq!(api_key_fingerprint("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+}
@@ -0,0 +1,279 @@
+//! Webhook requests as a partner's endpoint receives them, against a local HTTP receiver
+
+use api_gateway::application::webhook::webhook_event_handler::WebhookEventHandler;
+use api_gateway::core::configure::webhook::WebhookConfig;
+use api_gateway::domain::webhook::webhook_payload::partner_fields;
+use api_gateway::domain::webhook::{webhook_delivery, webhook_subscription};
+use api_gateway::domain::webhook::webhook_subscription::pattern_matches;
+use api_gateway::infrastructure::constant::{WEBHOOK_BASE_BACKOFF, WEBHOOK_MAX_BACKOFF};
+use api_gateway::infrastructure::third_party::webhook::{
+    sign_payload, verify_signature, DeliveryOutcome, WebhookRequest, WebhookSender, WEBHOOK_EVENT_HEADER,
+    WEBHOOK_ID_HEADER, WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER,
+};
+use api_gateway::infrastructure::third_party::webhook_dispatcher::retry_at;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Redirect;
+use axum::routing::post;
+use axum::Router;
+use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase, TransactionTrait, Value as SqlValue};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use utils::events::EventEnvelope;
+use utils::kafka_consumer::{EventHandler, RecordMeta};
+use uuid::Uuid;
+
+const SECRET: &str = "whsec_test";
+const PAYLOAD: &str = r#"{"type":"user.created","data":{"user_id":1}}"#;
+
+#[derive(Debug, Clone)]
+struct Received {
+    headers: HeaderMap,
+    body: String,
+}
+
+type Inbox = Arc<Mutex<Vec<Received>>>;
+
+async fn receive(State(inbox): State<Inbox>, headers: HeaderMap, body: String) -> StatusCode {
+    inbox.lock().unwrap().push(Received { headers, body });
+    StatusCode::NO_CONTENT
+}
+
+/// Base URL of a receiver recording what it gets on `/ok`, failing on `/fail` and
+/// redirecting on `/moved`
+async fn start_receiver() -> (String, Inbox) {
+    let inbox = Inbox::default();
+    let app = Router::new()
+        .route("/ok", post(receive))
+        .route("/fail", post(|| async { (StatusCode::SERVICE_UNAVAILABLE, "down for maintenance") }))
+        .route("/moved", post(|| async { Redirect::temporary("/ok") }))
+        .with_state(inbox.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}", address), inbox)
+}
+
+fn request(url: &str) -> WebhookRequest<'_> {
+    WebhookRequest {
+        url,
+        secret: SECRET,
+        event_id: Uuid::nil(),
+        event_type: "user.created",
+        payload: PAYLOAD,
+    }
+}
+
+fn sender() -> WebhookSender {
+    WebhookSender::new(Duration::from_secs(5))
+}
+
+#[tokio::test]
+async fn delivered_request_carries_a_verifiable_signature() {
+    let (base, inbox) = start_receiver().await;
+
+    let outcome = sender().send(&request(&format!("{}/ok", base))).await;
+    assert_eq!(outcome, DeliveryOutcome::Delivered { status: 204 });
+
+    let received = inbox.lock().unwrap().pop().expect("receiver got the webhook");
+    let header = |name: &str| received.headers.get(name).unwrap().to_str().unwrap().to_string();
+    assert_eq!(received.body, PAYLOAD);
+    assert_eq!(header(WEBHOOK_ID_HEADER), Uuid::nil().to_string());
+    assert_eq!(header(WEBHOOK_EVENT_HEADER), "user.created");
+    assert_eq!(header("content-type"), "application/json");
+
+    let timestamp: i64 = header(WEBHOOK_TIMESTAMP_HEADER).parse().unwrap();
+    let signature = header(WEBHOOK_SIGNATURE_HEADER);
+    assert!(verify_signature(SECRET, timestamp, &received.body, &signature));
+    assert!(!verify_signature("another secret", timestamp, &received.body, &signature));
+    assert!(!verify_signature(SECRET, timestamp + 1, &received.body, &signature));
+    assert!(!verify_signature(SECRET, timestamp, "{}", &signature));
+}
+
+#[test]
+fn signature_is_hmac_sha256_of_timestamp_and_body() {
+    // echo -n '1700000000.{"a":1}' | openssl dgst -sha256 -hmac secret
+    assert_eq!(
+        sign_payload("secret", 1700000000, r#"{"a":1}"#),
+        "sha256=49f24e537407743fa4a0242bb63b94b9a47ee99cbbe071ccd8a22550ae411686"
+    );
+}
+
+#[tokio::test]
+async fn error_status_fails_with_the_response_body() {
+    let (base, _) = start_receiver().await;
+
+    match sender().send(&request(&format!("{}/fail", base))).await {
+        DeliveryOutcome::Failed { status, error } => {
+            assert_eq!(status, Some(503));
+            assert!(error.contains("down for maintenance"), "{}", error);
+        },
+        outcome => panic!("expected a failure, got {:?}", outcome),
+    }
+}
+
+#[tokio::test]
+async fn redirects_are_not_followed() {
+    let (base, inbox) = start_receiver().await;
+
+    let outcome = sender().send(&request(&format!("{}/moved", base))).await;
+    assert!(matches!(outcome, DeliveryOutcome::Failed { status: Some(307), .. }), "{:?}", outcome);
+    assert!(inbox.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn unreachable_receiver_fails_without_a_status() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    drop(listener);
+
+    let outcome = sender().send(&request(&format!("http://{}/ok", address))).await;
+    assert!(matches!(outcome, DeliveryOutcome::Failed { status: None, .. }), "{:?}", outcome);
+}
+
+#[test]
+fn retries_back_off_exponentially_until_the_last_attempt() {
+    let now = chrono::Utc::now().naive_utc();
+    let wait = |attempts| retry_at(now, attempts, 10).map(|at| (at - now).to_std().unwrap());
+
+    assert_eq!(wait(1), Some(WEBHOOK_BASE_BACKOFF));
+    assert_eq!(wait(2), Some(WEBHOOK_BASE_BACKOFF * 2));
+    assert_eq!(wait(4), Some(WEBHOOK_BASE_BACKOFF * 8));
+    assert_eq!(wait(9), Some((WEBHOOK_BASE_BACKOFF * 256).min(WEBHOOK_MAX_BACKOFF)));
+    assert_eq!(wait(10), None);
+}
+
+#[test]
+fn event_type_patterns() {
+    assert!(pattern_matches("user.created", "user.created"));
+    assert!(!pattern_matches("user.created", "user.updated"));
+    assert!(pattern_matches("user.*", "user.updated"));
+    assert!(!pattern_matches("user.*", "address.added"));
+    assert!(pattern_matches("*", "order.created"));
+}
+
+const VERIFICATION_TOKEN: &str = "vt_do_not_leak";
+
+fn envelope(event_type: &str, data: Value) -> EventEnvelope<Value> {
+    serde_json::from_value(json!({
+        "specversion": "1.0",
+        "id": Uuid::new_v4(),
+        "type": event_type,
+        "source": "api-gateway",
+        "time": chrono::Utc::now(),
+        "datacontenttype": "application/json",
+        "schemaversion": 1,
+        "correlationid": "req-42",
+        "aggregateid": "1",
+        "data": data
+    }))
+    .unwrap()
+}
+
+/// A database with one subscription to every event, and the delivery row its insert returns
+fn subscribed_db() -> DatabaseConnection {
+    let now = chrono::Utc::now().naive_utc();
+    let subscription = webhook_subscription::Model {
+        id: 1,
+        owner: "acme".to_string(),
+        url: "https://acme.example/hooks".to_string(),
+        secret: SECRET.to_string(),
+        event_types: json!(["*"]),
+        status: webhook_subscription::Status::ACTIVE,
+        consecutive_failures: 0,
+        disabled_reason: None,
+        created_at: now,
+        updated_at: now,
+    };
+    let delivery = webhook_delivery::Model {
+        id: 1,
+        subscription_id: 1,
+        event_id: Uuid::nil(),
+        event_type: "user.created".to_string(),
+        payload: String::new(),
+        status: webhook_delivery::Status::PENDING,
+        attempts: 0,
+        response_status: None,
+        last_error: None,
+        next_attempt_at: now,
+        created_at: now,
+        delivered_at: None,
+    };
+    MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![subscription]])
+        .append_query_results([vec![delivery]])
+        .into_connection()
+}
+
+/// Payloads of the deliveries the handler queued for `event`
+async fn queued_payloads(event: EventEnvelope<Value>) -> Vec<String> {
+    let db = subscribed_db();
+    let tx = db.begin().await.unwrap();
+    let meta = RecordMeta {
+        topic: "events".to_string(),
+        partition: 0,
+        offset: 0,
+        key: None,
+        event_type: event.event_type.clone(),
+        attempt: 0,
+    };
+    WebhookEventHandler.handle(&tx, event, &meta).await.unwrap();
+    tx.commit().await.unwrap();
+
+    db.into_transaction_log()
+        .iter()
+        .flat_map(|transaction| transaction.statements().to_vec())
+        .filter(|statement| statement.sql.starts_with(r#"INSERT INTO "webhook_deliveries""#))
+        .flat_map(|statement| statement.values.map(|values| values.0).unwrap_or_default())
+        .filter_map(|value| match value {
+            SqlValue::String(Some(text)) if text.contains("specversion") => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn registration_is_not_a_default_partner_event() {
+    let defaults = WebhookConfig::default();
+
+    assert!(defaults.events.iter().all(|event| event.event_type != "user.registered"));
+    assert!(defaults.events.iter().all(|event| partner_fields(&event.event_type).is_some()));
+    assert!(partner_fields("user.registered").is_none());
+}
+
+#[tokio::test]
+async fn registration_events_are_never_queued_even_when_configured() {
+    let registered = envelope(
+        "user.registered",
+        json!({ "user_id": 1, "email": "jane@example.com", "verification_token": VERIFICATION_TOKEN }),
+    );
+
+    assert!(queued_payloads(registered).await.is_empty());
+}
+
+#[tokio::test]
+async fn signed_body_keeps_only_partner_fields_and_the_correlation_id() {
+    let (base, inbox) = start_receiver().await;
+    let created = envelope(
+        "user.created",
+        json!({ "user_id": 1, "email": "jane@example.com", "verification_token": VERIFICATION_TOKEN }),
+    );
+
+    let payloads = queued_payloads(created).await;
+    assert_eq!(payloads.len(), 1);
+    let outcome = sender()
+        .send(&WebhookRequest { payload: &payloads[0], ..request(&format!("{}/ok", base)) })
+        .await;
+    assert_eq!(outcome, DeliveryOutcome::Delivered { status: 204 });
+
+    let received = inbox.lock().unwrap().pop().expect("receiver got the webhook");
+    let timestamp: i64 = received.headers[WEBHOOK_TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+    let signature = received.headers[WEBHOOK_SIGNATURE_HEADER].to_str().unwrap();
+    assert!(verify_signature(SECRET, timestamp, &received.body, signature));
+    assert!(!received.body.contains(VERIFICATION_TOKEN), "{}", received.body);
+
+    let body: Value = serde_json::from_str(&received.body).unwrap();
+    assert_eq!(body["correlationid"], "req-42");
+    assert_eq!(body["data"], json!({ "user_id": 1, "email": "jane@example.com" }));
+}
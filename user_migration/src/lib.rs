@@ -6,6 +6,9 @@ pub mod m20251126_142840_create_user_table;
 pub mod m20251126_142841_create_address_table;
 pub mod m20251209_000000_add_email_verification_resend_tracking;
 pub mod m20251209_000001_add_login_tracking_fields;
+pub mod m20251215_000000_create_outbox_events_table;
+pub mod m20251220_000000_create_processed_events_table;
+pub mod m20251228_000000_create_webhook_tables;
 
 pub struct Migrator;
 
@@ -17,6 +20,9 @@ impl MigratorTrait for Migrator {
             Box::new(m20251126_142841_create_address_table::Migration),
             Box::new(m20251209_000000_add_email_verification_resend_tracking::Migration),
             Box::new(m20251209_000001_add_login_tracking_fields::Migration),
+            Box::new(m20251215_000000_create_outbox_events_table::Migration),
+            Box::new(m20251220_000000_create_processed_events_table::Migration),
+            Box::new(m20251228_000000_create_webhook_tables::Migration),
         ]
     }
 }
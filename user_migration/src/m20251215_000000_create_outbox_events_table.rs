@@ -0,0 +1,63 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OutboxEvents::Table)
+                    .if_not_exists()
+                    .col(big_pk_auto(OutboxEvents::Id))
+                    .col(string(OutboxEvents::Topic))
+                    .col(string(OutboxEvents::AggregateKey))
+                    .col(text(OutboxEvents::Payload))
+                    .col(json_binary(OutboxEvents::Headers))
+                    .col(string(OutboxEvents::Status).default("pending".to_string()))
+                    .col(integer(OutboxEvents::Attempts).default(0))
+                    .col(text_null(OutboxEvents::LastError))
+                    .col(timestamp(OutboxEvents::AvailableAt))
+                    .col(timestamp(OutboxEvents::CreatedAt))
+                    .col(timestamp_null(OutboxEvents::PublishedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        // The relay scans pending rows in insertion order
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_outbox_events_status_id")
+                    .table(OutboxEvents::Table)
+                    .col(OutboxEvents::Status)
+                    .col(OutboxEvents::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OutboxEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum OutboxEvents {
+    Table,
+    Id,
+    Topic,
+    AggregateKey,
+    Payload,
+    Headers,
+    Status,
+    Attempts,
+    LastError,
+    AvailableAt,
+    CreatedAt,
+    PublishedAt,
+}
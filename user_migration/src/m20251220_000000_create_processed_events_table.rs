@@ -0,0 +1,55 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Event IDs handled per consumer group, see `utils::kafka_consumer::ConsumerRuntime`
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProcessedEvents::Table)
+                    .if_not_exists()
+                    .col(string(ProcessedEvents::ConsumerGroup))
+                    .col(uuid(ProcessedEvents::EventId))
+                    .col(string(ProcessedEvents::EventType))
+                    .col(timestamp_with_time_zone(ProcessedEvents::ProcessedAt))
+                    .primary_key(
+                        Index::create()
+                            .col(ProcessedEvents::ConsumerGroup)
+                            .col(ProcessedEvents::EventId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Old rows are deleted per group by age
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_processed_events_group_processed_at")
+                    .table(ProcessedEvents::Table)
+                    .col(ProcessedEvents::ConsumerGroup)
+                    .col(ProcessedEvents::ProcessedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProcessedEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum ProcessedEvents {
+    Table,
+    ConsumerGroup,
+    EventId,
+    EventType,
+    ProcessedAt,
+}
@@ -0,0 +1,132 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookSubscriptions::Table)
+                    .if_not_exists()
+                    .col(big_pk_auto(WebhookSubscriptions::Id))
+                    .col(string(WebhookSubscriptions::Owner))
+                    .col(string_len(WebhookSubscriptions::Url, 2048))
+                    .col(string(WebhookSubscriptions::Secret))
+                    .col(json_binary(WebhookSubscriptions::EventTypes))
+                    .col(string(WebhookSubscriptions::Status).default("active".to_string()))
+                    .col(integer(WebhookSubscriptions::ConsecutiveFailures).default(0))
+                    .col(text_null(WebhookSubscriptions::DisabledReason))
+                    .col(timestamp(WebhookSubscriptions::CreatedAt))
+                    .col(timestamp(WebhookSubscriptions::UpdatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_subscriptions_owner")
+                    .table(WebhookSubscriptions::Table)
+                    .col(WebhookSubscriptions::Owner)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookDeliveries::Table)
+                    .if_not_exists()
+                    .col(big_pk_auto(WebhookDeliveries::Id))
+                    .col(big_integer(WebhookDeliveries::SubscriptionId))
+                    .col(uuid(WebhookDeliveries::EventId))
+                    .col(string(WebhookDeliveries::EventType))
+                    .col(text(WebhookDeliveries::Payload))
+                    .col(string(WebhookDeliveries::Status).default("pending".to_string()))
+                    .col(integer(WebhookDeliveries::Attempts).default(0))
+                    .col(integer_null(WebhookDeliveries::ResponseStatus))
+                    .col(text_null(WebhookDeliveries::LastError))
+                    .col(timestamp(WebhookDeliveries::NextAttemptAt))
+                    .col(timestamp(WebhookDeliveries::CreatedAt))
+                    .col(timestamp_null(WebhookDeliveries::DeliveredAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_deliveries_subscription_id")
+                            .from(WebhookDeliveries::Table, WebhookDeliveries::SubscriptionId)
+                            .to(WebhookSubscriptions::Table, WebhookSubscriptions::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The dispatcher scans pending deliveries that are due
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_deliveries_status_next_attempt_at")
+                    .table(WebhookDeliveries::Table)
+                    .col(WebhookDeliveries::Status)
+                    .col(WebhookDeliveries::NextAttemptAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // The delivery log lists a subscription's deliveries, newest first
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_deliveries_subscription_id_id")
+                    .table(WebhookDeliveries::Table)
+                    .col(WebhookDeliveries::SubscriptionId)
+                    .col(WebhookDeliveries::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebhookDeliveries::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(WebhookSubscriptions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum WebhookSubscriptions {
+    Table,
+    Id,
+    Owner,
+    Url,
+    Secret,
+    EventTypes,
+    Status,
+    ConsecutiveFailures,
+    DisabledReason,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum WebhookDeliveries {
+    Table,
+    Id,
+    SubscriptionId,
+    EventId,
+    EventType,
+    Payload,
+    Status,
+    Attempts,
+    ResponseStatus,
+    LastError,
+    NextAttemptAt,
+    CreatedAt,
+    DeliveredAt,
+}